@@ -0,0 +1,294 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+
+use garnish_lang_compiler::lex::{lex, TokenType};
+use garnish_lang_compiler::parse::{parse, Definition, ParseResult};
+
+use crate::compat::node_text;
+use crate::context::{PhraseContext, PhraseStatus, SimplePhraseContext};
+use crate::ext::ParseResultExt;
+use crate::options::ReduceOptions;
+use crate::reduce_phrases_with_options;
+use crate::span::Span;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum UnreachableReason {
+    // word isn't valid garnish source
+    LexError { word: String, message: String },
+    // word lexes to something other than a single identifier
+    NotIdentifier { word: String, lexed_as: Vec<TokenType> },
+    // words parse fine but the reducer doesn't produce this phrase from them
+    NotMatched,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ReachabilityFinding {
+    pub phrase: String,
+    pub reason: UnreachableReason,
+}
+
+impl Display for ReachabilityFinding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.reason {
+            UnreachableReason::LexError { word, message } => {
+                write!(f, "phrase '{}' can't be written, word '{}' fails to lex: {}", self.phrase, word, message)
+            }
+            UnreachableReason::NotIdentifier { word, lexed_as } => write!(
+                f,
+                "phrase '{}' can't be written, word '{}' lexes as {:?} instead of an identifier",
+                self.phrase, word, lexed_as
+            ),
+            UnreachableReason::NotMatched => write!(f, "phrase '{}' is never matched from its own words", self.phrase),
+        }
+    }
+}
+
+// checks every phrase can be produced by writing its words separated by spaces
+pub fn analyze_reachability(context: &SimplePhraseContext) -> Vec<ReachabilityFinding> {
+    let options = ReduceOptions { include_internal: true, ..ReduceOptions::default() };
+    let mut findings = vec![];
+
+    for phrase in context.complete_phrases() {
+        if let Some(reason) = check_phrase(phrase, context, &options) {
+            findings.push(ReachabilityFinding { phrase: phrase.to_string(), reason });
+        }
+    }
+
+    findings
+}
+
+fn check_phrase(phrase: &str, context: &SimplePhraseContext, options: &ReduceOptions) -> Option<UnreachableReason> {
+    let words = phrase.split('_').collect::<Vec<&str>>();
+
+    for word in words.iter() {
+        let tokens = match lex(word) {
+            Err(error) => return Some(UnreachableReason::LexError {
+                word: word.to_string(),
+                message: error.get_message().clone(),
+            }),
+            Ok(tokens) => tokens,
+        };
+
+        if tokens.len() != 1 || tokens[0].get_token_type() != TokenType::Identifier {
+            return Some(UnreachableReason::NotIdentifier {
+                word: word.to_string(),
+                lexed_as: tokens.iter().map(|token| token.get_token_type()).collect(),
+            });
+        }
+    }
+
+    let matched = lex(&words.join(" ")).ok()
+        .and_then(|tokens| parse(&tokens).ok())
+        .and_then(|parsed| reduce_phrases_with_options(&parsed, context, options).ok())
+        .map(|(_, report)| report.matches.iter().any(|m| m.phrase == phrase))
+        .unwrap_or(false);
+
+    match matched {
+        true => None,
+        false => Some(UnreachableReason::NotMatched),
+    }
+}
+
+// word sequence the dictionary splits into complete phrases more than one way
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq)]
+pub struct Ambiguity {
+    pub words: Vec<String>,
+    // each entry is one way of splitting words into phrases
+    pub segmentations: Vec<Vec<String>>,
+}
+
+impl Display for Ambiguity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let options = self.segmentations.iter()
+            .map(|segmentation| format!("[{}]", segmentation.join(", ")))
+            .collect::<Vec<String>>();
+
+        write!(f, "'{}' can be read as {}", self.words.join(" "), options.join(" or "))
+    }
+}
+
+// enumerates sequences of up to max_words words built from complete phrases
+pub fn analyze_ambiguity(context: &SimplePhraseContext, max_words: usize) -> Vec<Ambiguity> {
+    find_ambiguities(&context.complete_phrases(), max_words)
+}
+
+fn find_ambiguities(phrases: &[&str], max_words: usize) -> Vec<Ambiguity> {
+    let phrase_words = phrases.iter()
+        .map(|phrase| (phrase.to_string(), phrase.split('_').map(|word| word.to_string()).collect::<Vec<String>>()))
+        .collect::<Vec<(String, Vec<String>)>>();
+
+    let mut segmentations: BTreeMap<Vec<String>, BTreeSet<Vec<String>>> = BTreeMap::new();
+    let mut stack: Vec<(Vec<String>, Vec<String>)> = vec![(vec![], vec![])];
+
+    while let Some((words, used)) = stack.pop() {
+        for (phrase, parts) in phrase_words.iter() {
+            if words.len() + parts.len() > max_words {
+                continue;
+            }
+
+            let mut next_words = words.clone();
+            next_words.extend(parts.iter().cloned());
+            let mut next_used = used.clone();
+            next_used.push(phrase.clone());
+
+            segmentations.entry(next_words.clone()).or_default().insert(next_used.clone());
+            stack.push((next_words, next_used));
+        }
+    }
+
+    segmentations.into_iter()
+        .filter(|(_, options)| options.len() > 1)
+        // sequences that only extend a shorter ambiguity share a first or last phrase
+        .filter(|(_, options)| !shares_end(options, |s| s.first()) && !shares_end(options, |s| s.last()))
+        .map(|(words, options)| Ambiguity { words, segmentations: options.into_iter().collect() })
+        .collect()
+}
+
+fn shares_end<'a>(options: &'a BTreeSet<Vec<String>>, end: impl Fn(&'a Vec<String>) -> Option<&'a String>) -> bool {
+    let mut ends = options.iter().map(end);
+    match ends.next() {
+        None => false,
+        Some(first) => ends.all(|other| other == first),
+    }
+}
+
+// identifier a phrase could begin at, found without reducing
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PhraseStart {
+    pub node: usize,
+    pub word: String,
+    pub span: Span,
+    pub status: PhraseStatus,
+    // complete phrases beginning with the word, sorted
+    // only the word itself when the context doesn't list completions
+    pub phrases: Vec<String>,
+}
+
+// every identifier of parse_result that is a phrase or the start of one, in node order
+// the number found bounds how much work reducing has to do
+pub fn potential_phrase_starts<Context: PhraseContext + ?Sized>(parse_result: &ParseResult, context: &Context) -> Vec<PhraseStart> {
+    parse_result.indexed_nodes()
+        .filter(|(_, node)| node.get_definition() == Definition::Identifier)
+        .filter_map(|(index, node)| {
+            let word = node_text(node);
+            let status = context.get_phrase_status(&word);
+            if status == PhraseStatus::NotAPhrase {
+                return None;
+            }
+
+            let mut phrases = context.completions(&word);
+            if status.is_complete() {
+                phrases.push(word.clone());
+            }
+            phrases.sort();
+            phrases.dedup();
+
+            Some(PhraseStart { node: index, span: Span::of_node(node), word, status, phrases })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use garnish_lang_compiler::lex::{lex, TokenType};
+    use garnish_lang_compiler::parse::parse;
+
+    use crate::analysis::{analyze_ambiguity, analyze_reachability, find_ambiguities, potential_phrase_starts, Ambiguity, ReachabilityFinding, UnreachableReason};
+    use crate::context::{OverlapPolicy, PhraseStatus, SimplePhraseContext};
+    use crate::meta::{PhraseMeta, Visibility};
+
+    #[test]
+    fn all_reachable() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+        context.add_phrase("stop").unwrap();
+        context.add_phrase_with_meta("helper_task", PhraseMeta::new().with_visibility(Visibility::Internal)).unwrap();
+
+        assert!(analyze_reachability(&context).is_empty());
+    }
+
+    #[test]
+    fn number_word_unreachable() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_5task").unwrap();
+
+        assert_eq!(analyze_reachability(&context), vec![ReachabilityFinding {
+            phrase: "perform_5task".to_string(),
+            reason: UnreachableReason::NotIdentifier { word: "5task".to_string(), lexed_as: vec![TokenType::Number] },
+        }]);
+    }
+
+    #[test]
+    fn operator_word_unreachable() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task-list").unwrap();
+
+        assert_eq!(analyze_reachability(&context), vec![ReachabilityFinding {
+            phrase: "perform_task-list".to_string(),
+            reason: UnreachableReason::NotIdentifier {
+                word: "task-list".to_string(),
+                lexed_as: vec![TokenType::Identifier, TokenType::Subtraction, TokenType::Identifier],
+            },
+        }]);
+    }
+
+    #[test]
+    fn simple_context_unambiguous() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+        context.add_phrase("task_list").unwrap();
+        context.add_phrase("list").unwrap();
+
+        assert!(analyze_ambiguity(&context, 6).is_empty());
+    }
+
+    #[test]
+    fn complete_prefix_context_ambiguous() {
+        let mut context = SimplePhraseContext::new();
+        context.set_overlap_policy(OverlapPolicy::AllowCompletePrefix);
+        context.add_phrase("perform").unwrap();
+        context.add_phrase("perform_task").unwrap();
+        context.add_phrase("task").unwrap();
+
+        let words = analyze_ambiguity(&context, 3).into_iter().map(|a| a.words.join(" ")).collect::<Vec<String>>();
+
+        assert_eq!(words, vec!["perform task"]);
+    }
+
+    #[test]
+    fn overlapping_phrases_ambiguous() {
+        let ambiguities = find_ambiguities(&["a", "a_b", "b_c", "c"], 4);
+
+        let expected = Ambiguity {
+            words: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            segmentations: vec![
+                vec!["a".to_string(), "b_c".to_string()],
+                vec!["a_b".to_string(), "c".to_string()],
+            ],
+        };
+
+        assert_eq!(ambiguities, vec![expected]);
+    }
+
+    #[test]
+    fn ambiguity_limited_by_length() {
+        assert!(find_ambiguities(&["a", "a_b", "b_c", "c"], 2).is_empty());
+    }
+
+    #[test]
+    fn starts_listed_without_reducing() {
+        let mut context = SimplePhraseContext::new();
+        context.set_overlap_policy(OverlapPolicy::AllowCompletePrefix);
+        context.add_phrases(["perform_task", "perform_the_job", "stop", "stop_now"]).unwrap();
+
+        let parsed = parse(&lex("perform 5 task value stop").unwrap()).unwrap();
+        let starts = potential_phrase_starts(&parsed, &context);
+
+        let found = starts.iter().map(|start| (start.word.as_str(), start.status, start.phrases.clone())).collect::<Vec<(&str, PhraseStatus, Vec<String>)>>();
+        assert_eq!(found, vec![
+            ("perform", PhraseStatus::Incomplete, vec!["perform_task".to_string(), "perform_the_job".to_string()]),
+            ("stop", PhraseStatus::CompleteAndExtendable, vec!["stop".to_string(), "stop_now".to_string()]),
+        ]);
+        assert_eq!(parsed.get_node(starts[1].node).unwrap().get_lex_token().get_text(), "stop");
+    }
+}
@@ -0,0 +1,78 @@
+use garnish_lang_compiler::lex::{LexerToken, TokenType};
+use garnish_lang_compiler::parse::{Definition, ParseNode, ParseResult, SecondaryDefinition};
+
+use crate::compat::node_text;
+use crate::span::{Position, Span};
+
+// annotation nodes are Drop nodes like the ones garnish gives '@' annotations
+// each is parented to the apply it describes without being one of its children
+// so building the reduced tree skips them, tooling holding the result can still find them
+const MARKER: &str = "@phrase";
+
+// phrase that produced node and where its words were in the original source
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PhraseAnnotation {
+    pub node: usize,
+    pub phrase: String,
+    pub span: Span,
+}
+
+// appends an annotation for node, returns its index
+// text is "@phrase <phrase> <line>:<column>-<line>:<column>" with the zero based span
+pub(crate) fn annotate(result: &mut ParseResult, node: usize, phrase: &str, span: Span) -> usize {
+    let index = result.get_nodes().len();
+    let text = format!("{} {} {}:{}-{}:{}", MARKER, phrase, span.start.line, span.start.column, span.end.line, span.end.column);
+
+    result.add_node(ParseNode::new(
+        Definition::Drop,
+        SecondaryDefinition::Annotation,
+        Some(node),
+        None,
+        None,
+        LexerToken::new(text, TokenType::Annotation, span.start.line, span.start.column),
+    ));
+
+    index
+}
+
+// annotations of a result reduced with ReduceOptions::annotate, in node order
+pub fn phrase_annotations(result: &ParseResult) -> Vec<PhraseAnnotation> {
+    result.get_nodes().iter()
+        .filter(|node| node.get_definition() == Definition::Drop && node.get_secondary_definition() == SecondaryDefinition::Annotation)
+        .filter_map(|node| parse_annotation(node.get_parent()?, &node_text(node)))
+        .collect()
+}
+
+fn parse_annotation(node: usize, text: &str) -> Option<PhraseAnnotation> {
+    let mut parts = text.split(' ');
+    if parts.next()? != MARKER {
+        return None;
+    }
+
+    let phrase = parts.next()?.to_string();
+    let (start, end) = parts.next()?.split_once('-')?;
+
+    Some(PhraseAnnotation { node, phrase, span: Span { start: parse_position(start)?, end: parse_position(end)? } })
+}
+
+fn parse_position(text: &str) -> Option<Position> {
+    let (line, column) = text.split_once(':')?;
+    Some(Position { line: line.parse().ok()?, column: column.parse().ok()? })
+}
+
+#[cfg(test)]
+mod tests {
+    use garnish_lang_compiler::parse::ParseResult;
+
+    use crate::annotation::{annotate, phrase_annotations, PhraseAnnotation};
+    use crate::span::{Position, Span};
+
+    #[test]
+    fn annotation_read_back() {
+        let span = Span { start: Position { line: 1, column: 2 }, end: Position { line: 3, column: 14 } };
+        let mut result = ParseResult::new();
+        annotate(&mut result, 5, "perform_task", span);
+
+        assert_eq!(phrase_annotations(&result), vec![PhraseAnnotation { node: 5, phrase: "perform_task".to_string(), span }]);
+    }
+}
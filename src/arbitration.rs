@@ -0,0 +1,326 @@
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+use crate::span::Span;
+
+// a match found before any mutation, claiming its words and arguments
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MatchCandidate {
+    pub phrase: String,
+    pub priority: i64,
+    // score from usage data, only compared by arbitrate_weighted
+    pub weight: i64,
+    pub words: Vec<usize>,
+    pub arguments: Vec<usize>,
+    pub span: Span,
+}
+
+impl MatchCandidate {
+    pub fn new(phrase: &str, words: Vec<usize>, arguments: Vec<usize>, span: Span) -> Self {
+        MatchCandidate { phrase: phrase.to_string(), priority: 0, weight: 0, words, arguments, span }
+    }
+
+    pub fn with_priority(mut self, priority: i64) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn with_weight(mut self, weight: i64) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    pub fn overlaps(&self, other: &MatchCandidate) -> bool {
+        self.claims().any(|node| other.claims().any(|other| other == node))
+    }
+
+    fn claims(&self) -> impl Iterator<Item = &usize> {
+        self.words.iter().chain(self.arguments.iter())
+    }
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Arbitration {
+    // non overlapping candidates in source order
+    pub accepted: Vec<MatchCandidate>,
+    // dropped candidates paired with the phrase of the accepted candidate they collided with
+    pub rejected: Vec<(MatchCandidate, String)>,
+    // other sets of candidates scoring the same total as accepted, only filled by arbitrate_weighted
+    pub ties: Vec<Vec<MatchCandidate>>,
+}
+
+// resolves overlapping candidates so only one rewrite touches any node
+// preference: higher priority, then more words, then earlier start, then phrase text
+pub fn arbitrate(mut candidates: Vec<MatchCandidate>) -> Arbitration {
+    candidates.sort_by(|a, b| {
+        b.priority.cmp(&a.priority)
+            .then(b.words.len().cmp(&a.words.len()))
+            .then(a.span.start.cmp(&b.span.start))
+            .then(a.phrase.cmp(&b.phrase))
+    });
+
+    let mut arbitration = Arbitration::default();
+
+    for candidate in candidates {
+        match arbitration.accepted.iter().find(|accepted| accepted.overlaps(&candidate)) {
+            None => arbitration.accepted.push(candidate),
+            Some(winner) => {
+                let winner = winner.phrase.clone();
+                arbitration.rejected.push((candidate, winner));
+            }
+        }
+    }
+
+    arbitration.accepted.sort_by_key(|candidate| candidate.span);
+
+    arbitration
+}
+
+// accepts the non overlapping candidates with the highest total weight
+// equal totals go to the set arbitrate's preference reaches first and the rest are reported as ties
+// tries every combination, meant for the handful of candidates competing for the same words
+pub fn arbitrate_weighted(mut candidates: Vec<MatchCandidate>) -> Arbitration {
+    candidates.sort_by(|a, b| {
+        b.priority.cmp(&a.priority)
+            .then(b.words.len().cmp(&a.words.len()))
+            .then(a.span.start.cmp(&b.span.start))
+            .then(a.phrase.cmp(&b.phrase))
+    });
+
+    let mut best: Vec<(i64, Vec<usize>)> = vec![];
+    let mut chosen = vec![];
+    best_sets(&candidates, 0, &mut chosen, &mut best);
+
+    let mut sets = best.into_iter().map(|(_, set)| set);
+    let accepted = sets.next().unwrap_or_default();
+    let ties = sets
+        .map(|set| {
+            let mut tied = set.iter().map(|index| candidates[*index].clone()).collect::<Vec<MatchCandidate>>();
+            tied.sort_by_key(|candidate| candidate.span);
+            tied
+        })
+        .collect();
+
+    let mut arbitration = Arbitration { ties, ..Arbitration::default() };
+    for (index, candidate) in candidates.iter().enumerate() {
+        match accepted.contains(&index) {
+            true => arbitration.accepted.push(candidate.clone()),
+            false => {
+                let winner = accepted.iter().map(|index| &candidates[*index]).find(|accepted| accepted.overlaps(candidate));
+                arbitration.rejected.push((candidate.clone(), winner.map(|winner| winner.phrase.clone()).unwrap_or_default()));
+            }
+        }
+    }
+    arbitration.accepted.sort_by_key(|candidate| candidate.span);
+
+    arbitration
+}
+
+// collects maximal non overlapping sets with the highest total, in the order they're found
+fn best_sets(candidates: &[MatchCandidate], next: usize, chosen: &mut Vec<usize>, best: &mut Vec<(i64, Vec<usize>)>) {
+    if next == candidates.len() {
+        // a set one more candidate fits into isn't a separate reading
+        let maximal = candidates.iter().enumerate().all(|(index, candidate)| {
+            chosen.contains(&index) || chosen.iter().any(|chosen| candidates[*chosen].overlaps(candidate))
+        });
+        if !maximal {
+            return;
+        }
+
+        let total = chosen.iter().map(|index| candidates[*index].weight).sum::<i64>();
+        match best.first().map(|(best_total, _)| *best_total) {
+            Some(best_total) if total < best_total => (),
+            Some(best_total) if total == best_total => best.push((total, chosen.clone())),
+            _ => *best = vec![(total, chosen.clone())],
+        }
+        return;
+    }
+
+    if !chosen.iter().any(|index| candidates[*index].overlaps(&candidates[next])) {
+        chosen.push(next);
+        best_sets(candidates, next + 1, chosen, best);
+        chosen.pop();
+    }
+    best_sets(candidates, next + 1, chosen, best);
+}
+
+// picks one of several valid interpretations of the same words, None leaves the choice to arbitrate
+pub trait Disambiguator {
+    fn choose(&self, candidates: &[MatchCandidate]) -> Option<usize>;
+}
+
+impl<F: Fn(&[MatchCandidate]) -> Option<usize>> Disambiguator for F {
+    fn choose(&self, candidates: &[MatchCandidate]) -> Option<usize> {
+        self(candidates)
+    }
+}
+
+// how the matcher chooses between a phrase and a longer phrase starting with it
+// or between several phrases an unterminated phrase could be completed to
+#[derive(Clone, Default)]
+pub enum Disambiguation {
+    // first candidate accepted by arbitrate, ambiguous completions are left unterminated
+    #[default]
+    Arbitrate,
+    // highest weight, ties go to arbitrate and are reported
+    Weighted,
+    // asks first, e.g. an editor prompting the user
+    Custom(Arc<dyn Disambiguator + Send + Sync>),
+}
+
+impl Debug for Disambiguation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Disambiguation::Arbitrate => write!(f, "Arbitrate"),
+            Disambiguation::Weighted => write!(f, "Weighted"),
+            Disambiguation::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
+impl Disambiguation {
+    // index of the chosen candidate, None when nothing was chosen
+    pub(crate) fn choose(&self, candidates: &[MatchCandidate]) -> Option<usize> {
+        let custom = match self {
+            Disambiguation::Arbitrate | Disambiguation::Weighted => None,
+            Disambiguation::Custom(disambiguator) => disambiguator.choose(candidates).filter(|index| *index < candidates.len()),
+        };
+
+        custom.or_else(|| {
+            let accepted = match self {
+                Disambiguation::Weighted => arbitrate_weighted(candidates.to_vec()).accepted,
+                _ => arbitrate(candidates.to_vec()).accepted,
+            };
+            let first = accepted.first()?;
+            candidates.iter().position(|candidate| candidate == first)
+        })
+    }
+
+    // phrases of candidates scoring the same as the chosen one, empty unless weighted
+    pub(crate) fn ties(&self, candidates: &[MatchCandidate]) -> Vec<String> {
+        match self {
+            Disambiguation::Weighted => {
+                let arbitration = arbitrate_weighted(candidates.to_vec());
+                let mut phrases = arbitration.accepted.iter()
+                    .chain(arbitration.ties.iter().flatten())
+                    .map(|candidate| candidate.phrase.clone())
+                    .collect::<Vec<String>>();
+
+                match arbitration.ties.is_empty() {
+                    true => vec![],
+                    false => {
+                        phrases.sort();
+                        phrases.dedup();
+                        phrases
+                    }
+                }
+            }
+            _ => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::arbitration::{arbitrate, arbitrate_weighted, Disambiguation, MatchCandidate};
+    use crate::span::{Position, Span};
+
+    fn span(start: usize, end: usize) -> Span {
+        Span { start: Position { line: 0, column: start }, end: Position { line: 0, column: end } }
+    }
+
+    #[test]
+    fn disjoint_candidates_all_accepted() {
+        let first = MatchCandidate::new("perform_task", vec![0, 2], vec![], span(0, 12));
+        let second = MatchCandidate::new("stop", vec![4], vec![], span(13, 17));
+
+        let arbitration = arbitrate(vec![second.clone(), first.clone()]);
+
+        assert_eq!(arbitration.accepted, vec![first, second]);
+        assert!(arbitration.rejected.is_empty());
+    }
+
+    #[test]
+    fn longer_candidate_wins() {
+        let short = MatchCandidate::new("perform", vec![0], vec![], span(0, 7));
+        let long = MatchCandidate::new("perform_task", vec![0, 2], vec![], span(0, 12));
+
+        let arbitration = arbitrate(vec![short.clone(), long.clone()]);
+
+        assert_eq!(arbitration.accepted, vec![long]);
+        assert_eq!(arbitration.rejected, vec![(short, "perform_task".to_string())]);
+    }
+
+    #[test]
+    fn priority_beats_length() {
+        let short = MatchCandidate::new("perform", vec![0], vec![], span(0, 7)).with_priority(1);
+        let long = MatchCandidate::new("perform_task", vec![0, 2], vec![], span(0, 12));
+
+        let arbitration = arbitrate(vec![long, short.clone()]);
+
+        assert_eq!(arbitration.accepted, vec![short]);
+    }
+
+    #[test]
+    fn earlier_candidate_wins_tie() {
+        let first = MatchCandidate::new("a_b", vec![0, 2], vec![], span(0, 3));
+        let second = MatchCandidate::new("b_c", vec![2, 4], vec![], span(2, 5));
+
+        let arbitration = arbitrate(vec![second.clone(), first.clone()]);
+
+        assert_eq!(arbitration.accepted, vec![first]);
+        assert_eq!(arbitration.rejected, vec![(second, "a_b".to_string())]);
+    }
+
+    #[test]
+    fn argument_claims_overlap() {
+        let outer = MatchCandidate::new("perform_task", vec![0, 4], vec![2], span(0, 14));
+        let inner = MatchCandidate::new("five", vec![2], vec![], span(8, 12));
+
+        assert!(outer.overlaps(&inner));
+    }
+
+    #[test]
+    fn highest_total_weight_wins() {
+        let first = MatchCandidate::new("a_b", vec![0, 2], vec![], span(0, 3)).with_weight(2);
+        let second = MatchCandidate::new("b_c", vec![2, 4], vec![], span(2, 5)).with_weight(5);
+        let third = MatchCandidate::new("c_d", vec![4, 6], vec![], span(4, 7)).with_weight(4);
+
+        let arbitration = arbitrate_weighted(vec![first.clone(), second.clone(), third.clone()]);
+
+        // a_b + c_d scores 6, b_c alone 5
+        assert_eq!(arbitration.accepted, vec![first, third]);
+        assert_eq!(arbitration.rejected, vec![(second, "a_b".to_string())]);
+        assert!(arbitration.ties.is_empty());
+    }
+
+    #[test]
+    fn equal_weights_tied() {
+        let short = MatchCandidate::new("perform", vec![0], vec![], span(0, 7)).with_weight(3);
+        let long = MatchCandidate::new("perform_task", vec![0, 2], vec![], span(0, 12)).with_weight(3);
+
+        let arbitration = arbitrate_weighted(vec![short.clone(), long.clone()]);
+
+        assert_eq!(arbitration.accepted, vec![long.clone()]);
+        assert_eq!(arbitration.ties, vec![vec![short.clone()]]);
+        assert_eq!(Disambiguation::Weighted.ties(&[long, short]), vec!["perform".to_string(), "perform_task".to_string()]);
+    }
+
+    #[test]
+    fn custom_disambiguation_asked_first() {
+        let short = MatchCandidate::new("perform", vec![0], vec![], span(0, 7));
+        let long = MatchCandidate::new("perform_task", vec![0, 2], vec![], span(0, 12));
+        let candidates = vec![long, short];
+
+        let shortest = Disambiguation::Custom(Arc::new(|candidates: &[MatchCandidate]| {
+            candidates.iter().position(|candidate| candidate.words.len() == 1)
+        }));
+        let undecided = Disambiguation::Custom(Arc::new(|_: &[MatchCandidate]| None));
+
+        assert_eq!(Disambiguation::Arbitrate.choose(&candidates), Some(0));
+        assert_eq!(shortest.choose(&candidates), Some(1));
+        assert_eq!(undecided.choose(&candidates), Some(0));
+    }
+}
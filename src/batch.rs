@@ -0,0 +1,89 @@
+use garnish_lang_compiler::parse::ParseResult;
+
+use crate::cache::{CacheStats, CachingPhraseContext};
+use crate::context::PhraseContext;
+use crate::error::PhraseError;
+use crate::options::ReduceOptions;
+use crate::report::ReduceReport;
+
+// results of reducing many parse results against one context, e.g. every file of a build
+#[derive(Debug)]
+pub struct BatchReduction {
+    // one per input, in input order
+    pub results: Vec<Result<(ParseResult, ReduceReport), PhraseError>>,
+    // matches, warnings, errors and unfinished phrases of every input that reduced, in input order
+    // node indices of a match are indices into the result of the input at the same position in match_inputs
+    pub combined: ReduceReport,
+    pub match_inputs: Vec<usize>,
+    // status lookups shared across inputs
+    pub cache: CacheStats,
+}
+
+impl BatchReduction {
+    pub fn failures(&self) -> impl Iterator<Item = (usize, &PhraseError)> {
+        self.results.iter().enumerate().filter_map(|(input, result)| result.as_ref().err().map(|error| (input, error)))
+    }
+}
+
+pub fn reduce_all<Context: PhraseContext + ?Sized>(inputs: &[ParseResult], context: &Context) -> BatchReduction {
+    reduce_all_with_options(inputs, context, &ReduceOptions::default())
+}
+
+// every input is reduced even when an earlier one fails
+// status lookups are cached once for the whole batch instead of per input
+pub fn reduce_all_with_options<Context: PhraseContext + ?Sized>(inputs: &[ParseResult], context: &Context, options: &ReduceOptions) -> BatchReduction {
+    let context = CachingPhraseContext::new(context);
+    let mut combined = ReduceReport::default();
+    let mut match_inputs = vec![];
+
+    let results = inputs.iter()
+        .enumerate()
+        .map(|(input, parse_result)| {
+            let reduced = crate::reduce_phrases_with_options(parse_result, &context, options);
+            if let Ok((_, report)) = &reduced {
+                match_inputs.extend(std::iter::repeat_n(input, report.matches.len()));
+                combined.matches.extend(report.matches.iter().cloned());
+                combined.warnings.extend(report.warnings.iter().cloned());
+                combined.errors.extend(report.errors.iter().cloned());
+                combined.unfinished.extend(report.unfinished.iter().cloned());
+                match (&mut combined.profile, report.profile) {
+                    (Some(total), Some(profile)) => total.add(&profile),
+                    (total, profile) => *total = total.or(profile),
+                }
+            }
+
+            reduced
+        })
+        .collect();
+
+    BatchReduction { results, combined, match_inputs, cache: context.stats() }
+}
+
+#[cfg(test)]
+mod tests {
+    use garnish_lang_compiler::lex::lex;
+    use garnish_lang_compiler::parse::parse;
+
+    use crate::batch::reduce_all;
+    use crate::context::SimplePhraseContext;
+    use crate::reduce_phrases;
+
+    #[test]
+    fn every_input_reduced_with_combined_report() {
+        let inputs = ["perform 5 task", "value 5", "perform 6 task, halt now"]
+            .map(|input| parse(&lex(input).unwrap()).unwrap());
+        let mut context = SimplePhraseContext::new();
+        context.add_phrases(["perform_task", "halt_now"]).unwrap();
+
+        let batch = reduce_all(&inputs, &context);
+
+        for (input, result) in inputs.iter().zip(&batch.results) {
+            assert_eq!(result.as_ref().unwrap().0, reduce_phrases(input, &context).unwrap());
+        }
+        assert_eq!(batch.combined.phrase_counts().into_iter().collect::<Vec<(&str, usize)>>(), vec![("halt_now", 1), ("perform_task", 2)]);
+        assert_eq!(batch.match_inputs.len(), 3);
+        assert_eq!(batch.match_inputs[0], 0);
+        assert!(batch.cache.hits > 0);
+        assert_eq!(batch.failures().count(), 0);
+    }
+}
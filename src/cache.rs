@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::context::{PhraseContext, PhraseStatus};
+use crate::meta::PhraseMeta;
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct CacheStats {
+    pub hits: usize,
+    pub misses: usize,
+    // times the cache was cleared, by invalidate or a changed fingerprint
+    pub invalidations: usize,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    statuses: HashMap<String, PhraseStatus>,
+    fingerprint: Option<u64>,
+    stats: CacheStats,
+}
+
+// memoizes status lookups of a context that is expensive to ask, layered, remote or computed
+// changes to the inner context aren't seen until validate or invalidate is called
+#[derive(Debug)]
+pub struct CachingPhraseContext<Context: PhraseContext> {
+    inner: Context,
+    state: Mutex<CacheState>,
+}
+
+impl<Context: PhraseContext> CachingPhraseContext<Context> {
+    pub fn new(inner: Context) -> Self {
+        let fingerprint = inner.fingerprint();
+        CachingPhraseContext { inner, state: Mutex::new(CacheState { fingerprint, ..CacheState::default() }) }
+    }
+
+    pub fn inner(&self) -> &Context {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> Context {
+        self.inner
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.state().stats
+    }
+
+    // clears the cache when the inner context's fingerprint changed, returns whether it did
+    // contexts without a fingerprint are never considered changed
+    pub fn validate(&self) -> bool {
+        let fingerprint = self.inner.fingerprint();
+        let mut state = self.state();
+        if fingerprint.is_none() || fingerprint == state.fingerprint {
+            return false;
+        }
+
+        state.fingerprint = fingerprint;
+        state.statuses.clear();
+        state.stats.invalidations += 1;
+        true
+    }
+
+    pub fn invalidate(&self) {
+        let mut state = self.state();
+        state.fingerprint = self.inner.fingerprint();
+        state.statuses.clear();
+        state.stats.invalidations += 1;
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, CacheState> {
+        // cached statuses are only inserted whole, a panic elsewhere can't leave one half written
+        self.state.lock().unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+impl<Context: PhraseContext> PhraseContext for CachingPhraseContext<Context> {
+    fn get_phrase_status(&self, s: &str) -> PhraseStatus {
+        {
+            let mut state = self.state();
+            if let Some(status) = state.statuses.get(s).copied() {
+                state.stats.hits += 1;
+                return status;
+            }
+        }
+
+        // inner lookup is done unlocked so slow contexts don't block other readers
+        let status = self.inner.get_phrase_status(s);
+        let mut state = self.state();
+        state.stats.misses += 1;
+        state.statuses.insert(s.to_string(), status);
+        status
+    }
+
+    fn get_phrase_meta(&self, s: &str) -> Option<PhraseMeta> {
+        self.inner.get_phrase_meta(s)
+    }
+
+    fn get_versioned_meta(&self, s: &str, version: &str) -> Option<PhraseMeta> {
+        self.inner.get_versioned_meta(s, version)
+    }
+
+    fn get_overloads(&self, s: &str) -> Vec<PhraseMeta> {
+        self.inner.get_overloads(s)
+    }
+
+    fn completions(&self, prefix: &str) -> Vec<String> {
+        self.inner.completions(prefix)
+    }
+
+    fn fingerprint(&self) -> Option<u64> {
+        self.inner.fingerprint()
+    }
+
+    fn separator(&self) -> Option<String> {
+        self.inner.separator()
+    }
+
+    fn free_order_phrase(&self, s: &str) -> Option<String> {
+        self.inner.free_order_phrase(s)
+    }
+
+    fn alias_target(&self, s: &str) -> Option<String> {
+        self.inner.alias_target(s)
+    }
+
+    fn iter_phrases(&self) -> Box<dyn Iterator<Item = String> + '_> {
+        self.inner.iter_phrases()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use crate::cache::{CacheStats, CachingPhraseContext};
+    use crate::context::{PhraseContext, PhraseStatus, SimplePhraseContext};
+
+    struct CountingContext {
+        lookups: Cell<usize>,
+        fingerprint: Cell<u64>,
+    }
+
+    impl PhraseContext for CountingContext {
+        fn get_phrase_status(&self, s: &str) -> PhraseStatus {
+            self.lookups.set(self.lookups.get() + 1);
+            match s {
+                "perform" => PhraseStatus::Incomplete,
+                _ => PhraseStatus::NotAPhrase,
+            }
+        }
+
+        fn fingerprint(&self) -> Option<u64> {
+            Some(self.fingerprint.get())
+        }
+    }
+
+    #[test]
+    fn repeated_lookups_cached() {
+        let context = CachingPhraseContext::new(CountingContext { lookups: Cell::new(0), fingerprint: Cell::new(1) });
+
+        assert_eq!(context.get_phrase_status("perform"), PhraseStatus::Incomplete);
+        assert_eq!(context.get_phrase_status("perform"), PhraseStatus::Incomplete);
+        assert_eq!(context.get_phrase_status("task"), PhraseStatus::NotAPhrase);
+
+        assert_eq!(context.inner().lookups.get(), 2);
+        assert_eq!(context.stats(), CacheStats { hits: 1, misses: 2, invalidations: 0 });
+    }
+
+    #[test]
+    fn validate_clears_on_changed_fingerprint() {
+        let context = CachingPhraseContext::new(CountingContext { lookups: Cell::new(0), fingerprint: Cell::new(1) });
+        context.get_phrase_status("perform");
+
+        assert!(!context.validate());
+
+        context.inner().fingerprint.set(2);
+        assert!(context.validate());
+
+        context.get_phrase_status("perform");
+        assert_eq!(context.inner().lookups.get(), 2);
+        assert_eq!(context.stats().invalidations, 1);
+    }
+
+    #[test]
+    fn reduce_through_cache() {
+        let tokens = garnish_lang_compiler::lex::lex("perform 5 task").unwrap();
+        let parsed = garnish_lang_compiler::parse::parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+        let cached = CachingPhraseContext::new(context.clone());
+
+        assert_eq!(crate::reduce_phrases(&parsed, &cached).unwrap(), crate::reduce_phrases(&parsed, &context).unwrap());
+        assert_eq!(cached.fingerprint(), Some(context.fingerprint()));
+    }
+}
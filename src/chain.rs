@@ -0,0 +1,159 @@
+use garnish_lang_compiler::lex::{LexerToken, TokenType};
+use garnish_lang_compiler::parse::{Definition, ParseResult, SecondaryDefinition};
+
+use crate::compat::node_text;
+use crate::tree::{add_joining_node, copy_subtree};
+
+// a top level list split on the chaining word
+// each segment is a standalone parse result so phrases in it span a whole list
+pub(crate) struct Chain {
+    pub segments: Vec<ParseResult>,
+    // tokens of the chaining words, used for nodes joining segments
+    pub links: Vec<LexerToken>,
+}
+
+// None when the root isn't a list containing the chaining word
+pub(crate) fn split_chain(parse_result: &ParseResult, word: &str) -> Option<Chain> {
+    let items = list_items(parse_result)?;
+
+    let mut segments = vec![];
+    let mut links = vec![];
+    let mut current = vec![];
+
+    for item in items {
+        let node = parse_result.get_node(item)?;
+        if node.get_definition() == Definition::Identifier && node_text(node) == word {
+            links.push(node.get_lex_token());
+            segments.push(std::mem::take(&mut current));
+        } else {
+            current.push(item);
+        }
+    }
+    segments.push(current);
+
+    // leading, trailing or doubled chaining word isn't a chain
+    if links.is_empty() || segments.iter().any(|segment| segment.is_empty()) {
+        return None;
+    }
+
+    let segments = segments.iter()
+        .map(|items| build_segment(parse_result, items))
+        .collect::<Option<Vec<ParseResult>>>()?;
+
+    Some(Chain { segments, links })
+}
+
+// items of a left leaning list at the root, in source order
+fn list_items(parse_result: &ParseResult) -> Option<Vec<usize>> {
+    let mut current = parse_result.get_root();
+    let mut items = vec![];
+
+    loop {
+        let node = parse_result.get_node(current)?;
+        if node.get_definition() != Definition::List {
+            items.push(current);
+            break;
+        }
+
+        items.push(node.get_right()?);
+        current = node.get_left()?;
+    }
+
+    match items.len() {
+        1 => None,
+        _ => {
+            items.reverse();
+            Some(items)
+        }
+    }
+}
+
+fn build_segment(source: &ParseResult, items: &[usize]) -> Option<ParseResult> {
+    let mut segment = ParseResult::new();
+    let mut root = copy_subtree(source, items[0], &mut segment)?;
+
+    for item in items.iter().skip(1) {
+        let right = copy_subtree(source, *item, &mut segment)?;
+        let token = source.get_node(*item)?.get_lex_token();
+        root = add_joining_node(&mut segment, Definition::List, root, right, " ", TokenType::Whitespace, &token);
+    }
+
+    segment.set_root(root);
+    Some(segment)
+}
+
+// feeds previous into the segment rooted at current
+// phrase applications get previous as their first argument
+// anything else is applied to previous directly
+pub(crate) fn pipe(
+    result: &mut ParseResult,
+    previous: usize,
+    current: usize,
+    is_phrase: bool,
+    link: &LexerToken,
+) -> Option<usize> {
+    let node = result.get_node(current)?;
+
+    let arguments_side = match (is_phrase, node.get_definition()) {
+        (true, Definition::EmptyApply) => {
+            // no arguments, previous becomes the only one
+            let phrase = node.get_left()?;
+            let node = result.get_node_mut(current)?;
+            node.set_definition(Definition::ApplyTo);
+            node.set_secondary_definition(SecondaryDefinition::BinaryLeftToRight);
+            node.set_left(Some(previous));
+            node.set_right(Some(phrase));
+            result.get_node_mut(previous)?.set_parent(Some(current));
+            return Some(current);
+        }
+        (true, Definition::ApplyTo) | (true, Definition::Access) => true,
+        (true, Definition::Apply) => false,
+        _ => return Some(add_joining_node(result, Definition::ApplyTo, previous, current, "~>", TokenType::ApplyTo, link)),
+    };
+
+    // first argument is the leftmost item of the argument list
+    let mut first = match arguments_side {
+        true => node.get_left()?,
+        false => node.get_right()?,
+    };
+    while result.get_node(first)?.get_definition() == Definition::List {
+        first = result.get_node(first)?.get_left()?;
+    }
+
+    let parent = result.get_node(first)?.get_parent()?;
+    let list = add_joining_node(result, Definition::List, previous, first, " ", TokenType::Whitespace, link);
+    result.get_node_mut(list)?.set_parent(Some(parent));
+
+    let parent_node = result.get_node_mut(parent)?;
+    match parent_node.get_left() == Some(first) {
+        true => parent_node.set_left(Some(list)),
+        false => parent_node.set_right(Some(list)),
+    }
+
+    Some(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use garnish_lang_compiler::lex::lex;
+    use garnish_lang_compiler::parse::parse;
+
+    use crate::chain::split_chain;
+
+    #[test]
+    fn splits_on_word() {
+        let parsed = parse(&lex("load file then parse 5 content then print").unwrap()).unwrap();
+
+        let chain = split_chain(&parsed, "then").unwrap();
+
+        assert_eq!(chain.segments.iter().map(|s| s.get_nodes().len()).collect::<Vec<usize>>(), vec![3, 5, 1]);
+        assert_eq!(chain.links.len(), 2);
+    }
+
+    #[test]
+    fn no_chain_without_word() {
+        let parsed = parse(&lex("load file").unwrap()).unwrap();
+
+        assert!(split_chain(&parsed, "then").is_none());
+    }
+}
@@ -0,0 +1,50 @@
+// smooths over api differences between supported garnish_lang_compiler versions
+
+use std::borrow::Cow;
+
+use garnish_lang_compiler::parse::{ParseNode, SecondaryDefinition};
+
+#[cfg(feature = "compiler-0-0-24")]
+pub(crate) fn node_text(node: &ParseNode) -> String {
+    node.text().to_string()
+}
+
+#[cfg(not(feature = "compiler-0-0-24"))]
+pub(crate) fn node_text(node: &ParseNode) -> String {
+    node.get_lex_token().get_text().clone()
+}
+
+// text of node for lookups, borrowed where the compiler allows it
+#[cfg(feature = "compiler-0-0-24")]
+pub(crate) fn node_str(node: &ParseNode) -> Cow<'_, str> {
+    Cow::Borrowed(node.text())
+}
+
+// 0.0.18 only hands out a copy of the token
+#[cfg(not(feature = "compiler-0-0-24"))]
+pub(crate) fn node_str(node: &ParseNode) -> Cow<'_, str> {
+    Cow::Owned(node.get_lex_token().get_text().clone())
+}
+
+// associativity the parser gives pair nodes
+#[cfg(feature = "compiler-0-0-24")]
+pub(crate) const PAIR_SECONDARY: SecondaryDefinition = SecondaryDefinition::BinaryRightToLeft;
+
+#[cfg(not(feature = "compiler-0-0-24"))]
+pub(crate) const PAIR_SECONDARY: SecondaryDefinition = SecondaryDefinition::BinaryLeftToRight;
+
+#[cfg(test)]
+mod tests {
+    use garnish_lang_compiler::lex::lex;
+    use garnish_lang_compiler::parse::parse;
+
+    use crate::compat::node_text;
+
+    #[test]
+    fn text_of_node() {
+        let tokens = lex("perform").unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        assert_eq!(node_text(parsed.get_node(0).unwrap()), "perform");
+    }
+}
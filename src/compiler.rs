@@ -0,0 +1,23 @@
+// front end of the garnish_lang_compiler version this crate was built against
+// using these keeps lexed and parsed input in step with what the reducer expects
+
+pub use garnish_lang_compiler::error::CompilerError;
+pub use garnish_lang_compiler::lex::{lex, LexerToken, TokenType};
+pub use garnish_lang_compiler::parse::{parse, Definition, ParseNode, ParseResult, SecondaryDefinition};
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::{lex, parse};
+    use crate::context::SimplePhraseContext;
+    use crate::reduce_phrases;
+
+    #[test]
+    fn reduce_with_reexported_front_end() {
+        let parsed = parse(&lex("perform 5 task").unwrap()).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+
+        assert!(reduce_phrases(&parsed, &context).is_ok());
+    }
+}
@@ -1,201 +1,1841 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
 
-#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
+use thiserror::Error;
+
+use crate::fingerprint::Fingerprinter;
+use crate::meta::{Deprecation, PhraseMeta, PhraseSource};
+use crate::normalize::identifier_words;
+use crate::reserved::{reserved_kind, CollisionSeverity, ReservedKind};
+
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Error)]
+#[non_exhaustive]
 pub enum SimpleContextCodes {
+    #[error("phrase is already registered as the start of a longer phrase")]
     IncompleteVersionExists,
+    #[error("start of phrase is already registered as a complete phrase")]
     CompleteVersionExists,
+    #[error("single word phrase collides with a reserved word")]
+    ReservedWord,
+    #[error("words of phrase can be matched in the same order as another phrase")]
+    FreeOrderConflict,
+    #[error("free order phrase has more words than can be rearranged")]
+    TooManyFreeWords,
+    #[error("template has an argument slot before its first word")]
+    LeadingSlot,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Error)]
+#[error("could not add phrase '{phrase}', conflicts with '{conflict}'{}", describe_source(.conflict_source))]
+pub struct ContextError {
+    pub phrase: String,
+    // already registered phrase that prevented the add
+    pub conflict: String,
+    pub conflict_source: Option<PhraseSource>,
+    #[source]
+    pub code: SimpleContextCodes,
+}
+
+// every failure from adding a batch of phrases
+#[derive(Debug, Clone, Eq, PartialEq, Error)]
+#[error("could not add {} phrase(s): {}", .0.len(), describe_errors(.0))]
+pub struct ContextErrors(pub Vec<ContextError>);
+
+fn describe_errors(errors: &[ContextError]) -> String {
+    errors.iter().map(|error| error.to_string()).collect::<Vec<String>>().join("; ")
+}
+
+fn describe_source(source: &Option<PhraseSource>) -> String {
+    match source {
+        None => String::new(),
+        Some(source) => format!(" defined by {}", source),
+    }
+}
+
+// how add_phrase treats a phrase that is also the start of another phrase
+#[derive(Debug, Copy, Clone, Default, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum OverlapPolicy {
+    // overlaps are still allowed where either phrase has a priority to settle them, see PhraseMeta::priority
+    #[default]
+    Strict,
+    // record the phrase as complete and a prefix
+    // status lookups report it as CompleteAndExtendable and the longest phrase written is matched
+    AllowCompletePrefix,
+}
+
+// order phrases, iter, dictionaries built from the context and conflict reports list complete phrases in
+#[derive(Debug, Copy, Clone, Default, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum PhraseOrder {
+    #[default]
+    Sorted,
+    // order each phrase was first added in, for output that follows a hand written dictionary
+    Insertion,
+}
+
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum ContextWarning {
+    // single word phrase that changes the meaning of ordinary looking code
+    ReservedWord { phrase: String, kind: ReservedKind },
+}
+
+impl Display for ContextWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContextWarning::ReservedWord { phrase, kind } => {
+                write!(f, "phrase '{}' collides with garnish {}", phrase, kind)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
+pub enum PhraseStatus {
+    Incomplete,
+    Complete,
+    // a phrase on its own and the start of a longer one
+    CompleteAndExtendable,
+    NotAPhrase,
+}
+
+impl PhraseStatus {
+    // phrase can end at this word
+    pub fn is_complete(&self) -> bool {
+        matches!(self, PhraseStatus::Complete | PhraseStatus::CompleteAndExtendable)
+    }
+
+    // more words can follow
+    pub fn is_extendable(&self) -> bool {
+        matches!(self, PhraseStatus::Incomplete | PhraseStatus::CompleteAndExtendable)
+    }
+
+    // status of the same text looked up in two contexts at once
+    pub(crate) fn merge(self, other: PhraseStatus) -> PhraseStatus {
+        match (self.is_complete() || other.is_complete(), self.is_extendable() || other.is_extendable()) {
+            (true, true) => PhraseStatus::CompleteAndExtendable,
+            (true, false) => PhraseStatus::Complete,
+            (false, true) => PhraseStatus::Incomplete,
+            (false, false) => PhraseStatus::NotAPhrase,
+        }
+    }
+}
+
+pub trait PhraseContext {
+    fn get_phrase_status(&self, s: &str) -> PhraseStatus;
+
+    fn get_phrase_meta(&self, _s: &str) -> Option<PhraseMeta> {
+        None
+    }
+
+    // meta the phrase was registered with for version, None falls back to get_phrase_meta
+    fn get_versioned_meta(&self, _s: &str, _version: &str) -> Option<PhraseMeta> {
+        None
+    }
+
+    // meta of each arity the phrase is overloaded for, the reducer picks one by the arguments given
+    fn get_overloads(&self, _s: &str) -> Vec<PhraseMeta> {
+        vec![]
+    }
+
+    // complete phrases that start with the words of prefix, used to recover unterminated phrases
+    fn completions(&self, _prefix: &str) -> Vec<String> {
+        vec![]
+    }
+
+    // changes whenever the registered phrases do, None for contexts that can't tell
+    fn fingerprint(&self) -> Option<u64> {
+        None
+    }
+
+    // joins the words of emitted identifiers, None for underscores
+    fn separator(&self) -> Option<String> {
+        None
+    }
+
+    // phrase s matches when s is another order of a free order phrase's words, None otherwise
+    fn free_order_phrase(&self, _s: &str) -> Option<String> {
+        None
+    }
+
+    // phrase s is reduced as when s was registered as an alias of it, None otherwise
+    fn alias_target(&self, _s: &str) -> Option<String> {
+        None
+    }
+
+    // complete phrases in a fixed order, empty for contexts that can't list them
+    fn iter_phrases(&self) -> Box<dyn Iterator<Item = String> + '_> {
+        Box::new(std::iter::empty())
+    }
+}
+
+// boxed and shared providers, loaded by plugins as trait objects, are contexts themselves
+impl<T: PhraseContext + ?Sized> PhraseContext for Box<T> {
+    fn get_phrase_status(&self, s: &str) -> PhraseStatus {
+        (**self).get_phrase_status(s)
+    }
+
+    fn get_phrase_meta(&self, s: &str) -> Option<PhraseMeta> {
+        (**self).get_phrase_meta(s)
+    }
+
+    fn get_versioned_meta(&self, s: &str, version: &str) -> Option<PhraseMeta> {
+        (**self).get_versioned_meta(s, version)
+    }
+
+    fn get_overloads(&self, s: &str) -> Vec<PhraseMeta> {
+        (**self).get_overloads(s)
+    }
+
+    fn completions(&self, prefix: &str) -> Vec<String> {
+        (**self).completions(prefix)
+    }
+
+    fn fingerprint(&self) -> Option<u64> {
+        (**self).fingerprint()
+    }
+
+    fn separator(&self) -> Option<String> {
+        (**self).separator()
+    }
+
+    fn free_order_phrase(&self, s: &str) -> Option<String> {
+        (**self).free_order_phrase(s)
+    }
+
+    fn alias_target(&self, s: &str) -> Option<String> {
+        (**self).alias_target(s)
+    }
+
+    fn iter_phrases(&self) -> Box<dyn Iterator<Item = String> + '_> {
+        (**self).iter_phrases()
+    }
+}
+
+impl<T: PhraseContext + ?Sized> PhraseContext for &T {
+    fn get_phrase_status(&self, s: &str) -> PhraseStatus {
+        (**self).get_phrase_status(s)
+    }
+
+    fn get_phrase_meta(&self, s: &str) -> Option<PhraseMeta> {
+        (**self).get_phrase_meta(s)
+    }
+
+    fn get_versioned_meta(&self, s: &str, version: &str) -> Option<PhraseMeta> {
+        (**self).get_versioned_meta(s, version)
+    }
+
+    fn get_overloads(&self, s: &str) -> Vec<PhraseMeta> {
+        (**self).get_overloads(s)
+    }
+
+    fn completions(&self, prefix: &str) -> Vec<String> {
+        (**self).completions(prefix)
+    }
+
+    fn fingerprint(&self) -> Option<u64> {
+        (**self).fingerprint()
+    }
+
+    fn separator(&self) -> Option<String> {
+        (**self).separator()
+    }
+
+    fn free_order_phrase(&self, s: &str) -> Option<String> {
+        (**self).free_order_phrase(s)
+    }
+
+    fn alias_target(&self, s: &str) -> Option<String> {
+        (**self).alias_target(s)
+    }
+
+    fn iter_phrases(&self) -> Box<dyn Iterator<Item = String> + '_> {
+        (**self).iter_phrases()
+    }
+}
+
+impl<T: PhraseContext + ?Sized> PhraseContext for Arc<T> {
+    fn get_phrase_status(&self, s: &str) -> PhraseStatus {
+        (**self).get_phrase_status(s)
+    }
+
+    fn get_phrase_meta(&self, s: &str) -> Option<PhraseMeta> {
+        (**self).get_phrase_meta(s)
+    }
+
+    fn get_versioned_meta(&self, s: &str, version: &str) -> Option<PhraseMeta> {
+        (**self).get_versioned_meta(s, version)
+    }
+
+    fn get_overloads(&self, s: &str) -> Vec<PhraseMeta> {
+        (**self).get_overloads(s)
+    }
+
+    fn completions(&self, prefix: &str) -> Vec<String> {
+        (**self).completions(prefix)
+    }
+
+    fn fingerprint(&self) -> Option<u64> {
+        (**self).fingerprint()
+    }
+
+    fn separator(&self) -> Option<String> {
+        (**self).separator()
+    }
+
+    fn free_order_phrase(&self, s: &str) -> Option<String> {
+        (**self).free_order_phrase(s)
+    }
+
+    fn alias_target(&self, s: &str) -> Option<String> {
+        (**self).alias_target(s)
+    }
+
+    fn iter_phrases(&self) -> Box<dyn Iterator<Item = String> + '_> {
+        (**self).iter_phrases()
+    }
+}
+
+// registered complete phrase as seen through the context
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct PhraseEntry<'a> {
+    pub phrase: &'a str,
+    // status lookups return for the phrase, CompleteAndExtendable for complete phrases that are also prefixes
+    pub status: PhraseStatus,
+    pub meta: Option<&'a PhraseMeta>,
+}
+
+pub(crate) fn write_meta(fingerprinter: &mut Fingerprinter, meta: Option<&PhraseMeta>) {
+    fingerprinter.write_option(meta, |fingerprinter, meta| meta.write_fingerprint(fingerprinter));
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SimplePhraseContext {
+    part_map: HashMap<String, PhraseStatus>,
+    meta_map: HashMap<String, PhraseMeta>,
+    // meta of each version label a phrase was registered for
+    version_map: HashMap<String, BTreeMap<String, PhraseMeta>>,
+    // meta of each arity a phrase was overloaded for
+    overload_map: HashMap<String, BTreeMap<usize, PhraseMeta>>,
+    // complete phrases that are also the start of a longer phrase
+    complete_prefixes: HashSet<String>,
+    overlap_policy: OverlapPolicy,
+    collision_severity: CollisionSeverity,
+    warnings: Vec<ContextWarning>,
+    separator: Option<String>,
+    split_case: bool,
+    // other orders of free order phrases, mapped to the phrase as registered
+    free_orders: HashMap<String, String>,
+    // aliases mapped to the phrase they reduce as
+    aliases: HashMap<String, String>,
+    phrase_order: PhraseOrder,
+    // position each complete phrase was first added at
+    added_at: HashMap<String, usize>,
 }
 
-#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
-pub enum PhraseStatus {
-    Incomplete,
-    Complete,
-    NotAPhrase,
-}
+impl SimplePhraseContext {
+    pub fn new() -> Self {
+        SimplePhraseContext {
+            part_map: HashMap::new(),
+            meta_map: HashMap::new(),
+            version_map: HashMap::new(),
+            overload_map: HashMap::new(),
+            complete_prefixes: HashSet::new(),
+            overlap_policy: OverlapPolicy::default(),
+            collision_severity: CollisionSeverity::default(),
+            warnings: vec![],
+            separator: None,
+            split_case: false,
+            free_orders: HashMap::new(),
+            aliases: HashMap::new(),
+            phrase_order: PhraseOrder::default(),
+            added_at: HashMap::new(),
+        }
+    }
+
+    // context with every phrase added, all conflicts are reported instead of just the first
+    pub fn with_phrases<S: AsRef<str>>(phrases: impl IntoIterator<Item = S>) -> Result<Self, ContextErrors> {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrases(phrases)?;
+        Ok(context)
+    }
+
+    pub fn set_overlap_policy(&mut self, policy: OverlapPolicy) {
+        self.overlap_policy = policy;
+    }
+
+    pub fn set_phrase_order(&mut self, order: PhraseOrder) {
+        self.phrase_order = order;
+    }
+
+    // true for complete phrases including those that are also prefixes
+    pub fn contains_phrase(&self, phrase: &str) -> bool {
+        let phrase = self.normalize(phrase);
+        self.complete_prefixes.contains(&phrase) || self.part_map.get(&phrase) == Some(&PhraseStatus::Complete)
+    }
+
+    // true when phrase is the start of a longer registered phrase
+    pub fn is_prefix(&self, phrase: &str) -> bool {
+        self.part_map.get(&self.normalize(phrase)) == Some(&PhraseStatus::Incomplete)
+    }
+
+    // longest complete phrase made of leading words of text
+    pub fn longest_prefix_of(&self, text: &str) -> Option<&str> {
+        let words = self.normalize(text);
+        let words = words.split('_').collect::<Vec<&str>>();
+
+        (1..=words.len()).rev()
+            .map(|count| words[..count].join("_"))
+            .find(|candidate| self.contains_phrase(candidate))
+            .and_then(|candidate| self.part_map.get_key_value(&candidate))
+            .map(|(phrase, _)| phrase.as_str())
+    }
+
+    pub fn set_collision_severity(&mut self, severity: CollisionSeverity) {
+        self.collision_severity = severity;
+    }
+
+    // warnings raised while adding phrases
+    pub fn warnings(&self) -> &[ContextWarning] {
+        &self.warnings
+    }
+
+    pub fn take_warnings(&mut self) -> Vec<ContextWarning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    pub fn phrase_count(&self) -> usize {
+        self.part_map.len()
+    }
+
+    // complete phrases in the context's PhraseOrder so output built from them is stable
+    pub(crate) fn complete_phrases(&self) -> Vec<&str> {
+        let mut phrases = self.part_map.iter()
+            .filter(|(phrase, status)| **status == PhraseStatus::Complete || self.complete_prefixes.contains(*phrase))
+            .filter(|(phrase, _)| !self.free_orders.contains_key(*phrase))
+            .map(|(phrase, _)| phrase.as_str())
+            .collect::<Vec<&str>>();
+
+        phrases.sort();
+        if self.phrase_order == PhraseOrder::Insertion {
+            phrases.sort_by_key(|phrase| self.added_at.get(*phrase).copied().unwrap_or(usize::MAX));
+        }
+        phrases
+    }
+
+    // complete phrases in PhraseOrder, prefixes created for longer phrases aren't included
+    pub fn phrases(&self) -> impl Iterator<Item = &str> {
+        self.complete_phrases().into_iter()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = PhraseEntry<'_>> {
+        self.phrases().map(|phrase| PhraseEntry {
+            phrase,
+            status: self.get_phrase_status(phrase),
+            meta: self.meta_map.get(phrase),
+        })
+    }
+
+    // hash of everything that changes how phrases reduce, independent of the order they were added
+    // covers phrases with their meta, versions and overloads, aliases, the join separator and case splitting
+    pub fn fingerprint(&self) -> u64 {
+        let mut fingerprinter = Fingerprinter::new();
+        let mut phrases = self.complete_phrases();
+        phrases.sort();
+
+        for phrase in phrases {
+            fingerprinter.write_str(phrase);
+            write_meta(&mut fingerprinter, self.meta_map.get(phrase));
+            for (version, meta) in self.version_map.get(phrase).into_iter().flatten() {
+                fingerprinter.write_str(version);
+                write_meta(&mut fingerprinter, Some(meta));
+            }
+            for (arity, meta) in self.overload_map.get(phrase).into_iter().flatten() {
+                fingerprinter.write_str(&arity.to_string());
+                write_meta(&mut fingerprinter, Some(meta));
+            }
+        }
+
+        let mut aliases = self.aliases.iter().collect::<Vec<(&String, &String)>>();
+        aliases.sort();
+        for (alias, target) in aliases {
+            fingerprinter.write_str(alias);
+            fingerprinter.write_str(target);
+        }
+
+        fingerprinter.write_str(self.separator.as_deref().unwrap_or_default());
+        fingerprinter.write_u8(self.split_case as u8);
+        fingerprinter.finish()
+    }
+
+    // rust source for a phf map of every phrase and prefix, for a build script to write out
+    // include it as the value of a static to build a StaticPhraseContext at compile time
+    #[cfg(feature = "codegen")]
+    pub fn static_entries(&self) -> String {
+        let mut entries = self.part_map.keys()
+            .map(|phrase| (phrase, self.get_phrase_status(phrase)))
+            .collect::<Vec<(&String, PhraseStatus)>>();
+        entries.sort();
+
+        let mut map = phf_codegen::Map::new();
+        map.phf_path("garnish_phrases::phf");
+        for (phrase, status) in entries {
+            map.entry(phrase.as_str(), format!("garnish_phrases::context::PhraseStatus::{:?}", status));
+        }
+        map.build().to_string()
+    }
+
+    pub(crate) fn incomplete_phrases(&self) -> Vec<&str> {
+        let mut phrases = self.part_map.iter()
+            .filter(|(_, status)| **status == PhraseStatus::Incomplete)
+            .map(|(phrase, _)| phrase.as_str())
+            .collect::<Vec<&str>>();
+
+        phrases.sort();
+        phrases
+    }
+
+    pub fn add_phrase(&mut self, phrase: &str) -> Result<(), SimpleContextCodes> {
+        self.try_add_phrase(phrase).map_err(|error| error.code)
+    }
+
+    // as add_phrase, with the phrase that conflicted and where it came from on failure
+    pub fn try_add_phrase(&mut self, phrase: &str) -> Result<(), ContextError> {
+        self.insert_phrase(phrase).map(|_| ())
+    }
+
+    // adds every phrase, collecting failures instead of stopping at the first
+    pub fn add_phrases<S: AsRef<str>>(&mut self, phrases: impl IntoIterator<Item = S>) -> Result<(), ContextErrors> {
+        let errors = phrases.into_iter()
+            .filter_map(|phrase| self.try_add_phrase(phrase.as_ref()).err())
+            .collect::<Vec<ContextError>>();
+
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(ContextErrors(errors)),
+        }
+    }
+
+    // same as add_phrases
+    pub fn extend<S: AsRef<str>>(&mut self, phrases: impl IntoIterator<Item = S>) -> Result<(), ContextErrors> {
+        self.add_phrases(phrases)
+    }
+
+    // removes a complete phrase with its meta, versions, overloads and other word orders
+    // prefixes no remaining phrase starts with are removed too, false when phrase wasn't registered
+    pub fn remove_phrase(&mut self, phrase: &str) -> bool {
+        let phrase = self.normalize(phrase);
+        let phrase = self.free_orders.get(&phrase).cloned().unwrap_or(phrase);
+        if !self.contains_phrase(&phrase) {
+            return false;
+        }
+
+        let orders = self.free_orders.iter()
+            .filter(|(_, canonical)| **canonical == phrase)
+            .map(|(order, _)| order.clone())
+            .collect::<Vec<String>>();
+        for order in orders {
+            self.free_orders.remove(&order);
+            self.remove_words(&order);
+        }
+
+        self.remove_words(&phrase);
+        self.meta_map.remove(&phrase);
+        self.version_map.remove(&phrase);
+        self.overload_map.remove(&phrase);
+        self.aliases.remove(&phrase);
+
+        // aliases of the phrase would reduce to a phrase that's gone
+        let aliases = self.aliases.iter()
+            .filter(|(_, target)| **target == phrase)
+            .map(|(alias, _)| alias.clone())
+            .collect::<Vec<String>>();
+        for alias in aliases {
+            self.remove_phrase(&alias);
+        }
+        true
+    }
+
+    // removes everything added, settings like the overlap policy and separator are kept
+    pub fn clear(&mut self) {
+        self.part_map.clear();
+        self.meta_map.clear();
+        self.version_map.clear();
+        self.overload_map.clear();
+        self.complete_prefixes.clear();
+        self.warnings.clear();
+        self.free_orders.clear();
+        self.aliases.clear();
+        self.added_at.clear();
+    }
+
+    // drops phrase as complete then its prefixes from longest to shortest until one is still needed
+    fn remove_words(&mut self, phrase: &str) {
+        self.added_at.remove(phrase);
+        if self.complete_prefixes.remove(phrase) {
+            // still the start of a longer phrase
+            return;
+        }
+        self.part_map.remove(phrase);
+
+        let words = phrase.split('_').collect::<Vec<&str>>();
+        for length in (1..words.len()).rev() {
+            let prefix = words[..length].join("_");
+            let extension = format!("{}_", prefix);
+            if self.part_map.keys().any(|existing| existing.starts_with(&extension)) {
+                return;
+            }
+
+            match self.complete_prefixes.remove(&prefix) {
+                true => {
+                    // complete on its own again, shorter prefixes are needed by it
+                    self.part_map.insert(prefix, PhraseStatus::Complete);
+                    return;
+                }
+                false => {
+                    self.part_map.remove(&prefix);
+                }
+            }
+        }
+    }
+
+    pub fn add_phrase_with_meta(&mut self, phrase: &str, meta: PhraseMeta) -> Result<(), SimpleContextCodes> {
+        self.try_add_phrase_with_meta(phrase, meta).map_err(|error| error.code)
+    }
+
+    // as add_phrase_with_meta, with the phrase that conflicted and where it came from on failure
+    pub fn try_add_phrase_with_meta(&mut self, phrase: &str, meta: PhraseMeta) -> Result<(), ContextError> {
+        let free_orders = match meta.free_order {
+            false => vec![],
+            true => self.free_orders_of(&self.normalize(phrase))?,
+        };
+
+        let policy = match meta.priority {
+            None => self.overlap_policy,
+            Some(_) => OverlapPolicy::AllowCompletePrefix,
+        };
+
+        if let Some(complete_phrase) = self.insert_phrase_with_policy(phrase, policy)? {
+            for order in free_orders {
+                self.insert_phrase(&order)?;
+                self.free_orders.insert(order, complete_phrase.clone());
+            }
+            self.meta_map.insert(complete_phrase, meta);
+        }
+
+        Ok(())
+    }
+
+    // adds the words of template as a phrase that only matches with arguments in its slots
+    // each _ after a word is one argument, e.g. "move _ to _", a slot after the final word takes trailing arguments
+    pub fn add_template(&mut self, template: &str, mut meta: PhraseMeta) -> Result<(), ContextError> {
+        let mut words = vec![];
+        let mut slots: Vec<usize> = vec![];
+        for part in template.split_whitespace() {
+            match (part, slots.last_mut()) {
+                ("_", None) => return Err(self.conflict_error(template, template.to_string(), SimpleContextCodes::LeadingSlot)),
+                ("_", Some(count)) => *count += 1,
+                (word, _) => {
+                    words.push(word);
+                    slots.push(0);
+                }
+            }
+        }
+
+        meta.trailing_arguments |= slots.last().is_some_and(|count| *count > 0);
+        meta.arity = meta.arity.or(Some(slots.iter().sum()));
+        meta.slots = slots;
+        self.try_add_phrase_with_meta(&words.join("_"), meta)
+    }
+
+    // alias is matched like any phrase, then reduced as phrase with its meta and identifier
+    // phrase doesn't need to be registered, an unregistered phrase is only used as the identifier
+    pub fn add_alias(&mut self, alias: &str, phrase: &str) -> Result<(), ContextError> {
+        let target = self.normalize(phrase);
+        if let Some(alias) = self.insert_phrase(alias)? {
+            self.aliases.insert(alias, target);
+        }
+
+        Ok(())
+    }
+
+    pub fn alias_of(&self, alias: &str) -> Option<&str> {
+        self.aliases.get(&self.normalize(alias)).map(String::as_str)
+    }
+
+    // every other order of the words of phrase not yet registered for it
+    // fails when an order is already a phrase or an order of another free order phrase
+    fn free_orders_of(&self, phrase: &str) -> Result<Vec<String>, ContextError> {
+        let words = phrase.split('_').collect::<Vec<&str>>();
+        if words.len() > MAX_FREE_WORDS {
+            return Err(self.conflict_error(phrase, phrase.to_string(), SimpleContextCodes::TooManyFreeWords));
+        }
+
+        let mut free_orders = vec![];
+        for order in orders(&words) {
+            let order = order.join("_");
+            match self.free_orders.get(&order) {
+                _ if order == phrase || free_orders.contains(&order) => (),
+                Some(existing) if existing == phrase => (),
+                Some(existing) => {
+                    return Err(self.conflict_error(phrase, existing.clone(), SimpleContextCodes::FreeOrderConflict));
+                }
+                None if self.contains_phrase(&order) => {
+                    return Err(self.conflict_error(phrase, order, SimpleContextCodes::FreeOrderConflict));
+                }
+                None => free_orders.push(order),
+            }
+        }
+
+        Ok(free_orders)
+    }
+
+    // adds phrase if needed and sets its meta for one version
+    // reductions listing version in ReduceOptions::versions use it instead of the unversioned meta
+    pub fn add_phrase_version(&mut self, phrase: &str, version: &str, meta: PhraseMeta) -> Result<(), ContextError> {
+        if let Some(complete_phrase) = self.insert_phrase(phrase)? {
+            self.version_map.entry(complete_phrase).or_default().insert(version.to_string(), meta);
+        }
+
+        Ok(())
+    }
+
+    // adds phrase if needed and registers meta for calls given arity arguments
+    // each overload can name its own target, e.g. draw_circle with 1 or 3 arguments
+    pub fn add_phrase_overload(&mut self, phrase: &str, arity: usize, meta: PhraseMeta) -> Result<(), ContextError> {
+        if let Some(complete_phrase) = self.insert_phrase(phrase)? {
+            self.overload_map.entry(complete_phrase).or_default().insert(arity, meta.with_arity(arity));
+        }
+
+        Ok(())
+    }
+
+    // arities phrase was overloaded for, sorted
+    pub fn phrase_overloads(&self, phrase: &str) -> Vec<usize> {
+        match self.overload_map.get(&self.normalize(phrase)) {
+            None => vec![],
+            Some(overloads) => overloads.keys().copied().collect(),
+        }
+    }
+
+    // version labels phrase was registered for, sorted
+    pub fn phrase_versions(&self, phrase: &str) -> Vec<&str> {
+        match self.version_map.get(&self.normalize(phrase)) {
+            None => vec![],
+            Some(versions) => versions.keys().map(String::as_str).collect(),
+        }
+    }
+
+    pub fn phrase_meta(&self, phrase: &str) -> Option<&PhraseMeta> {
+        self.meta_map.get(&self.normalize(phrase))
+    }
+
+    pub fn doc(&self, phrase: &str) -> Option<&str> {
+        self.phrase_meta(phrase).and_then(|meta| meta.doc.as_deref())
+    }
+
+    pub fn examples(&self, phrase: &str) -> &[String] {
+        match self.phrase_meta(phrase) {
+            None => &[],
+            Some(meta) => &meta.examples,
+        }
+    }
+
+    pub fn source(&self, phrase: &str) -> Option<&PhraseSource> {
+        self.phrase_meta(phrase).and_then(|meta| meta.source.as_ref())
+    }
+
+    pub fn deprecation(&self, phrase: &str) -> Option<&Deprecation> {
+        self.phrase_meta(phrase).and_then(|meta| meta.deprecated.as_ref())
+    }
+
+    // marks a registered phrase deprecated keeping the rest of its meta, false when it isn't registered
+    pub fn deprecate(&mut self, phrase: &str, replacement: Option<&str>) -> bool {
+        let phrase = self.normalize(phrase);
+        if !self.contains_phrase(&phrase) {
+            return false;
+        }
+
+        let meta = self.meta_map.remove(&phrase).unwrap_or_default();
+        self.meta_map.insert(phrase, meta.with_deprecation(replacement));
+        true
+    }
+
+    // words of phrases added from now on may also be joined with separator, e.g. "-" for perform-task
+    // phrases are still stored and reported joined with underscores
+    // reductions emit identifiers joined with it unless ReduceOptions::separator is set
+    pub fn set_separator(&mut self, separator: &str) {
+        self.separator = (separator != "_" && !separator.is_empty()).then(|| separator.to_string());
+    }
+
+    // words of phrases added from now on may also be written camelCase or PascalCase, e.g. performTask
+    // words are lowercased, so perform_Task is stored as perform_task
+    pub fn set_split_case(&mut self, split: bool) {
+        self.split_case = split;
+    }
+
+    // phrase with its words joined by underscores
+    fn canonical(&self, phrase: &str) -> String {
+        let phrase = match &self.separator {
+            None => phrase.to_string(),
+            Some(separator) => phrase.replace(separator.as_str(), "_"),
+        };
+
+        match self.split_case {
+            // empty pieces are kept so a trailing underscore still only adds a prefix
+            true => phrase.split('_').map(|piece| identifier_words(piece).join("_")).collect::<Vec<String>>().join("_"),
+            false => phrase,
+        }
+    }
+
+    fn normalize(&self, phrase: &str) -> String {
+        normalize_phrase(&self.canonical(phrase))
+    }
+
+    fn conflict_error(&self, phrase: &str, conflict: String, code: SimpleContextCodes) -> ContextError {
+        ContextError {
+            phrase: phrase.to_string(),
+            conflict_source: self.meta_map.get(&conflict).and_then(|meta| meta.source.clone()),
+            conflict,
+            code,
+        }
+    }
+
+    fn check_reserved(&mut self, phrase: &str) -> Result<(), ContextError> {
+        let normalized = normalize_phrase(phrase);
+        // only single words, multi word phrases can't be mistaken for a reserved word
+        // trailing underscore means nothing complete gets added
+        if normalized.contains('_') || phrase.ends_with('_') {
+            return Ok(());
+        }
+
+        let kind = match reserved_kind(&normalized) {
+            None => return Ok(()),
+            Some(kind) => kind,
+        };
+
+        match self.collision_severity {
+            CollisionSeverity::Allow => Ok(()),
+            CollisionSeverity::Warn => {
+                self.warnings.push(ContextWarning::ReservedWord { phrase: normalized, kind });
+                Ok(())
+            }
+            CollisionSeverity::Deny => Err(self.conflict_error(phrase, normalized, SimpleContextCodes::ReservedWord)),
+        }
+    }
+
+    // returns the normalized text of the complete phrase, if one was added
+    fn insert_phrase(&mut self, phrase: &str) -> Result<Option<String>, ContextError> {
+        self.insert_phrase_with_policy(phrase, self.overlap_policy)
+    }
+
+    // overlaps are also allowed with phrases that have a priority to settle them
+    fn insert_phrase_with_policy(&mut self, phrase: &str, policy: OverlapPolicy) -> Result<Option<String>, ContextError> {
+        let phrase = &self.canonical(phrase);
+        self.check_reserved(phrase)?;
+
+        if let Some(free_phrase) = self.free_orders.get(&normalize_phrase(phrase)) {
+            return Err(self.conflict_error(phrase, free_phrase.clone(), SimpleContextCodes::FreeOrderConflict));
+        }
+
+        let parts = phrase.split("_").collect::<Vec<&str>>();
+
+        if parts.is_empty() {
+            // unreachable?
+            return Ok(None);
+        }
+
+        let mut running_parts = vec![];
+
+        for part in parts.iter().take(parts.len() - 1) {
+            if part.is_empty() {
+                continue;
+            }
+            running_parts.push(*part);
+            let incomplete_phrase = running_parts.join("_");
+            match self.part_map.get(&incomplete_phrase) {
+                None => {
+                    self.part_map.insert(incomplete_phrase, PhraseStatus::Incomplete);
+                },
+                Some(status) => if *status == PhraseStatus::Complete {
+                    if policy == OverlapPolicy::AllowCompletePrefix || self.has_priority(&incomplete_phrase) {
+                        self.part_map.insert(incomplete_phrase.clone(), PhraseStatus::Incomplete);
+                        self.complete_prefixes.insert(incomplete_phrase);
+                        continue;
+                    }
+
+                    return Err(self.conflict_error(phrase, incomplete_phrase, SimpleContextCodes::CompleteVersionExists))
+                }
+            }
+
+        }
+
+        match parts.last() {
+            None => unreachable!(),
+            Some(part) => {
+                if part.is_empty() {
+                    return Ok(None);
+                }
+                running_parts.push(*part);
+                let complete_phrase = running_parts.join("_");
+                match self.part_map.get(&complete_phrase) {
+                    None => {
+                        self.part_map.insert(complete_phrase.clone(), PhraseStatus::Complete);
+                    }
+                    Some(status) => if *status == PhraseStatus::Incomplete {
+                        let extension = format!("{}_", complete_phrase);
+                        let longer = self.complete_phrases().into_iter()
+                            .filter(|existing| existing.starts_with(&extension))
+                            .map(str::to_string)
+                            .collect::<Vec<String>>();
+
+                        let prioritized = !longer.is_empty() && longer.iter().all(|existing| self.has_priority(existing));
+                        if policy == OverlapPolicy::AllowCompletePrefix || prioritized {
+                            self.complete_prefixes.insert(complete_phrase.clone());
+                            self.record_added(&complete_phrase);
+                            return Ok(Some(complete_phrase));
+                        }
+
+                        // report first of the longer phrases this would be the start of
+                        let conflict = longer.into_iter().next().unwrap_or(complete_phrase);
+
+                        return Err(self.conflict_error(phrase, conflict, SimpleContextCodes::IncompleteVersionExists));
+                    }
+                }
+
+                self.record_added(&complete_phrase);
+                Ok(Some(complete_phrase))
+            }
+        }
+    }
+
+    fn has_priority(&self, phrase: &str) -> bool {
+        self.meta_map.get(phrase).is_some_and(|meta| meta.priority.is_some())
+    }
+
+    fn record_added(&mut self, phrase: &str) {
+        if !self.added_at.contains_key(phrase) {
+            self.added_at.insert(phrase.to_string(), self.added_at.len());
+        }
+    }
+}
+
+// free order phrases are registered in every order of their words, 5 words is 120 orders
+const MAX_FREE_WORDS: usize = 5;
+
+// every order of words, including the given one
+fn orders<'a>(words: &[&'a str]) -> Vec<Vec<&'a str>> {
+    if words.len() <= 1 {
+        return vec![words.to_vec()];
+    }
+
+    let mut orders = vec![];
+    for (index, word) in words.iter().enumerate() {
+        let mut rest = words.to_vec();
+        rest.remove(index);
+        for mut order in self::orders(&rest) {
+            order.insert(0, *word);
+            orders.push(order);
+        }
+    }
+
+    orders
+}
+
+impl Default for SimplePhraseContext {
+    fn default() -> Self {
+        SimplePhraseContext::new()
+    }
+}
+
+impl TryFrom<&[&str]> for SimplePhraseContext {
+    type Error = ContextErrors;
+
+    fn try_from(phrases: &[&str]) -> Result<Self, Self::Error> {
+        SimplePhraseContext::with_phrases(phrases)
+    }
+}
+
+impl TryFrom<Vec<String>> for SimplePhraseContext {
+    type Error = ContextErrors;
+
+    fn try_from(phrases: Vec<String>) -> Result<Self, Self::Error> {
+        SimplePhraseContext::with_phrases(phrases)
+    }
+}
+
+// map keys have no order to resolve overlaps by, so complete prefixes are allowed
+// meta can still be rejected, e.g. free order phrases whose orders conflict
+impl TryFrom<HashMap<String, PhraseMeta>> for SimplePhraseContext {
+    type Error = ContextErrors;
+
+    fn try_from(phrases: HashMap<String, PhraseMeta>) -> Result<Self, Self::Error> {
+        let mut context = SimplePhraseContext::new();
+        context.set_overlap_policy(OverlapPolicy::AllowCompletePrefix);
+
+        let mut phrases = phrases.into_iter().collect::<Vec<(String, PhraseMeta)>>();
+        phrases.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let errors = phrases.into_iter()
+            .filter_map(|(phrase, meta)| context.try_add_phrase_with_meta(&phrase, meta).err())
+            .collect::<Vec<ContextError>>();
+
+        match errors.is_empty() {
+            true => Ok(context),
+            false => Err(ContextErrors(errors)),
+        }
+    }
+}
+
+impl PhraseContext for SimplePhraseContext {
+    fn get_phrase_status(&self, s: &str) -> PhraseStatus {
+        match self.part_map.get(s) {
+            None => PhraseStatus::NotAPhrase,
+            Some(_) if self.complete_prefixes.contains(s) => PhraseStatus::CompleteAndExtendable,
+            Some(status) => *status
+        }
+    }
+
+    fn get_phrase_meta(&self, s: &str) -> Option<PhraseMeta> {
+        self.meta_map.get(s).cloned()
+    }
+
+    fn get_versioned_meta(&self, s: &str, version: &str) -> Option<PhraseMeta> {
+        self.version_map.get(s).and_then(|versions| versions.get(version)).cloned()
+    }
+
+    fn get_overloads(&self, s: &str) -> Vec<PhraseMeta> {
+        self.overload_map.get(s).map(|overloads| overloads.values().cloned().collect()).unwrap_or_default()
+    }
+
+    fn completions(&self, prefix: &str) -> Vec<String> {
+        let extension = format!("{}_", prefix);
+        self.phrases().filter(|phrase| phrase.starts_with(&extension)).map(str::to_string).collect()
+    }
+
+    fn fingerprint(&self) -> Option<u64> {
+        Some(SimplePhraseContext::fingerprint(self))
+    }
+
+    fn separator(&self) -> Option<String> {
+        self.separator.clone()
+    }
+
+    fn free_order_phrase(&self, s: &str) -> Option<String> {
+        self.free_orders.get(s).cloned()
+    }
+
+    fn alias_target(&self, s: &str) -> Option<String> {
+        self.aliases.get(s).cloned()
+    }
+
+    // in the context's phrase order
+    fn iter_phrases(&self) -> Box<dyn Iterator<Item = String> + '_> {
+        Box::new(SimplePhraseContext::phrases(self).map(str::to_string))
+    }
+}
+
+// fixed phf map of phrases and prefixes that can live in a const or static, lookups don't allocate
+// the map is written by SimplePhraseContext::static_entries from a build script with the codegen feature, or with phf_map!
+#[derive(Debug, Copy, Clone)]
+pub struct StaticPhraseContext {
+    entries: &'static phf::Map<&'static str, PhraseStatus>,
+}
+
+impl StaticPhraseContext {
+    pub const fn new(entries: &'static phf::Map<&'static str, PhraseStatus>) -> Self {
+        StaticPhraseContext { entries }
+    }
+
+    pub fn phrase_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn contains_phrase(&self, phrase: &str) -> bool {
+        self.get_phrase_status(phrase).is_complete()
+    }
+}
+
+impl PhraseContext for StaticPhraseContext {
+    fn get_phrase_status(&self, s: &str) -> PhraseStatus {
+        self.entries.get(s).copied().unwrap_or(PhraseStatus::NotAPhrase)
+    }
+
+    // sorted, the map's own order depends on its hashes
+    fn completions(&self, prefix: &str) -> Vec<String> {
+        let extension = format!("{}_", prefix);
+        let mut completions = self.entries.entries()
+            .filter(|(phrase, status)| status.is_complete() && phrase.starts_with(&extension))
+            .map(|(phrase, _)| phrase.to_string())
+            .collect::<Vec<String>>();
+        completions.sort();
+        completions
+    }
+
+    // sorted, as completions
+    fn iter_phrases(&self) -> Box<dyn Iterator<Item = String> + '_> {
+        let mut phrases = self.entries.entries()
+            .filter(|(_, status)| status.is_complete())
+            .map(|(phrase, _)| phrase.to_string())
+            .collect::<Vec<String>>();
+        phrases.sort();
+        Box::new(phrases.into_iter())
+    }
+}
+
+// same form add_phrase stores, extra underscores removed
+pub(crate) fn normalize_phrase(phrase: &str) -> String {
+    phrase.split('_').filter(|part| !part.is_empty()).collect::<Vec<&str>>().join("_")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::context::{ContextErrors, ContextWarning, OverlapPolicy, PhraseContext, PhraseEntry, PhraseOrder, PhraseStatus, SimpleContextCodes, SimplePhraseContext, StaticPhraseContext};
+    use crate::meta::{ArgumentOrder, PhraseMeta, PhraseSource, Visibility};
+    use crate::reserved::{CollisionSeverity, ReservedKind};
+
+    #[test]
+    fn create() {
+        SimplePhraseContext::new();
+    }
+
+    #[test]
+    fn add_single_word_phrase() {
+        let mut context = SimplePhraseContext::new();
+        let result = context.add_phrase("phrase");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(context.get_phrase_status("phrase"), PhraseStatus::Complete);
+    }
+
+    #[test]
+    fn get_non_phrase() {
+        let mut context = SimplePhraseContext::new();
+        let result = context.add_phrase("phrase");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(context.get_phrase_status("not"), PhraseStatus::NotAPhrase);
+    }
+
+    #[test]
+    fn add_two_word_phrase() {
+        let mut context = SimplePhraseContext::new();
+        let result = context.add_phrase("some_phrase");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(context.get_phrase_status("some"), PhraseStatus::Incomplete);
+        assert_eq!(context.get_phrase_status("some_phrase"), PhraseStatus::Complete);
+    }
+
+    #[test]
+    fn add_three_word_phrase() {
+        let mut context = SimplePhraseContext::new();
+        let result = context.add_phrase("some_great_phrase");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(context.get_phrase_status("some"), PhraseStatus::Incomplete);
+        assert_eq!(context.get_phrase_status("some_great"), PhraseStatus::Incomplete);
+        assert_eq!(context.get_phrase_status("some_great_phrase"), PhraseStatus::Complete);
+    }
+
+    #[test]
+    fn add_three_word_phrase_extra_underscores_no_effect() {
+        let mut context = SimplePhraseContext::new();
+        let result = context.add_phrase("_some__great__phrase");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(context.get_phrase_status("some"), PhraseStatus::Incomplete);
+        assert_eq!(context.get_phrase_status("some_great"), PhraseStatus::Incomplete);
+        assert_eq!(context.get_phrase_status("some_great_phrase"), PhraseStatus::Complete);
+        assert_eq!(context.phrase_count(), 3);
+    }
+
+    #[test]
+    fn add_three_word_phrase_extra_underscores_end_forces_last_incomplete() {
+        let mut context = SimplePhraseContext::new();
+        let result = context.add_phrase("_some__great__phrase__");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(context.get_phrase_status("some"), PhraseStatus::Incomplete);
+        assert_eq!(context.get_phrase_status("some_great"), PhraseStatus::Incomplete);
+        assert_eq!(context.get_phrase_status("some_great_phrase"), PhraseStatus::Incomplete);
+        assert_eq!(context.phrase_count(), 3);
+    }
+
+    #[test]
+    fn empty_phrase_ok() {
+        let mut context = SimplePhraseContext::new();
+        let result = context.add_phrase("");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(context.phrase_count(), 0);
+    }
+
+    #[test]
+    fn just_underscore_ok() {
+        let mut context = SimplePhraseContext::new();
+        let result = context.add_phrase("_");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(context.phrase_count(), 0);
+    }
+
+    #[test]
+    fn error_adding_complete_phrase_when_already_incomplete() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("some_phrase").unwrap();
+
+        let result = context.add_phrase("some");
+
+        assert_eq!(result, Err(SimpleContextCodes::IncompleteVersionExists));
+    }
+
+    #[test]
+    fn error_adding_incomplete_phrase_when_already_complete() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("some").unwrap();
+
+        let result = context.add_phrase("some_phrase");
+
+        assert_eq!(result, Err(SimpleContextCodes::CompleteVersionExists));
+    }
+
+    #[test]
+    fn fingerprint_independent_of_insertion_order() {
+        let mut first = SimplePhraseContext::new();
+        first.add_phrase("perform_task").unwrap();
+        first.add_phrase("stop").unwrap();
+
+        let mut second = SimplePhraseContext::new();
+        second.add_phrase("stop").unwrap();
+        second.add_phrase("perform_task").unwrap();
+
+        assert_eq!(first.fingerprint(), second.fingerprint());
+    }
+
+    #[test]
+    fn fingerprint_changes_with_content() {
+        let mut first = SimplePhraseContext::new();
+        first.add_phrase("perform_task").unwrap();
+
+        let mut second = SimplePhraseContext::new();
+        second.add_phrase("perform_job").unwrap();
+
+        assert_ne!(first.fingerprint(), second.fingerprint());
+        assert_ne!(first.fingerprint(), SimplePhraseContext::new().fingerprint());
+    }
+
+    #[test]
+    fn fingerprint_changes_with_meta() {
+        let mut first = SimplePhraseContext::new();
+        first.add_phrase_with_meta("perform_task", PhraseMeta::new().with_target("run")).unwrap();
+
+        let mut second = SimplePhraseContext::new();
+        second.add_phrase_with_meta("perform_task", PhraseMeta::new().with_target("execute")).unwrap();
+
+        let mut plain = SimplePhraseContext::new();
+        plain.add_phrase("perform_task").unwrap();
+
+        assert_ne!(first.fingerprint(), second.fingerprint());
+        assert_ne!(first.fingerprint(), plain.fingerprint());
+
+        // only where it was loaded from differs
+        let mut sourced = SimplePhraseContext::new();
+        let meta = PhraseMeta::new().with_target("run").with_source(PhraseSource::File("phrases.json".to_string()));
+        sourced.add_phrase_with_meta("perform_task", meta).unwrap();
+        assert_eq!(first.fingerprint(), sourced.fingerprint());
+    }
+
+    #[test]
+    fn fingerprint_is_stable() {
+        let mut context = SimplePhraseContext::new();
+        let meta = PhraseMeta::new()
+            .with_target("run")
+            .with_arity(1)
+            .with_argument_order(ArgumentOrder::Permutation(vec![0]))
+            .with_visibility(Visibility::Internal);
+        context.add_phrase_with_meta("perform_task", meta).unwrap();
+        context.add_phrase("stop").unwrap();
+        context.add_alias("halt", "stop").unwrap();
+
+        // changes only when what's written changes, update with care
+        assert_eq!(context.fingerprint(), 4898915228350478001);
+    }
+
+    #[test]
+    fn fingerprint_changes_with_alias() {
+        let mut start = SimplePhraseContext::new();
+        start.add_phrases(["start_job", "stop_job"]).unwrap();
+        start.add_alias("kick_off_job", "start_job").unwrap();
+
+        let mut stop = SimplePhraseContext::new();
+        stop.add_phrases(["start_job", "stop_job"]).unwrap();
+        stop.add_alias("kick_off_job", "stop_job").unwrap();
+
+        let mut plain = SimplePhraseContext::new();
+        plain.add_phrases(["start_job", "stop_job", "kick_off_job"]).unwrap();
+
+        assert_ne!(start.fingerprint(), stop.fingerprint());
+        assert_ne!(start.fingerprint(), plain.fingerprint());
+    }
+
+    #[test]
+    fn doc_by_phrase_name() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("perform_task", PhraseMeta::new().with_doc("Performs a task.")).unwrap();
+
+        assert_eq!(context.doc("perform_task"), Some("Performs a task."));
+        assert_eq!(context.doc("_perform__task"), Some("Performs a task."));
+        assert_eq!(context.get_phrase_meta("perform_task"), Some(PhraseMeta::new().with_doc("Performs a task.")));
+    }
+
+    #[test]
+    fn add_phrase_keeps_existing_meta() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("perform_task", PhraseMeta::new().with_doc("Performs a task.")).unwrap();
+        context.add_phrase("perform_task").unwrap();
+
+        assert_eq!(context.doc("perform_task"), Some("Performs a task."));
+    }
+
+    #[test]
+    fn no_meta_for_incomplete_phrase() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("perform_task", PhraseMeta::new().with_doc("Performs a task.")).unwrap();
+
+        assert_eq!(context.get_phrase_meta("perform"), None);
+    }
+
+    #[test]
+    fn examples_by_phrase_name() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta(
+            "perform_task",
+            PhraseMeta::new().with_example("perform 5 task").with_example("perform 5 10 task"),
+        ).unwrap();
+        context.add_phrase("stop").unwrap();
+
+        assert_eq!(context.examples("perform_task"), &["perform 5 task".to_string(), "perform 5 10 task".to_string()]);
+        assert!(context.examples("stop").is_empty());
+        assert!(context.examples("not").is_empty());
+    }
+
+    #[test]
+    fn conflict_reports_existing_source() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta(
+            "some_phrase",
+            PhraseMeta::new().with_source(PhraseSource::File("base.json".to_string())),
+        ).unwrap();
 
-pub trait PhraseContext {
-    fn get_phrase_status(&self, s: &str) -> PhraseStatus;
-}
+        let error = context.try_add_phrase("some").unwrap_err();
 
-pub struct SimplePhraseContext {
-    part_map: HashMap<String, PhraseStatus>
-}
+        assert_eq!(error.conflict, "some_phrase");
+        assert_eq!(error.conflict_source, Some(PhraseSource::File("base.json".to_string())));
+        assert_eq!(error.to_string(), "could not add phrase 'some', conflicts with 'some_phrase' defined by file 'base.json'");
+    }
 
-impl SimplePhraseContext {
-    pub fn new() -> Self {
-        SimplePhraseContext { part_map: HashMap::new() }
+    #[test]
+    fn conflict_with_complete_prefix() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("some", PhraseMeta::new().with_source(PhraseSource::Library("base".to_string()))).unwrap();
+
+        let error = context.try_add_phrase("some_phrase").unwrap_err();
+
+        assert_eq!(error.conflict, "some");
+        assert_eq!(error.to_string(), "could not add phrase 'some_phrase', conflicts with 'some' defined by library 'base'");
     }
 
-    pub fn phrase_count(&self) -> usize {
-        self.part_map.len()
+    #[test]
+    fn source_by_phrase_name() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("perform_task", PhraseMeta::new().with_source(PhraseSource::Call("setup".to_string()))).unwrap();
+        context.add_phrase("stop").unwrap();
+
+        assert_eq!(context.source("perform_task"), Some(&PhraseSource::Call("setup".to_string())));
+        assert_eq!(context.source("stop"), None);
     }
 
-    pub fn add_phrase(&mut self, phrase: &str) -> Result<(), SimpleContextCodes> {
-        let parts = phrase.split("_").collect::<Vec<&str>>();
+    #[test]
+    fn reserved_word_warns_by_default() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("true").unwrap();
 
-        if parts.len() == 0 {
-            // unreachable?
-            return Ok(());
-        }
+        assert_eq!(context.get_phrase_status("true"), PhraseStatus::Complete);
+        assert_eq!(context.warnings(), &[ContextWarning::ReservedWord {
+            phrase: "true".to_string(),
+            kind: ReservedKind::Keyword,
+        }]);
+    }
 
-        let mut running_parts = vec![];
+    #[test]
+    fn reserved_word_in_longer_phrase_no_warning() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("print_report").unwrap();
 
-        for part in parts.iter().take(parts.len() - 1) {
-            if part.is_empty() {
-                continue;
-            }
-            running_parts.push(*part);
-            let incomplete_phrase = running_parts.join("_");
-            match self.part_map.get(&incomplete_phrase) {
-                None => {
-                    self.part_map.insert(incomplete_phrase, PhraseStatus::Incomplete);
-                },
-                Some(status) => if *status == PhraseStatus::Complete {
-                    return Err(SimpleContextCodes::CompleteVersionExists)
-                }
-            }
+        assert!(context.warnings().is_empty());
+    }
 
-        }
+    #[test]
+    fn reserved_word_allowed() {
+        let mut context = SimplePhraseContext::new();
+        context.set_collision_severity(CollisionSeverity::Allow);
+        context.add_phrase("print").unwrap();
 
-        match parts.last() {
-            None => unreachable!(),
-            Some(part) => {
-                if part.is_empty() {
-                    return Ok(());
-                }
-                running_parts.push(*part);
-                let complete_phrase = running_parts.join("_");
-                match self.part_map.get(&complete_phrase) {
-                    None => {
-                        self.part_map.insert(complete_phrase, PhraseStatus::Complete);
-                    }
-                    Some(status) => if *status == PhraseStatus::Incomplete {
-                        return Err(SimpleContextCodes::IncompleteVersionExists);
-                    }
-                }
-            }
-        };
+        assert!(context.take_warnings().is_empty());
+    }
 
-        Ok(())
+    #[test]
+    fn reserved_word_denied() {
+        let mut context = SimplePhraseContext::new();
+        context.set_collision_severity(CollisionSeverity::Deny);
+        let result = context.add_phrase("and");
+
+        assert_eq!(result, Err(SimpleContextCodes::ReservedWord));
+        assert_eq!(context.get_phrase_status("and"), PhraseStatus::NotAPhrase);
     }
-}
 
-impl PhraseContext for SimplePhraseContext {
-    fn get_phrase_status(&self, s: &str) -> PhraseStatus {
-        match self.part_map.get(s) {
-            None => PhraseStatus::NotAPhrase,
-            Some(status) => *status
-        }
+    #[test]
+    fn complete_prefix_allowed_when_added_after() {
+        let mut context = SimplePhraseContext::new();
+        context.set_overlap_policy(OverlapPolicy::AllowCompletePrefix);
+        context.add_phrase("perform_task").unwrap();
+        context.add_phrase("perform").unwrap();
+
+        assert_eq!(context.get_phrase_status("perform"), PhraseStatus::CompleteAndExtendable);
+        assert!(context.contains_phrase("perform"));
+        assert_eq!(context.complete_phrases(), vec!["perform", "perform_task"]);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::context::{PhraseContext, PhraseStatus, SimpleContextCodes, SimplePhraseContext};
+    #[test]
+    fn complete_prefix_allowed_when_added_before() {
+        let mut context = SimplePhraseContext::new();
+        context.set_overlap_policy(OverlapPolicy::AllowCompletePrefix);
+        context.add_phrase_with_meta("perform", PhraseMeta::new().with_doc("Performs")).unwrap();
+        context.add_phrase("perform_task").unwrap();
+
+        assert_eq!(context.get_phrase_status("perform"), PhraseStatus::CompleteAndExtendable);
+        assert_eq!(context.get_phrase_status("perform_task"), PhraseStatus::Complete);
+        assert!(context.contains_phrase("perform"));
+        assert_eq!(context.doc("perform"), Some("Performs"));
+    }
 
     #[test]
-    fn create() {
-        SimplePhraseContext::new();
+    fn prefix_not_complete_unless_added() {
+        let mut context = SimplePhraseContext::new();
+        context.set_overlap_policy(OverlapPolicy::AllowCompletePrefix);
+        context.add_phrase("perform_task").unwrap();
+
+        assert!(!context.contains_phrase("perform"));
+        assert!(context.contains_phrase("perform_task"));
     }
 
     #[test]
-    fn add_single_word_phrase() {
+    fn clone_is_equal() {
         let mut context = SimplePhraseContext::new();
-        let result = context.add_phrase("phrase");
+        context.add_phrase_with_meta("perform_task", PhraseMeta::new().with_doc("Performs")).unwrap();
 
-        assert_eq!(result, Ok(()));
-        assert_eq!(context.get_phrase_status("phrase"), PhraseStatus::Complete);
+        let mut other = context.clone();
+        assert_eq!(context, other);
+
+        other.add_phrase("stop").unwrap();
+        assert_ne!(context, other);
     }
 
     #[test]
-    fn get_non_phrase() {
+    fn default_is_empty() {
+        assert_eq!(SimplePhraseContext::default(), SimplePhraseContext::new());
+        assert!(format!("{:?}", SimplePhraseContext::default()).starts_with("SimplePhraseContext"));
+    }
+
+    #[test]
+    fn phrases_exclude_prefixes() {
         let mut context = SimplePhraseContext::new();
-        let result = context.add_phrase("phrase");
+        context.add_phrase("perform_task").unwrap();
+        context.add_phrase("stop").unwrap();
 
-        assert_eq!(result, Ok(()));
-        assert_eq!(context.get_phrase_status("not"), PhraseStatus::NotAPhrase);
+        assert_eq!(context.phrases().collect::<Vec<&str>>(), vec!["perform_task", "stop"]);
     }
 
     #[test]
-    fn add_two_word_phrase() {
+    fn iter_with_status_and_meta() {
         let mut context = SimplePhraseContext::new();
-        let result = context.add_phrase("some_phrase");
+        context.set_overlap_policy(OverlapPolicy::AllowCompletePrefix);
+        context.add_phrase("perform_task").unwrap();
+        context.add_phrase_with_meta("perform", PhraseMeta::new().with_doc("Performs")).unwrap();
 
-        assert_eq!(result, Ok(()));
-        assert_eq!(context.get_phrase_status("some"), PhraseStatus::Incomplete);
-        assert_eq!(context.get_phrase_status("some_phrase"), PhraseStatus::Complete);
+        let doc = PhraseMeta::new().with_doc("Performs");
+        assert_eq!(context.iter().collect::<Vec<PhraseEntry>>(), vec![
+            PhraseEntry { phrase: "perform", status: PhraseStatus::CompleteAndExtendable, meta: Some(&doc) },
+            PhraseEntry { phrase: "perform_task", status: PhraseStatus::Complete, meta: None },
+        ]);
     }
 
     #[test]
-    fn add_three_word_phrase() {
+    fn membership_queries() {
         let mut context = SimplePhraseContext::new();
-        let result = context.add_phrase("some_great_phrase");
+        context.add_phrase("perform_task").unwrap();
 
-        assert_eq!(result, Ok(()));
-        assert_eq!(context.get_phrase_status("some"), PhraseStatus::Incomplete);
-        assert_eq!(context.get_phrase_status("some_great"), PhraseStatus::Incomplete);
-        assert_eq!(context.get_phrase_status("some_great_phrase"), PhraseStatus::Complete);
+        assert!(context.contains_phrase("perform_task"));
+        assert!(!context.contains_phrase("perform"));
+        assert!(context.is_prefix("perform"));
+        assert!(!context.is_prefix("perform_task"));
+        assert!(!context.is_prefix("stop"));
     }
 
     #[test]
-    fn add_three_word_phrase_extra_underscores_no_effect() {
+    fn longest_prefix() {
         let mut context = SimplePhraseContext::new();
-        let result = context.add_phrase("_some__great__phrase");
+        context.set_overlap_policy(OverlapPolicy::AllowCompletePrefix);
+        context.add_phrase("perform").unwrap();
+        context.add_phrase("perform_task").unwrap();
 
-        assert_eq!(result, Ok(()));
-        assert_eq!(context.get_phrase_status("some"), PhraseStatus::Incomplete);
-        assert_eq!(context.get_phrase_status("some_great"), PhraseStatus::Incomplete);
-        assert_eq!(context.get_phrase_status("some_great_phrase"), PhraseStatus::Complete);
-        assert_eq!(context.phrase_count(), 3);
+        assert_eq!(context.longest_prefix_of("perform_task_quickly"), Some("perform_task"));
+        assert_eq!(context.longest_prefix_of("perform_quickly"), Some("perform"));
+        assert_eq!(context.longest_prefix_of("stop_task"), None);
     }
 
     #[test]
-    fn add_three_word_phrase_extra_underscores_end_forces_last_incomplete() {
+    fn try_from_slice() {
+        let context = SimplePhraseContext::try_from(&["perform_task", "stop"][..]).unwrap();
+
+        assert_eq!(context.phrases().collect::<Vec<&str>>(), vec!["perform_task", "stop"]);
+    }
+
+    #[test]
+    fn with_phrases_reports_conflicts() {
+        let context = SimplePhraseContext::with_phrases(["perform_task", "stop"]).unwrap();
+
+        assert_eq!(context.phrases().collect::<Vec<&str>>(), vec!["perform_task", "stop"]);
+        assert_eq!(SimplePhraseContext::with_phrases(["perform_task", "perform", "perform_task_now"]).unwrap_err().0.len(), 2);
+    }
+
+    #[test]
+    fn try_from_vec_aggregates_errors() {
+        let phrases = vec!["perform_task".to_string(), "perform".to_string(), "stop".to_string(), "stop_now".to_string()];
+
+        let errors = SimplePhraseContext::try_from(phrases).unwrap_err();
+
+        assert_eq!(errors.0.iter().map(|e| e.phrase.as_str()).collect::<Vec<&str>>(), vec!["perform", "stop_now"]);
+        assert_eq!(
+            errors.to_string(),
+            "could not add 2 phrase(s): could not add phrase 'perform', conflicts with 'perform_task'; \
+            could not add phrase 'stop_now', conflicts with 'stop'"
+        );
+    }
+
+    #[test]
+    fn from_meta_map() {
+        let mut phrases = HashMap::new();
+        phrases.insert("perform_task".to_string(), PhraseMeta::new().with_doc("Performs"));
+        phrases.insert("perform".to_string(), PhraseMeta::new());
+
+        let context = SimplePhraseContext::try_from(phrases).unwrap();
+
+        assert!(context.contains_phrase("perform"));
+        assert_eq!(context.doc("perform_task"), Some("Performs"));
+    }
+
+    #[test]
+    fn from_meta_map_aggregates_errors() {
+        let mut phrases = HashMap::new();
+        phrases.insert("list_sort".to_string(), PhraseMeta::new().with_free_order());
+        phrases.insert("sort_list".to_string(), PhraseMeta::new().with_free_order());
+        phrases.insert("stop".to_string(), PhraseMeta::new());
+
+        let errors = SimplePhraseContext::try_from(phrases).unwrap_err();
+
+        assert_eq!(errors.0.len(), 1);
+        assert_eq!(errors.0[0].phrase, "sort_list");
+        assert_eq!(errors.0[0].code, SimpleContextCodes::FreeOrderConflict);
+    }
+
+    #[test]
+    fn add_phrases_keeps_successes() {
         let mut context = SimplePhraseContext::new();
-        let result = context.add_phrase("_some__great__phrase__");
+        let result = context.add_phrases(["perform_task", "perform", "stop"]);
 
-        assert_eq!(result, Ok(()));
-        assert_eq!(context.get_phrase_status("some"), PhraseStatus::Incomplete);
-        assert_eq!(context.get_phrase_status("some_great"), PhraseStatus::Incomplete);
-        assert_eq!(context.get_phrase_status("some_great_phrase"), PhraseStatus::Incomplete);
-        assert_eq!(context.phrase_count(), 3);
+        assert!(matches!(result, Err(ContextErrors(errors)) if errors.len() == 1));
+        assert!(context.contains_phrase("stop"));
     }
 
     #[test]
-    fn empty_phrase_ok() {
+    fn phrases_in_insertion_order() {
+        let mut sorted = SimplePhraseContext::new();
+        sorted.add_phrases(["stop", "perform_task", "halt"]).unwrap();
+
+        let mut inserted = SimplePhraseContext::new();
+        inserted.set_phrase_order(PhraseOrder::Insertion);
+        inserted.add_phrases(["stop", "perform_task", "halt", "stop"]).unwrap();
+
+        assert_eq!(sorted.phrases().collect::<Vec<&str>>(), vec!["halt", "perform_task", "stop"]);
+        assert_eq!(inserted.phrases().collect::<Vec<&str>>(), vec!["stop", "perform_task", "halt"]);
+        assert_eq!(inserted.fingerprint(), sorted.fingerprint());
+    }
+
+    #[test]
+    fn iter_phrases_through_trait() {
         let mut context = SimplePhraseContext::new();
-        let result = context.add_phrase("");
+        context.set_phrase_order(PhraseOrder::Insertion);
+        context.add_phrases(["stop", "perform_task"]).unwrap();
+        let boxed: Box<dyn PhraseContext> = Box::new(context);
 
-        assert_eq!(result, Ok(()));
-        assert_eq!(context.phrase_count(), 0);
+        assert_eq!(boxed.iter_phrases().collect::<Vec<String>>(), vec!["stop", "perform_task"]);
+        assert_eq!(STATIC_CONTEXT.iter_phrases().collect::<Vec<String>>(), vec!["perform_task", "run"]);
     }
 
     #[test]
-    fn just_underscore_ok() {
+    fn priority_allows_overlap() {
         let mut context = SimplePhraseContext::new();
-        let result = context.add_phrase("_");
+        context.add_phrase("perform_task_quickly").unwrap();
+        assert!(context.add_phrase("perform_task").is_err());
 
-        assert_eq!(result, Ok(()));
+        context.add_phrase_with_meta("perform_task", PhraseMeta::new().with_priority(1)).unwrap();
+        assert_eq!(context.get_phrase_status("perform_task"), PhraseStatus::CompleteAndExtendable);
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("perform", PhraseMeta::new().with_priority(1)).unwrap();
+        context.add_phrase("perform_task").unwrap();
+        assert_eq!(context.get_phrase_status("perform"), PhraseStatus::CompleteAndExtendable);
+    }
+
+    #[test]
+    fn remove_cleans_unused_prefixes() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrases(["perform_the_task", "perform_the_job", "halt"]).unwrap();
+
+        assert!(context.remove_phrase("perform_the_task"));
+        assert!(!context.remove_phrase("perform_the_task"));
+        assert!(!context.remove_phrase("perform_the"));
+        assert_eq!(context.get_phrase_status("perform_the"), PhraseStatus::Incomplete);
+        assert_eq!(context.get_phrase_status("perform_the_task"), PhraseStatus::NotAPhrase);
+
+        assert!(context.remove_phrase("perform_the_job"));
+        assert_eq!(context.get_phrase_status("perform"), PhraseStatus::NotAPhrase);
+        assert_eq!(context.phrase_count(), 1);
+
+        // prefix freed by the removal can be added as a phrase
+        context.add_phrases(["perform_the_task"]).unwrap();
+        context.remove_phrase("perform_the_task");
+        context.add_phrase("perform").unwrap();
+        assert!(context.contains_phrase("perform"));
+    }
+
+    #[test]
+    fn remove_complete_prefix() {
+        let mut context = SimplePhraseContext::new();
+        context.set_overlap_policy(OverlapPolicy::AllowCompletePrefix);
+        context.add_phrases(["perform", "perform_task"]).unwrap();
+        context.add_phrase_with_meta("halt_now", PhraseMeta::new().with_free_order()).unwrap();
+
+        assert!(context.remove_phrase("perform_task"));
+        assert_eq!(context.get_phrase_status("perform"), PhraseStatus::Complete);
+
+        assert!(context.remove_phrase("now_halt"));
+        assert_eq!(context.get_phrase_status("halt_now"), PhraseStatus::NotAPhrase);
+        assert_eq!(context.get_phrase_status("now"), PhraseStatus::NotAPhrase);
+    }
+
+    #[test]
+    fn clear_and_extend() {
+        let mut context = SimplePhraseContext::new();
+        context.set_overlap_policy(OverlapPolicy::AllowCompletePrefix);
+        context.add_phrase_with_meta("perform_task", PhraseMeta::new().with_doc("Performs")).unwrap();
+
+        context.clear();
         assert_eq!(context.phrase_count(), 0);
+        assert_eq!(context.doc("perform_task"), None);
+
+        context.extend(["perform", "perform_task"]).unwrap();
+        assert_eq!(context.get_phrase_status("perform"), PhraseStatus::CompleteAndExtendable);
+    }
+
+    static STATIC_ENTRIES: phf::Map<&'static str, PhraseStatus> = phf::phf_map! {
+        "perform" => PhraseStatus::Incomplete,
+        "perform_task" => PhraseStatus::Complete,
+        "run" => PhraseStatus::Complete,
+    };
+    static STATIC_CONTEXT: StaticPhraseContext = StaticPhraseContext::new(&STATIC_ENTRIES);
+
+    #[test]
+    fn static_context_lookups() {
+        assert_eq!(STATIC_CONTEXT.get_phrase_status("perform"), PhraseStatus::Incomplete);
+        assert_eq!(STATIC_CONTEXT.get_phrase_status("perform_task"), PhraseStatus::Complete);
+        assert_eq!(STATIC_CONTEXT.get_phrase_status("task"), PhraseStatus::NotAPhrase);
+        assert!(STATIC_CONTEXT.contains_phrase("run"));
+        assert_eq!(STATIC_CONTEXT.phrase_count(), 3);
     }
 
+    #[cfg(feature = "codegen")]
     #[test]
-    fn error_adding_complete_phrase_when_already_incomplete() {
+    fn static_entries_source() {
         let mut context = SimplePhraseContext::new();
-        context.add_phrase("some_phrase").unwrap();
+        context.add_phrase("run").unwrap();
+        context.add_phrase("perform_task").unwrap();
 
-        let result = context.add_phrase("some");
+        let source = context.static_entries();
 
-        assert_eq!(result, Err(SimpleContextCodes::IncompleteVersionExists));
+        assert!(source.starts_with("garnish_phrases::phf::Map {"), "{}", source);
+        for entry in [
+            "(\"perform\", garnish_phrases::context::PhraseStatus::Incomplete)",
+            "(\"perform_task\", garnish_phrases::context::PhraseStatus::Complete)",
+            "(\"run\", garnish_phrases::context::PhraseStatus::Complete)",
+        ] {
+            assert!(source.contains(entry), "{}", source);
+        }
+        assert_eq!(source, context.static_entries());
     }
 
     #[test]
-    fn error_adding_incomplete_phrase_when_already_complete() {
+    fn boxed_trait_object() {
         let mut context = SimplePhraseContext::new();
-        context.add_phrase("some").unwrap();
+        context.add_phrase_with_meta("perform_task", PhraseMeta::new().with_doc("does the task")).unwrap();
 
-        let result = context.add_phrase("some_phrase");
+        let boxed: Box<dyn PhraseContext> = Box::new(context);
 
-        assert_eq!(result, Err(SimpleContextCodes::CompleteVersionExists));
+        assert_eq!(boxed.get_phrase_status("perform"), PhraseStatus::Incomplete);
+        assert_eq!(boxed.get_phrase_meta("perform_task").unwrap().doc, Some("does the task".to_string()));
+    }
+
+    #[test]
+    fn completions_of_prefix() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrases(["perform_task", "perform_the_task", "perform", "performance_review"]).unwrap_err();
+        context.add_phrase("run_fast").unwrap();
+
+        assert_eq!(context.completions("perform"), vec!["perform_task".to_string(), "perform_the_task".to_string()]);
+        assert_eq!(context.completions("perform_the"), vec!["perform_the_task".to_string()]);
+        assert_eq!(context.completions("run_fast"), Vec::<String>::new());
+        assert_eq!(STATIC_CONTEXT.completions("perform"), vec!["perform_task".to_string()]);
+    }
+
+    #[test]
+    fn merged_status() {
+        assert_eq!(PhraseStatus::Complete.merge(PhraseStatus::Incomplete), PhraseStatus::CompleteAndExtendable);
+        assert_eq!(PhraseStatus::NotAPhrase.merge(PhraseStatus::Complete), PhraseStatus::Complete);
+        assert_eq!(PhraseStatus::Incomplete.merge(PhraseStatus::NotAPhrase), PhraseStatus::Incomplete);
+        assert_eq!(PhraseStatus::NotAPhrase.merge(PhraseStatus::NotAPhrase), PhraseStatus::NotAPhrase);
+    }
+
+    #[test]
+    fn versioned_meta() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("perform_task", PhraseMeta::new().with_target("run")).unwrap();
+        context.add_phrase_version("perform_task", "v2", PhraseMeta::new().with_target("tasks.run")).unwrap();
+        context.add_phrase_version("load__file", "v1", PhraseMeta::new().with_arity(1)).unwrap();
+
+        assert_eq!(context.phrase_versions("perform_task"), vec!["v2"]);
+        assert_eq!(context.get_versioned_meta("perform_task", "v2").and_then(|meta| meta.target), Some("tasks.run".to_string()));
+        assert_eq!(context.get_versioned_meta("perform_task", "v1"), None);
+        assert_eq!(context.get_phrase_meta("perform_task").and_then(|meta| meta.target), Some("run".to_string()));
+        assert_eq!(context.get_phrase_status("load_file"), PhraseStatus::Complete);
+    }
+
+    #[test]
+    fn add_phrase_with_separator() {
+        let mut context = SimplePhraseContext::new();
+        context.set_separator("-");
+        context.add_phrase_with_meta("perform-the-task", PhraseMeta::new().with_arity(1)).unwrap();
+
+        assert_eq!(context.get_phrase_status("perform_the"), PhraseStatus::Incomplete);
+        assert_eq!(context.get_phrase_status("perform_the_task"), PhraseStatus::Complete);
+        assert_eq!(context.get_phrase_meta("perform_the_task").and_then(|meta| meta.arity), Some(1));
+        assert_eq!(PhraseContext::separator(&context), Some("-".to_string()));
+    }
+
+    #[test]
+    fn add_phrase_with_split_case() {
+        let mut context = SimplePhraseContext::new();
+        context.set_separator("-");
+        context.set_split_case(true);
+        context.add_phrases(["performTheTask", "StopNow", "open-Door", "halt_"]).unwrap();
+
+        assert_eq!(context.phrases().collect::<Vec<&str>>(), vec!["open_door", "perform_the_task", "stop_now"]);
+        assert_eq!(context.get_phrase_status("halt"), PhraseStatus::Incomplete);
+        assert!(context.remove_phrase("performTheTask"));
+    }
+
+    #[test]
+    fn aliases_map_to_phrase() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("start_job").unwrap();
+        context.add_alias("kick_off_job", "start_job").unwrap();
+
+        assert_eq!(context.get_phrase_status("kick_off"), PhraseStatus::Incomplete);
+        assert_eq!(context.alias_target("kick_off_job"), Some("start_job".to_string()));
+        assert_eq!(context.alias_of("kick__off_job"), Some("start_job"));
+        assert_eq!(context.add_alias("start_job_now", "start_job").unwrap_err().code, SimpleContextCodes::CompleteVersionExists);
+
+        assert!(context.remove_phrase("kick_off_job"));
+        assert_eq!(context.alias_of("kick_off_job"), None);
+    }
+
+    #[test]
+    fn removing_phrase_removes_its_aliases() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("start_job").unwrap();
+        context.add_phrase("stop_job").unwrap();
+        context.add_alias("kick_off_job", "start_job").unwrap();
+        context.add_alias("begin_job", "start_job").unwrap();
+        context.add_alias("halt_job", "stop_job").unwrap();
+
+        assert!(context.remove_phrase("start_job"));
+
+        assert_eq!(context.alias_of("kick_off_job"), None);
+        assert_eq!(context.alias_of("begin_job"), None);
+        assert_eq!(context.get_phrase_status("kick_off_job"), PhraseStatus::NotAPhrase);
+        assert_eq!(context.get_phrase_status("kick"), PhraseStatus::NotAPhrase);
+        assert_eq!(context.get_phrase_status("begin_job"), PhraseStatus::NotAPhrase);
+        assert_eq!(context.alias_of("halt_job"), Some("stop_job"));
+        assert_eq!(context.phrases().collect::<Vec<&str>>(), vec!["halt_job", "stop_job"]);
+    }
+
+    #[test]
+    fn template_records_slots() {
+        let mut context = SimplePhraseContext::new();
+        context.add_template("move _ to _", PhraseMeta::new()).unwrap();
+        context.add_template("turn _ on", PhraseMeta::new().with_arity(2)).unwrap();
+
+        let meta = context.phrase_meta("move_to").unwrap();
+        assert_eq!(meta.slots, vec![1, 1]);
+        assert_eq!(meta.arity, Some(2));
+        assert!(meta.trailing_arguments);
+
+        let meta = context.phrase_meta("turn_on").unwrap();
+        assert_eq!(meta.slots, vec![1, 0]);
+        assert_eq!(meta.arity, Some(2));
+        assert!(!meta.trailing_arguments);
+
+        assert_eq!(context.add_template("_ plus _", PhraseMeta::new()).unwrap_err().code, SimpleContextCodes::LeadingSlot);
+    }
+
+    #[test]
+    fn free_order_phrase_registered_in_every_order() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("sort_list_ascending", PhraseMeta::new().with_free_order()).unwrap();
+
+        assert_eq!(context.get_phrase_status("ascending_sort_list"), PhraseStatus::Complete);
+        assert_eq!(context.free_order_phrase("sort_ascending_list"), Some("sort_list_ascending".to_string()));
+        assert_eq!(context.free_order_phrase("sort_list_ascending"), None);
+        assert_eq!(context.phrases().collect::<Vec<&str>>(), vec!["sort_list_ascending"]);
+    }
+
+    #[test]
+    fn free_order_ambiguity_rejected() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("sort_list_ascending", PhraseMeta::new().with_free_order()).unwrap();
+
+        let error = context.try_add_phrase("list_sort_ascending").unwrap_err();
+        assert_eq!((error.conflict.as_str(), error.code), ("sort_list_ascending", SimpleContextCodes::FreeOrderConflict));
+
+        let error = context.try_add_phrase_with_meta("ascending_list_sort", PhraseMeta::new().with_free_order()).unwrap_err();
+        assert_eq!((error.conflict.as_str(), error.code), ("sort_list_ascending", SimpleContextCodes::FreeOrderConflict));
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("list_sort").unwrap();
+        let error = context.try_add_phrase_with_meta("sort_list", PhraseMeta::new().with_free_order()).unwrap_err();
+        assert_eq!((error.conflict.as_str(), error.code), ("list_sort", SimpleContextCodes::FreeOrderConflict));
+        assert!(!context.contains_phrase("sort_list"));
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,230 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+
+use garnish_lang_compiler::parse::{Definition, ParseResult};
+
+use crate::compat::node_text;
+use crate::context::SimplePhraseContext;
+use crate::distance::edit_distance;
+use crate::ext::ParseResultExt;
+use crate::report::ReduceReport;
+use crate::span::Span;
+
+// words close to a phrase that weren't matched as it, e.g. a misspelling
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq)]
+pub struct NearPhrase {
+    pub file: String,
+    pub span: Span,
+    // written words joined like a phrase
+    pub words: String,
+    pub phrase: String,
+    pub distance: usize,
+}
+
+impl Display for NearPhrase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "'{}' in {} at {}:{} is {} edit(s) from phrase '{}'",
+            self.words.replace('_', " "),
+            self.file,
+            self.span.start.line + 1,
+            self.span.start.column + 1,
+            self.distance,
+            self.phrase
+        )
+    }
+}
+
+// identifiers not part of any match, broken wherever a matched word was
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct UnmatchedRun {
+    file: String,
+    words: Vec<(String, Span)>,
+}
+
+// totals over the reports of many files, for maintaining a dictionary across a project
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct CorpusStats {
+    files: BTreeSet<String>,
+    matches: BTreeMap<String, usize>,
+    files_by_phrase: BTreeMap<String, BTreeSet<String>>,
+    unmatched: Vec<UnmatchedRun>,
+}
+
+impl CorpusStats {
+    pub fn new() -> Self {
+        CorpusStats::default()
+    }
+
+    // source is the parse result the report was reduced from
+    // adding the same file again counts its matches again
+    pub fn add(&mut self, file: impl Into<String>, source: &ParseResult, report: &ReduceReport) {
+        let file = file.into();
+
+        for m in &report.matches {
+            *self.matches.entry(m.phrase.clone()).or_insert(0) += 1;
+            self.files_by_phrase.entry(m.phrase.clone()).or_default().insert(file.clone());
+        }
+
+        let matched = report.matches.iter().flat_map(|m| m.words.iter()).collect::<BTreeSet<&usize>>();
+        let mut identifiers = source.indexed_nodes()
+            .filter(|(_, node)| node.get_definition() == Definition::Identifier)
+            .map(|(index, node)| (Span::of_node(node), index, node_text(node)))
+            .collect::<Vec<(Span, usize, String)>>();
+        identifiers.sort();
+
+        let mut run = vec![];
+        for (span, index, word) in identifiers {
+            match matched.contains(&index) {
+                true => self.push_run(&file, std::mem::take(&mut run)),
+                false => run.push((word, span)),
+            }
+        }
+        self.push_run(&file, run);
+
+        self.files.insert(file);
+    }
+
+    fn push_run(&mut self, file: &str, words: Vec<(String, Span)>) {
+        if !words.is_empty() {
+            self.unmatched.push(UnmatchedRun { file: file.to_string(), words });
+        }
+    }
+
+    pub fn merge(&mut self, other: CorpusStats) {
+        self.files.extend(other.files);
+        for (phrase, count) in other.matches {
+            *self.matches.entry(phrase).or_insert(0) += count;
+        }
+        for (phrase, files) in other.files_by_phrase {
+            self.files_by_phrase.entry(phrase).or_default().extend(files);
+        }
+        self.unmatched.extend(other.unmatched);
+    }
+
+    pub fn file_count(&self) -> usize {
+        self.files.len()
+    }
+
+    // matches of each phrase across all files, sorted by phrase
+    pub fn phrase_counts(&self) -> BTreeMap<&str, usize> {
+        self.matches.iter().map(|(phrase, count)| (phrase.as_str(), *count)).collect()
+    }
+
+    // files with at least one match of phrase, sorted
+    pub fn files_with(&self, phrase: &str) -> Vec<&str> {
+        match self.files_by_phrase.get(phrase) {
+            None => vec![],
+            Some(files) => files.iter().map(String::as_str).collect(),
+        }
+    }
+
+    // number of files each phrase was matched in, sorted by phrase
+    pub fn file_counts(&self) -> BTreeMap<&str, usize> {
+        self.files_by_phrase.iter().map(|(phrase, files)| (phrase.as_str(), files.len())).collect()
+    }
+
+    // registered phrases not matched in any file, sorted
+    pub fn unused_phrases<'a>(&self, context: &'a SimplePhraseContext) -> Vec<&'a str> {
+        context.phrases().filter(|phrase| !self.matches.contains_key(*phrase)).collect()
+    }
+
+    // unmatched word sequences within max_distance edits of a phrase of context
+    // sequences are as long as the longest phrase plus one word, sorted by file then position
+    pub fn near_phrases(&self, context: &SimplePhraseContext, max_distance: usize) -> Vec<NearPhrase> {
+        let phrases = context.phrases().collect::<Vec<&str>>();
+        let max_words = phrases.iter().map(|phrase| phrase.split('_').count()).max().unwrap_or_default() + 1;
+        let mut found = vec![];
+
+        for run in &self.unmatched {
+            for start in 0..run.words.len() {
+                for end in start + 1..=run.words.len().min(start + max_words) {
+                    let window = &run.words[start..end];
+                    let words = window.iter().map(|(word, _)| word.as_str()).collect::<Vec<&str>>().join("_");
+
+                    for phrase in phrases.iter() {
+                        let distance = edit_distance(&words, phrase);
+                        if distance <= max_distance {
+                            found.push(NearPhrase {
+                                file: run.file.clone(),
+                                span: Span { start: window[0].1.start, end: window[window.len() - 1].1.end },
+                                words: words.clone(),
+                                phrase: phrase.to_string(),
+                                distance,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        found.sort();
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use garnish_lang_compiler::lex::lex;
+    use garnish_lang_compiler::parse::parse;
+
+    use crate::context::SimplePhraseContext;
+    use crate::corpus::CorpusStats;
+    use crate::reduce_phrases_with_options;
+    use crate::options::ReduceOptions;
+
+    fn context() -> SimplePhraseContext {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrases(["perform_task", "stop", "load_file"]).unwrap();
+        context
+    }
+
+    fn add(stats: &mut CorpusStats, file: &str, input: &str) {
+        let parsed = parse(&lex(input).unwrap()).unwrap();
+        let (_, report) = reduce_phrases_with_options(&parsed, &context(), &ReduceOptions::default()).unwrap();
+        stats.add(file, &parsed, &report);
+    }
+
+    #[test]
+    fn counts_across_files() {
+        let mut stats = CorpusStats::new();
+        add(&mut stats, "a.garnish", "perform 5 task");
+        add(&mut stats, "b.garnish", "perform 10 task");
+        add(&mut stats, "c.garnish", "stop");
+
+        assert_eq!(stats.file_count(), 3);
+        assert_eq!(stats.phrase_counts().into_iter().collect::<Vec<(&str, usize)>>(), vec![("perform_task", 2), ("stop", 1)]);
+        assert_eq!(stats.files_with("perform_task"), vec!["a.garnish", "b.garnish"]);
+        assert_eq!(stats.file_counts().get("stop"), Some(&1));
+        assert_eq!(stats.unused_phrases(&context()), vec!["load_file"]);
+    }
+
+    #[test]
+    fn merged_stats() {
+        let mut first = CorpusStats::new();
+        add(&mut first, "a.garnish", "perform 5 task");
+        let mut second = CorpusStats::new();
+        add(&mut second, "b.garnish", "perform 10 task");
+
+        first.merge(second);
+
+        assert_eq!(first.phrase_counts().get("perform_task"), Some(&2));
+        assert_eq!(first.files_with("perform_task"), vec!["a.garnish", "b.garnish"]);
+    }
+
+    #[test]
+    fn misspelled_phrase_is_near() {
+        let mut stats = CorpusStats::new();
+        add(&mut stats, "a.garnish", "perform 5 tsk");
+        add(&mut stats, "b.garnish", "perform 5 task");
+
+        let near = stats.near_phrases(&context(), 1);
+
+        assert_eq!(near.len(), 1);
+        assert_eq!(near[0].file, "a.garnish");
+        assert_eq!(near[0].phrase, "perform_task");
+        assert_eq!(near[0].words, "perform_tsk");
+        assert_eq!(near[0].to_string(), "'perform tsk' in a.garnish at 1:1 is 1 edit(s) from phrase 'perform_task'");
+    }
+}
@@ -0,0 +1,63 @@
+// minimal csv reading for dictionaries
+// fields may be quoted to contain commas, a doubled quote is a literal quote
+// records can't span lines
+
+// records with their 1 based line, blank lines are skipped
+pub(crate) fn records(input: &str) -> impl Iterator<Item = (usize, Result<Vec<String>, String>)> + '_ {
+    input.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| (index + 1, fields(line)))
+}
+
+fn fields(line: &str) -> Result<Vec<String>, String> {
+    let mut fields = vec![];
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                current.push('"');
+            }
+            (true, '"') => quoted = false,
+            (false, '"') if current.trim().is_empty() => {
+                current.clear();
+                quoted = true;
+            }
+            (false, ',') => fields.push(std::mem::take(&mut current)),
+            (_, c) => current.push(c),
+        }
+    }
+
+    if quoted {
+        return Err("unterminated quoted field".to_string());
+    }
+
+    fields.push(current);
+    Ok(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::csv::records;
+
+    #[test]
+    fn quoted_fields() {
+        let parsed = records("a,\"b, c\",\"say \"\"hi\"\"\"\n\nd").collect::<Vec<_>>();
+
+        assert_eq!(parsed, vec![
+            (1, Ok(vec!["a".to_string(), "b, c".to_string(), "say \"hi\"".to_string()])),
+            (3, Ok(vec!["d".to_string()])),
+        ]);
+    }
+
+    #[test]
+    fn unterminated_quote() {
+        let parsed = records("a,\"b").collect::<Vec<_>>();
+
+        assert_eq!(parsed, vec![(1, Err("unterminated quoted field".to_string()))]);
+    }
+}
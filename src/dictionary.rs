@@ -0,0 +1,848 @@
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::context::{ContextError, SimplePhraseContext};
+use crate::csv;
+use crate::toml;
+use crate::meta::{ApplyForm, ArgumentOrder, Deprecation, PhraseMeta, PhraseSource, SyntacticPosition, Visibility};
+
+// bump when the serialized layout changes in a way older readers can't handle
+// and add a migration step for the previous version
+pub const DICTIONARY_VERSION: u64 = 1;
+pub const MIN_DICTIONARY_VERSION: u64 = 1;
+
+// features the versioned format supports that a legacy list can't express
+pub const RICH_FEATURES: &[&str] = &["version", "doc", "examples", "deprecated", "visibility", "apply", "target", "argument_order", "arity", "max_arity", "argument_names", "tags", "positions", "weight", "priority", "free_order", "trailing_arguments", "slots", "includes"];
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DictionaryEntry {
+    pub phrase: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deprecated: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<String>,
+    #[serde(default, skip_serializing_if = "is_public")]
+    pub visibility: Visibility,
+    #[serde(default, skip_serializing_if = "is_apply_to")]
+    pub apply: ApplyForm,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub argument_order: Option<ArgumentOrder>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arity: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_arity: Option<usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub argument_names: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub positions: Vec<SyntacticPosition>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<i64>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub free_order: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub trailing_arguments: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub slots: Vec<usize>,
+}
+
+fn is_apply_to(apply: &ApplyForm) -> bool {
+    *apply == ApplyForm::ApplyTo
+}
+
+fn is_public(visibility: &Visibility) -> bool {
+    *visibility == Visibility::Public
+}
+
+impl DictionaryEntry {
+    pub fn new(phrase: &str) -> Self {
+        DictionaryEntry::with_meta(phrase, PhraseMeta::default())
+    }
+
+    pub fn with_meta(phrase: &str, meta: PhraseMeta) -> Self {
+        DictionaryEntry {
+            phrase: phrase.to_string(),
+            doc: meta.doc,
+            examples: meta.examples,
+            deprecated: meta.deprecated.is_some(),
+            replacement: meta.deprecated.and_then(|deprecation| deprecation.replacement),
+            visibility: meta.visibility,
+            apply: meta.apply,
+            target: meta.target,
+            argument_order: meta.argument_order,
+            arity: meta.arity,
+            max_arity: meta.max_arity,
+            argument_names: meta.argument_names,
+            tags: meta.tags,
+            positions: meta.positions,
+            weight: meta.weight,
+            priority: meta.priority,
+            free_order: meta.free_order,
+            trailing_arguments: meta.trailing_arguments,
+            slots: meta.slots,
+        }
+    }
+
+    pub fn meta(&self) -> PhraseMeta {
+        PhraseMeta {
+            doc: self.doc.clone(),
+            examples: self.examples.clone(),
+            deprecated: match self.deprecated {
+                false => None,
+                true => Some(Deprecation { replacement: self.replacement.clone() }),
+            },
+            visibility: self.visibility,
+            source: None,
+            apply: self.apply,
+            target: self.target.clone(),
+            argument_order: self.argument_order.clone(),
+            arity: self.arity,
+            max_arity: self.max_arity,
+            argument_names: self.argument_names.clone(),
+            tags: self.tags.clone(),
+            positions: self.positions.clone(),
+            weight: self.weight,
+            priority: self.priority,
+            free_order: self.free_order,
+            trailing_arguments: self.trailing_arguments,
+            slots: self.slots.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Dictionary {
+    pub version: u64,
+    // paths of dictionaries loaded before this one's phrases, relative to this file
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub includes: Vec<String>,
+    pub phrases: Vec<DictionaryEntry>,
+}
+
+#[derive(Debug, Error)]
+pub enum DictionaryError {
+    #[error("dictionary is not valid json")]
+    Json(#[from] serde_json::Error),
+    #[error("dictionary is not valid toml, line {line}: {message}")]
+    Toml { line: usize, message: String },
+    #[error("dictionary entry {}{} is invalid: {message}", .index + 1, describe_phrase(.phrase))]
+    Entry { index: usize, phrase: Option<String>, message: String },
+    #[error("dictionary has no schema version")]
+    MissingVersion,
+    #[error("dictionary schema version {found} is newer than supported version {supported}")]
+    NewerVersion { found: u64, supported: u64 },
+    #[error("dictionary schema version {found} is older than minimum supported version {minimum}")]
+    OlderVersion { found: u64, minimum: u64 },
+    #[error("could not add phrase '{}' from dictionary", .0.phrase)]
+    Phrase(#[from] ContextError),
+    #[error("dictionary could not be read")]
+    Io(#[from] std::io::Error),
+    #[error("dictionary includes itself: {}", .0.join(" -> "))]
+    IncludeCycle(Vec<String>),
+    #[error("dictionary csv header is invalid: {0}")]
+    CsvHeader(String),
+    #[error("dictionary csv has {} invalid row(s): {}", .0.len(), describe_rows(.0))]
+    CsvRows(Vec<CsvRowError>),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Error)]
+#[error("line {line}: {message}")]
+pub struct CsvRowError {
+    // 1 based line in the csv input
+    pub line: usize,
+    pub message: String,
+}
+
+fn describe_phrase(phrase: &Option<String>) -> String {
+    match phrase {
+        None => String::new(),
+        Some(phrase) => format!(" '{}'", phrase),
+    }
+}
+
+fn describe_rows(rows: &[CsvRowError]) -> String {
+    rows.iter().map(|row| row.to_string()).collect::<Vec<String>>().join("; ")
+}
+
+// columns a csv dictionary can have, only phrase is required
+pub const CSV_COLUMNS: &[&str] = &["phrase", "canonical", "arity", "argument_names", "tags", "doc"];
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum DictionaryWarning {
+    // dictionary was a plain list of underscore separated phrases
+    LegacyFormat {
+        phrases: Vec<String>,
+        upgraded_to: u64,
+        available_features: Vec<&'static str>,
+    },
+}
+
+impl Display for DictionaryWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DictionaryWarning::LegacyFormat { phrases, upgraded_to, available_features } => write!(
+                f,
+                "legacy phrase list with {} entries upgraded to schema version {}, entries can also use: {}",
+                phrases.len(),
+                upgraded_to,
+                available_features.join(", ")
+            ),
+        }
+    }
+}
+
+impl Dictionary {
+    pub fn new() -> Self {
+        Dictionary { version: DICTIONARY_VERSION, includes: vec![], phrases: vec![] }
+    }
+
+    pub fn from_context(context: &SimplePhraseContext) -> Self {
+        Dictionary {
+            version: DICTIONARY_VERSION,
+            includes: vec![],
+            phrases: context.complete_phrases().into_iter()
+                .map(|phrase| {
+                    DictionaryEntry::with_meta(phrase, context.phrase_meta(phrase).cloned().unwrap_or_default())
+                })
+                .collect(),
+        }
+    }
+
+    pub fn from_json(input: &str) -> Result<Self, DictionaryError> {
+        Dictionary::from_json_with_warnings(input).map(|(dictionary, _)| dictionary)
+    }
+
+    pub fn from_json_with_warnings(input: &str) -> Result<(Self, Vec<DictionaryWarning>), DictionaryError> {
+        let value: Value = serde_json::from_str(input)?;
+
+        if value.is_array() {
+            let phrases: Vec<String> = serde_json::from_value(value)?;
+            return Ok(Dictionary::from_legacy(phrases));
+        }
+
+        Ok((Dictionary::from_value(value)?, vec![]))
+    }
+
+    // same layout as json, see toml.rs for the supported subset
+    // version = 1 at the top and one [[phrases]] table per entry
+    pub fn from_toml(input: &str) -> Result<Self, DictionaryError> {
+        let value = toml::parse(input).map_err(|(line, message)| DictionaryError::Toml { line, message })?;
+        Dictionary::from_value(value)
+    }
+
+    // entries are read one at a time so an error can say which one it is
+    fn from_value(value: Value) -> Result<Self, DictionaryError> {
+        let version = match value.get("version") {
+            None => Err(DictionaryError::MissingVersion)?,
+            Some(version) => version.as_u64().ok_or(DictionaryError::MissingVersion)?,
+        };
+
+        check_version(version)?;
+
+        #[derive(Deserialize)]
+        struct Layout {
+            version: u64,
+            #[serde(default)]
+            includes: Vec<String>,
+            phrases: Vec<Value>,
+        }
+
+        let layout: Layout = serde_json::from_value(value)?;
+        let phrases = layout.phrases.into_iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                serde_json::from_value(entry.clone()).map_err(|error| DictionaryError::Entry {
+                    index,
+                    phrase: entry.get("phrase").and_then(Value::as_str).map(str::to_string),
+                    message: error.to_string(),
+                })
+            })
+            .collect::<Result<Vec<DictionaryEntry>, DictionaryError>>()?;
+
+        Ok(Dictionary { version: layout.version, includes: layout.includes, phrases })
+    }
+
+    // upgrade plain list of phrases to current schema
+    pub fn from_legacy(phrases: Vec<String>) -> (Self, Vec<DictionaryWarning>) {
+        let dictionary = Dictionary {
+            version: DICTIONARY_VERSION,
+            includes: vec![],
+            phrases: phrases.iter().map(|phrase| DictionaryEntry::new(phrase)).collect(),
+        };
+
+        let warning = DictionaryWarning::LegacyFormat {
+            phrases,
+            upgraded_to: DICTIONARY_VERSION,
+            available_features: RICH_FEATURES.to_vec(),
+        };
+
+        (dictionary, vec![warning])
+    }
+
+    // header row names the columns, in any order, from CSV_COLUMNS
+    // canonical is emitted in place of the phrase and tags are separated by ';'
+    // every invalid row is reported, not just the first
+    pub fn from_csv(input: &str) -> Result<Self, DictionaryError> {
+        let mut records = csv::records(input);
+
+        let header = match records.next() {
+            None => Err(DictionaryError::CsvHeader("no header row".to_string()))?,
+            Some((_, Err(message))) => Err(DictionaryError::CsvHeader(message))?,
+            Some((_, Ok(header))) => header,
+        };
+
+        let columns = header.iter().map(|column| column.trim().to_lowercase()).collect::<Vec<String>>();
+        for (index, column) in columns.iter().enumerate() {
+            if !CSV_COLUMNS.contains(&column.as_str()) {
+                Err(DictionaryError::CsvHeader(format!("unknown column '{}'", column)))?;
+            }
+            if columns[..index].contains(column) {
+                Err(DictionaryError::CsvHeader(format!("duplicate column '{}'", column)))?;
+            }
+        }
+        if !columns.iter().any(|column| column == "phrase") {
+            Err(DictionaryError::CsvHeader("missing phrase column".to_string()))?;
+        }
+
+        let mut phrases = vec![];
+        let mut errors = vec![];
+
+        for (line, record) in records {
+            match record.and_then(|fields| csv_entry(&columns, fields)) {
+                Ok(entry) => phrases.push(entry),
+                Err(message) => errors.push(CsvRowError { line, message }),
+            }
+        }
+
+        match errors.is_empty() {
+            true => Ok(Dictionary { version: DICTIONARY_VERSION, includes: vec![], phrases }),
+            false => Err(DictionaryError::CsvRows(errors)),
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, DictionaryError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn to_context(&self) -> Result<SimplePhraseContext, DictionaryError> {
+        let mut context = SimplePhraseContext::new();
+
+        for entry in self.phrases.iter() {
+            context.try_add_phrase_with_meta(&entry.phrase, entry.meta())?;
+        }
+
+        Ok(context)
+    }
+
+    // loads a json dictionary file, or toml for a .toml extension, and everything it includes into one context
+    // includes are added before the including file's phrases, each phrase is sourced to the file defining it
+    // a file included more than once is only loaded the first time
+    pub fn load_file(path: impl AsRef<Path>) -> Result<SimplePhraseContext, DictionaryError> {
+        let mut context = SimplePhraseContext::new();
+        load_into(path.as_ref(), &mut context, &mut vec![], &mut HashSet::new())?;
+        Ok(context)
+    }
+
+    // record given source on every phrase, used when loading from files and libraries
+    pub fn to_context_from(&self, source: PhraseSource) -> Result<SimplePhraseContext, DictionaryError> {
+        let mut context = SimplePhraseContext::new();
+
+        for entry in self.phrases.iter() {
+            context.try_add_phrase_with_meta(&entry.phrase, entry.meta().with_source(source.clone()))?;
+        }
+
+        Ok(context)
+    }
+}
+
+fn load_into(
+    path: &Path,
+    context: &mut SimplePhraseContext,
+    including: &mut Vec<PathBuf>,
+    loaded: &mut HashSet<PathBuf>,
+) -> Result<(), DictionaryError> {
+    let path = path.canonicalize()?;
+
+    if including.contains(&path) {
+        let mut cycle = including.iter().skip_while(|file| **file != path).cloned().collect::<Vec<PathBuf>>();
+        cycle.push(path);
+        return Err(DictionaryError::IncludeCycle(cycle.iter().map(|file| file.display().to_string()).collect()));
+    }
+    if !loaded.insert(path.clone()) {
+        return Ok(());
+    }
+
+    let text = fs::read_to_string(&path)?;
+    let dictionary = match path.extension().is_some_and(|extension| extension == "toml") {
+        true => Dictionary::from_toml(&text)?,
+        false => Dictionary::from_json(&text)?,
+    };
+
+    including.push(path.clone());
+    let directory = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    for include in dictionary.includes.iter() {
+        load_into(&directory.join(include), context, including, loaded)?;
+    }
+    including.pop();
+
+    let source = PhraseSource::File(path.display().to_string());
+    for entry in dictionary.phrases.iter() {
+        context.try_add_phrase_with_meta(&entry.phrase, entry.meta().with_source(source.clone()))?;
+    }
+
+    Ok(())
+}
+
+fn csv_entry(columns: &[String], fields: Vec<String>) -> Result<DictionaryEntry, String> {
+    if fields.len() != columns.len() {
+        return Err(format!("expected {} fields, found {}", columns.len(), fields.len()));
+    }
+
+    let mut entry = DictionaryEntry::new("");
+    for (column, field) in columns.iter().zip(fields) {
+        let field = field.trim();
+        match column.as_str() {
+            "phrase" => entry.phrase = field.to_string(),
+            "canonical" if !field.is_empty() => entry.target = Some(field.to_string()),
+            "arity" if !field.is_empty() => {
+                entry.arity = Some(field.parse().map_err(|_| format!("arity '{}' is not a number", field))?)
+            }
+            "tags" => {
+                entry.tags = field.split(';').map(str::trim).filter(|tag| !tag.is_empty()).map(str::to_string).collect()
+            }
+            "doc" if !field.is_empty() => entry.doc = Some(field.to_string()),
+            _ => (),
+        }
+    }
+
+    match entry.phrase.is_empty() {
+        true => Err("phrase is empty".to_string()),
+        false => Ok(entry),
+    }
+}
+
+impl SimplePhraseContext {
+    pub fn from_json(input: &str) -> Result<Self, DictionaryError> {
+        Dictionary::from_json(input)?.to_context()
+    }
+
+    pub fn from_toml(input: &str) -> Result<Self, DictionaryError> {
+        Dictionary::from_toml(input)?.to_context()
+    }
+}
+
+impl Default for Dictionary {
+    fn default() -> Self {
+        Dictionary::new()
+    }
+}
+
+fn check_version(version: u64) -> Result<(), DictionaryError> {
+    if version > DICTIONARY_VERSION {
+        Err(DictionaryError::NewerVersion { found: version, supported: DICTIONARY_VERSION })
+    } else if version < MIN_DICTIONARY_VERSION {
+        Err(DictionaryError::OlderVersion { found: version, minimum: MIN_DICTIONARY_VERSION })
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::{PhraseContext, PhraseStatus, SimplePhraseContext};
+    use crate::meta::{ApplyForm, ArgumentOrder, Deprecation, PhraseMeta, PhraseSource, SyntacticPosition, Visibility};
+    use crate::dictionary::{CsvRowError, Dictionary, DictionaryError, DictionaryWarning, DICTIONARY_VERSION, RICH_FEATURES};
+
+    #[test]
+    fn load_current_version() {
+        let input = r#"{ "version": 1, "phrases": [{ "phrase": "perform_task" }] }"#;
+
+        let dictionary = Dictionary::from_json(input).unwrap();
+        let context = dictionary.to_context().unwrap();
+
+        assert_eq!(dictionary.version, DICTIONARY_VERSION);
+        assert_eq!(context.get_phrase_status("perform_task"), PhraseStatus::Complete);
+    }
+
+    #[test]
+    fn load_doc() {
+        let input = r#"{ "version": 1, "phrases": [{ "phrase": "perform_task", "doc": "Performs a task." }] }"#;
+
+        let context = Dictionary::from_json(input).unwrap().to_context().unwrap();
+
+        assert_eq!(context.doc("perform_task"), Some("Performs a task."));
+    }
+
+    #[test]
+    fn load_examples() {
+        let input = r#"{ "version": 1, "phrases": [{ "phrase": "perform_task", "examples": ["perform 5 task"] }] }"#;
+
+        let context = Dictionary::from_json(input).unwrap().to_context().unwrap();
+
+        assert_eq!(context.examples("perform_task"), &["perform 5 task".to_string()]);
+    }
+
+    #[test]
+    fn load_deprecated() {
+        let input = r#"{ "version": 1, "phrases": [{ "phrase": "do_task", "deprecated": true, "replacement": "perform_task" }] }"#;
+
+        let context = Dictionary::from_json(input).unwrap().to_context().unwrap();
+
+        assert_eq!(
+            context.phrase_meta("do_task").unwrap().deprecated,
+            Some(Deprecation { replacement: Some("perform_task".to_string()) })
+        );
+    }
+
+    #[test]
+    fn load_visibility() {
+        let input = r#"{ "version": 1, "phrases": [{ "phrase": "perform_task", "visibility": "internal" }] }"#;
+
+        let context = Dictionary::from_json(input).unwrap().to_context().unwrap();
+
+        assert_eq!(context.phrase_meta("perform_task").unwrap().visibility, Visibility::Internal);
+    }
+
+    #[test]
+    fn load_apply() {
+        let input = r#"{ "version": 1, "phrases": [{ "phrase": "perform_task", "apply": "empty_apply" }] }"#;
+
+        let context = Dictionary::from_json(input).unwrap().to_context().unwrap();
+
+        assert_eq!(context.phrase_meta("perform_task").unwrap().apply, ApplyForm::EmptyApply);
+    }
+
+    #[test]
+    fn load_target() {
+        let input = r#"{ "version": 1, "phrases": [{ "phrase": "perform_task", "target": "tasks.perform" }] }"#;
+
+        let context = Dictionary::from_json(input).unwrap().to_context().unwrap();
+
+        assert_eq!(context.phrase_meta("perform_task").unwrap().target, Some("tasks.perform".to_string()));
+    }
+
+    #[test]
+    fn load_argument_order() {
+        let input = r#"{ "version": 1, "phrases": [{ "phrase": "divide_by", "argument_order": { "permutation": [1, 0] } }] }"#;
+
+        let context = Dictionary::from_json(input).unwrap().to_context().unwrap();
+
+        assert_eq!(context.phrase_meta("divide_by").unwrap().argument_order, Some(ArgumentOrder::Permutation(vec![1, 0])));
+    }
+
+    #[test]
+    fn load_arity() {
+        let input = r#"{ "version": 1, "phrases": [{ "phrase": "double_value", "arity": 1 }] }"#;
+
+        let context = Dictionary::from_json(input).unwrap().to_context().unwrap();
+
+        assert_eq!(context.phrase_meta("double_value").unwrap().arity, Some(1));
+    }
+
+    #[test]
+    fn load_argument_names() {
+        let input = r#"{ "version": 1, "phrases": [{ "phrase": "copy_to", "argument_names": ["source", "destination"] }] }"#;
+
+        let context = Dictionary::from_json(input).unwrap().to_context().unwrap();
+
+        assert_eq!(context.phrase_meta("copy_to").unwrap().argument_names, vec!["source", "destination"]);
+    }
+
+    #[test]
+    fn load_weight() {
+        let input = r#"{ "version": 1, "phrases": [{ "phrase": "perform_task", "weight": 12 }] }"#;
+
+        let context = Dictionary::from_json(input).unwrap().to_context().unwrap();
+
+        assert_eq!(context.phrase_meta("perform_task").unwrap().weight, Some(12));
+    }
+
+    #[test]
+    fn load_positions() {
+        let input = r#"{ "version": 1, "phrases": [{ "phrase": "perform_task", "positions": ["expression_root", "list_element"] }] }"#;
+
+        let context = Dictionary::from_json(input).unwrap().to_context().unwrap();
+
+        assert_eq!(context.phrase_meta("perform_task").unwrap().positions, vec![SyntacticPosition::ExpressionRoot, SyntacticPosition::ListElement]);
+    }
+
+    #[test]
+    fn load_tags() {
+        let input = r#"{ "version": 1, "phrases": [{ "phrase": "perform_task", "tags": ["tasks", "core"] }] }"#;
+
+        let context = Dictionary::from_json(input).unwrap().to_context().unwrap();
+
+        assert_eq!(context.phrase_meta("perform_task").unwrap().tags, vec!["tasks".to_string(), "core".to_string()]);
+    }
+
+    #[test]
+    fn context_with_source() {
+        let input = r#"{ "version": 1, "phrases": [{ "phrase": "perform_task" }] }"#;
+
+        let context = Dictionary::from_json(input).unwrap()
+            .to_context_from(PhraseSource::File("base.json".to_string()))
+            .unwrap();
+
+        assert_eq!(context.source("perform_task"), Some(&PhraseSource::File("base.json".to_string())));
+    }
+
+    #[test]
+    fn missing_version() {
+        let input = r#"{ "phrases": [{ "phrase": "perform_task" }] }"#;
+
+        let result = Dictionary::from_json(input);
+
+        assert!(matches!(result, Err(DictionaryError::MissingVersion)));
+    }
+
+    #[test]
+    fn newer_version() {
+        let input = r#"{ "version": 100, "phrases": [] }"#;
+
+        let result = Dictionary::from_json(input);
+
+        assert!(matches!(result, Err(DictionaryError::NewerVersion { found: 100, supported: DICTIONARY_VERSION })));
+    }
+
+    #[test]
+    fn older_version() {
+        let input = r#"{ "version": 0, "phrases": [] }"#;
+
+        let result = Dictionary::from_json(input);
+
+        assert!(matches!(result, Err(DictionaryError::OlderVersion { found: 0, .. })));
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("perform_task", PhraseMeta::new().with_doc("Performs a task.").with_example("perform 5 task")).unwrap();
+        context.add_phrase_with_meta("halt", PhraseMeta::new().with_deprecation(Some("stop"))).unwrap();
+        context.add_phrase("stop").unwrap();
+
+        let json = Dictionary::from_context(&context).to_json().unwrap();
+        let dictionary = Dictionary::from_json(&json).unwrap();
+
+        assert_eq!(dictionary, Dictionary::from_context(&context));
+        assert_eq!(dictionary.phrases.len(), 3);
+    }
+
+    #[test]
+    fn conflicting_entry() {
+        let input = r#"{ "version": 1, "phrases": [{ "phrase": "perform_task" }, { "phrase": "perform" }] }"#;
+
+        let result = Dictionary::from_json(input).unwrap().to_context();
+
+        assert!(matches!(result, Err(DictionaryError::Phrase(_))));
+        assert_eq!(result.unwrap_err().to_string(), "could not add phrase 'perform' from dictionary");
+    }
+
+    #[test]
+    fn legacy_list_upgraded() {
+        let input = r#"["perform_task", "stop"]"#;
+
+        let (dictionary, warnings) = Dictionary::from_json_with_warnings(input).unwrap();
+        let context = dictionary.to_context().unwrap();
+
+        assert_eq!(dictionary.version, DICTIONARY_VERSION);
+        assert_eq!(context.get_phrase_status("perform_task"), PhraseStatus::Complete);
+        assert_eq!(context.get_phrase_status("stop"), PhraseStatus::Complete);
+        assert_eq!(warnings, vec![DictionaryWarning::LegacyFormat {
+            phrases: vec!["perform_task".to_string(), "stop".to_string()],
+            upgraded_to: DICTIONARY_VERSION,
+            available_features: RICH_FEATURES.to_vec(),
+        }]);
+    }
+
+    #[test]
+    fn current_version_has_no_warnings() {
+        let input = r#"{ "version": 1, "phrases": [{ "phrase": "perform_task" }] }"#;
+
+        let (_, warnings) = Dictionary::from_json_with_warnings(input).unwrap();
+
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn legacy_list_must_be_strings() {
+        let input = r#"["perform_task", 5]"#;
+
+        let result = Dictionary::from_json(input);
+
+        assert!(matches!(result, Err(DictionaryError::Json(_))));
+    }
+
+    #[test]
+    fn load_csv() {
+        let input = "phrase,canonical,arity,tags,doc\nperform_task,tasks.perform,1,tasks;core,\"Performs a task, once.\"\ndouble_value,,,,\n";
+
+        let context = Dictionary::from_csv(input).unwrap().to_context().unwrap();
+        let meta = context.phrase_meta("perform_task").unwrap();
+
+        assert_eq!(meta.target, Some("tasks.perform".to_string()));
+        assert_eq!(meta.arity, Some(1));
+        assert_eq!(meta.tags, vec!["tasks".to_string(), "core".to_string()]);
+        assert_eq!(meta.doc, Some("Performs a task, once.".to_string()));
+        assert_eq!(context.get_phrase_status("double_value"), PhraseStatus::Complete);
+    }
+
+    #[test]
+    fn csv_columns_any_order() {
+        let dictionary = Dictionary::from_csv("Doc,Phrase\nPerforms a task.,perform_task").unwrap();
+
+        assert_eq!(dictionary.phrases[0].phrase, "perform_task");
+        assert_eq!(dictionary.phrases[0].doc, Some("Performs a task.".to_string()));
+    }
+
+    #[test]
+    fn csv_unknown_column() {
+        let result = Dictionary::from_csv("phrase,weight\nperform_task,2");
+
+        assert!(matches!(result, Err(DictionaryError::CsvHeader(message)) if message == "unknown column 'weight'"));
+    }
+
+    #[test]
+    fn csv_missing_phrase_column() {
+        let result = Dictionary::from_csv("doc\nPerforms a task.");
+
+        assert!(matches!(result, Err(DictionaryError::CsvHeader(message)) if message == "missing phrase column"));
+    }
+
+    #[test]
+    fn csv_reports_every_bad_row() {
+        let result = Dictionary::from_csv("phrase,arity\nperform_task,one\ndouble_value,1\n,2\nhalt");
+
+        match result {
+            Err(DictionaryError::CsvRows(rows)) => assert_eq!(rows, vec![
+                CsvRowError { line: 2, message: "arity 'one' is not a number".to_string() },
+                CsvRowError { line: 4, message: "phrase is empty".to_string() },
+                CsvRowError { line: 5, message: "expected 2 fields, found 1".to_string() },
+            ]),
+            _ => panic!("expected row errors"),
+        }
+    }
+
+    #[test]
+    fn context_from_toml() {
+        let input = r#"
+version = 1
+
+[[phrases]]
+phrase = "perform_task"
+doc = "Performs"
+tags = ["jobs"]
+
+[[phrases]]
+phrase = "halt"
+"#;
+
+        let context = SimplePhraseContext::from_toml(input).unwrap();
+
+        assert_eq!(context.phrases().collect::<Vec<&str>>(), vec!["halt", "perform_task"]);
+        assert_eq!(context.doc("perform_task"), Some("Performs"));
+        assert_eq!(context, SimplePhraseContext::from_json(r#"{ "version": 1, "phrases": [
+            { "phrase": "perform_task", "doc": "Performs", "tags": ["jobs"] },
+            { "phrase": "halt" }
+        ] }"#).unwrap());
+    }
+
+    #[test]
+    fn invalid_entry_is_named() {
+        let error = SimplePhraseContext::from_json(r#"{ "version": 1, "phrases": [{ "phrase": "halt" }, { "phrase": "perform_task", "arity": "two" }] }"#).unwrap_err();
+        assert!(matches!(&error, DictionaryError::Entry { index: 1, phrase: Some(phrase), .. } if phrase == "perform_task"));
+        assert!(error.to_string().starts_with("dictionary entry 2 'perform_task' is invalid: "));
+
+        let error = SimplePhraseContext::from_toml("version = 1\n[[phrases]]\nphrase = \"halt\"\ntags = [\"a\" \"b\"]").unwrap_err();
+        assert!(matches!(error, DictionaryError::Toml { line: 4, .. }));
+    }
+
+    fn write_dictionaries(name: &str, files: &[(&str, &str)]) -> std::path::PathBuf {
+        let directory = std::env::temp_dir().join(format!("garnish_phrases_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(directory.join("shared")).unwrap();
+        for (file, content) in files {
+            std::fs::write(directory.join(file), content).unwrap();
+        }
+
+        directory
+    }
+
+    #[test]
+    fn load_file_with_includes() {
+        let directory = write_dictionaries("includes", &[
+            ("project.json", r#"{ "version": 1, "includes": ["shared/base.json"], "phrases": [{ "phrase": "perform_task" }] }"#),
+            ("shared/base.json", r#"{ "version": 1, "includes": ["common.json"], "phrases": [{ "phrase": "double_value" }] }"#),
+            ("shared/common.json", r#"{ "version": 1, "phrases": [{ "phrase": "halt_now" }] }"#),
+        ]);
+
+        let context = Dictionary::load_file(directory.join("project.json")).unwrap();
+
+        assert_eq!(context.phrases().collect::<Vec<&str>>(), vec!["double_value", "halt_now", "perform_task"]);
+        let base = directory.join("shared/base.json").canonicalize().unwrap();
+        assert_eq!(context.source("double_value"), Some(&PhraseSource::File(base.display().to_string())));
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn load_toml_file() {
+        let directory = write_dictionaries("toml", &[
+            ("project.toml", "version = 1\nincludes = [\"shared/base.json\"]\n\n[[phrases]]\nphrase = \"perform_task\"\n"),
+            ("shared/base.json", r#"{ "version": 1, "phrases": [{ "phrase": "halt_now" }] }"#),
+        ]);
+
+        let context = Dictionary::load_file(directory.join("project.toml")).unwrap();
+
+        assert_eq!(context.phrases().collect::<Vec<&str>>(), vec!["halt_now", "perform_task"]);
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn include_conflict_names_included_file() {
+        let directory = write_dictionaries("include_conflict", &[
+            ("project.json", r#"{ "version": 1, "includes": ["shared/base.json"], "phrases": [{ "phrase": "perform" }] }"#),
+            ("shared/base.json", r#"{ "version": 1, "phrases": [{ "phrase": "perform_task" }] }"#),
+        ]);
+
+        let error = Dictionary::load_file(directory.join("project.json")).unwrap_err();
+
+        match error {
+            DictionaryError::Phrase(error) => assert_eq!(
+                error.conflict_source,
+                Some(PhraseSource::File(directory.join("shared/base.json").canonicalize().unwrap().display().to_string()))
+            ),
+            error => panic!("unexpected error {}", error),
+        }
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn include_cycle() {
+        let directory = write_dictionaries("include_cycle", &[
+            ("project.json", r#"{ "version": 1, "includes": ["shared/base.json"], "phrases": [] }"#),
+            ("shared/base.json", r#"{ "version": 1, "includes": ["../project.json"], "phrases": [] }"#),
+        ]);
+
+        let error = Dictionary::load_file(directory.join("project.json")).unwrap_err();
+
+        assert!(matches!(error, DictionaryError::IncludeCycle(files) if files.len() == 3 && files[0] == files[2]));
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+
+// levenshtein distance over chars
+pub(crate) fn edit_distance(first: &str, second: &str) -> usize {
+    let second_chars = second.chars().collect::<Vec<char>>();
+    let mut previous = (0..=second_chars.len()).collect::<Vec<usize>>();
+    let mut current = vec![0; second_chars.len() + 1];
+
+    for (i, first_char) in first.chars().enumerate() {
+        current[0] = i + 1;
+
+        for (j, second_char) in second_chars.iter().enumerate() {
+            let substitution = previous[j] + if first_char == *second_char { 0 } else { 1 };
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[second_chars.len()]
+}
+
+// most suggestions included in a diagnostic
+const MAX_SUGGESTIONS: usize = 3;
+
+// candidates whose leading words are closest to words first, ties by name, duplicates dropped
+// only candidates within a third of the length of words are suggested, so short words need an exact start
+pub(crate) fn suggestions(words: &str, candidates: impl IntoIterator<Item = String>) -> Vec<String> {
+    let count = words.split('_').count();
+    let max = words.chars().count() / 3;
+    let mut ranked = candidates.into_iter()
+        .map(|candidate| (edit_distance(words, &leading_words(&candidate, count)), candidate))
+        .filter(|(distance, _)| *distance <= max)
+        .collect::<Vec<(usize, String)>>();
+    ranked.sort();
+    ranked.dedup();
+
+    ranked.into_iter().take(MAX_SUGGESTIONS).map(|(_, candidate)| candidate).collect()
+}
+
+fn leading_words(phrase: &str, count: usize) -> String {
+    phrase.split('_').take(count).collect::<Vec<&str>>().join("_")
+}
+
+fn first_word(phrase: &str) -> &str {
+    phrase.split('_').next().unwrap_or_default()
+}
+
+// phrases grouped by first word, built once so each lookup only ranks phrases starting close to the words
+#[derive(Debug, Default)]
+pub(crate) struct SuggestionIndex {
+    by_first_word: HashMap<String, Vec<String>>,
+}
+
+impl SuggestionIndex {
+    pub fn new(phrases: impl IntoIterator<Item = String>) -> Self {
+        let mut by_first_word: HashMap<String, Vec<String>> = HashMap::new();
+        for phrase in phrases {
+            by_first_word.entry(first_word(&phrase).to_string()).or_default().push(phrase);
+        }
+
+        SuggestionIndex { by_first_word }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_first_word.is_empty()
+    }
+
+    // as suggestions, over the phrases whose first word is within reach of the first of words
+    pub fn suggestions(&self, words: &str) -> Vec<String> {
+        let first = first_word(words);
+        let first_length = first.chars().count();
+        let max = words.chars().count() / 3;
+
+        let candidates = self.by_first_word.iter()
+            .filter(|(word, _)| word.chars().count().abs_diff(first_length) <= max && edit_distance(first, word) <= max)
+            .flat_map(|(_, phrases)| phrases.iter().cloned());
+        suggestions(words, candidates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::distance::{edit_distance, suggestions, SuggestionIndex};
+
+    #[test]
+    fn distances() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("task", "task"), 0);
+        assert_eq!(edit_distance("task", "tasks"), 1);
+        assert_eq!(edit_distance("task", "tusk"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn closest_suggestions_first() {
+        let candidates = ["perform_the_task", "perform_task", "perform_tasks", "perform_a_long_task", "perform_task"].map(String::from);
+
+        assert_eq!(suggestions("perform_tsk", candidates), vec!["perform_task", "perform_tasks", "perform_the_task"]);
+    }
+
+    #[test]
+    fn misspelled_start_suggests_longer_phrases() {
+        let candidates = ["perform_task", "stop", "halt_now"].map(String::from);
+
+        assert_eq!(suggestions("perfrom", candidates.clone()), vec!["perform_task"]);
+        assert_eq!(suggestions("sto", candidates.clone()), vec!["stop"]);
+        assert!(suggestions("value", candidates).is_empty());
+    }
+
+    #[test]
+    fn index_matches_full_ranking() {
+        let phrases = ["perform_the_task", "perform_task", "perform_tasks", "stop", "halt_now"].map(String::from);
+        let index = SuggestionIndex::new(phrases.clone());
+
+        for words in ["perfrom", "perform_tsk", "sto", "hlt_now", "value"] {
+            assert_eq!(index.suggestions(words), suggestions(words, phrases.clone()), "{}", words);
+        }
+        assert!(SuggestionIndex::new(vec![]).is_empty());
+    }
+}
@@ -0,0 +1,70 @@
+use garnish_lang_compiler::parse::{Definition, ParseResult};
+
+use crate::compat::node_text;
+use crate::span::Span;
+
+// what to do with a phrase word written twice in a row, e.g. "stop stop" or "perform perform 5 task"
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum DuplicateWords {
+    // each occurrence is matched on its own
+    // "stop stop" is two stops, in "perform perform 5 task" the second perform starts the match
+    // and the first is left as written like any other unfinished phrase
+    #[default]
+    Independent,
+    // matched like Independent, each repeat is reported as ReduceWarning::DuplicateWord
+    Warn,
+    // the reduction fails with PhraseError::DuplicateWord
+    Deny,
+}
+
+// phrase words directly followed by the same word in a whitespace list, with the span of both
+pub(crate) fn duplicate_words(parse_result: &ParseResult, is_phrase_word: impl Fn(&str) -> bool) -> Vec<(String, Span)> {
+    let mut duplicates = vec![];
+
+    for node in parse_result.get_nodes() {
+        if node.get_definition() != Definition::List {
+            continue;
+        }
+
+        // lists lean left, the item before right is left or the right of a list on the left
+        let right = node.get_right().and_then(|index| parse_result.get_node(index));
+        let mut left = node.get_left().and_then(|index| parse_result.get_node(index));
+        if let Some(list) = left.filter(|left| left.get_definition() == Definition::List) {
+            left = list.get_right().and_then(|index| parse_result.get_node(index));
+        }
+
+        if let (Some(left), Some(right)) = (left, right) {
+            let word = node_text(right);
+            if left.get_definition() == Definition::Identifier
+                && right.get_definition() == Definition::Identifier
+                && node_text(left) == word
+                && is_phrase_word(&word)
+            {
+                duplicates.push((word, Span::between(left, right)));
+            }
+        }
+    }
+
+    duplicates.sort_by_key(|(_, span)| *span);
+    duplicates
+}
+
+#[cfg(test)]
+mod tests {
+    use garnish_lang_compiler::lex::lex;
+    use garnish_lang_compiler::parse::parse;
+
+    use crate::duplicate::duplicate_words;
+
+    #[test]
+    fn repeated_phrase_words_found() {
+        let parsed = parse(&lex("stop stop 5 perform perform task, stop value value").unwrap()).unwrap();
+
+        let words = duplicate_words(&parsed, |word| ["stop", "perform", "task"].contains(&word))
+            .into_iter()
+            .map(|(word, span)| (word, span.start.column))
+            .collect::<Vec<(String, usize)>>();
+
+        assert_eq!(words, vec![("stop".to_string(), 0), ("perform".to_string(), 12)]);
+    }
+}
@@ -0,0 +1,85 @@
+use thiserror::Error;
+
+use crate::context::ContextError;
+use crate::dictionary::DictionaryError;
+use crate::limits::MatchLimit;
+use crate::span::Span;
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum PhraseError {
+    #[error("node at index {0} not present")]
+    MissingNode(usize),
+    #[error("node at index {0} has no parent")]
+    MissingParent(usize),
+    #[error("list node at index {0} has no left side")]
+    MissingLeft(usize),
+    #[error("node at index {node} is reached again from node {via}, links form a cycle")]
+    CyclicTree { node: usize, via: usize },
+    #[error("node at index {node} links to node {link} which isn't present")]
+    DanglingLink { node: usize, link: usize },
+    #[error("node at index {node} is a child of node {linked_from} but has parent {parent:?}")]
+    MismatchedParent { node: usize, linked_from: usize, parent: Option<usize> },
+    #[error("argument order for phrase '{phrase}' doesn't fit its {arguments} argument(s)")]
+    ArgumentOrder { phrase: String, arguments: usize },
+    #[error("phrase '{phrase}' expects {expected} argument(s) but was given {found}")]
+    MissingArguments { phrase: String, expected: usize, found: usize },
+    #[error("phrase '{phrase}' takes at most {expected} argument(s) but was given {found}")]
+    ExtraArguments { phrase: String, expected: usize, found: usize },
+    #[error("phrase '{phrase}' is written '{template}' but was given arguments as '{found}'")]
+    TemplateMismatch { phrase: String, template: String, found: String },
+    #[error("phrase '{phrase}' given {found} argument(s) could be its overload for any of {arities:?} arguments")]
+    AmbiguousOverload { phrase: String, found: usize, arities: Vec<usize> },
+    #[error("identifier '{name}' generated for phrase '{phrase}' collides with a program symbol")]
+    NameCollision { phrase: String, name: String },
+    #[error("word '{word}' at {}:{} is repeated", span.start.line + 1, span.start.column + 1)]
+    DuplicateWord { word: String, span: Span },
+    #[error("phrase '{phrase}' at {}:{} has more than {max} {limit}", span.start.line + 1, span.start.column + 1)]
+    LimitExceeded { limit: MatchLimit, max: usize, phrase: String, span: Span },
+    #[error("partial apply needs garnish_lang_compiler 0.0.24, enable the compiler-0-0-24 feature")]
+    UnsupportedPartialApply,
+    #[error("plan was made for a different parse result, fingerprint {planned:x} but found {found:x}")]
+    PlanMismatch { planned: u64, found: u64 },
+    #[error("parse result isn't the one the journal was recorded for")]
+    JournalMismatch,
+    #[error("could not add phrase '{}'", .0.phrase)]
+    Context(#[from] ContextError),
+    #[error("could not load dictionary")]
+    Dictionary(#[from] DictionaryError),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use crate::context::{ContextError, SimpleContextCodes, SimplePhraseContext};
+    use crate::error::PhraseError;
+
+    fn add(context: &mut SimplePhraseContext, phrase: &str) -> Result<(), PhraseError> {
+        context.try_add_phrase(phrase)?;
+        Ok(())
+    }
+
+    #[test]
+    fn context_error_chains_source() {
+        let mut context = SimplePhraseContext::new();
+        add(&mut context, "some_phrase").unwrap();
+
+        let error = add(&mut context, "some").unwrap_err();
+
+        assert_eq!(error.to_string(), "could not add phrase 'some'");
+
+        let context_error = error.source().unwrap().downcast_ref::<ContextError>().unwrap();
+        assert_eq!(context_error.phrase, "some");
+
+        let code = context_error.source().unwrap().downcast_ref::<SimpleContextCodes>();
+        assert_eq!(code, Some(&SimpleContextCodes::IncompleteVersionExists));
+    }
+
+    #[test]
+    fn converts_to_boxed_error() {
+        let error: Box<dyn Error> = PhraseError::MissingNode(4).into();
+
+        assert_eq!(error.to_string(), "node at index 4 not present");
+    }
+}
@@ -0,0 +1,161 @@
+use garnish_lang_compiler::parse::{Definition, ParseNode, ParseResult};
+
+use crate::context::PhraseContext;
+use crate::error::PhraseError;
+use crate::options::ReduceOptions;
+use crate::report::ReduceReport;
+use crate::{node_text, reduce_phrases, reduce_phrases_with_options, tree};
+
+// method forms of the reduce functions for pipeline style code
+pub trait ParseResultPhraseExt {
+    fn reduce_phrases<Context: PhraseContext + ?Sized>(&self, context: &Context) -> Result<ParseResult, PhraseError>;
+
+    fn reduce_phrases_with_options<Context: PhraseContext + ?Sized>(
+        &self,
+        context: &Context,
+        options: &ReduceOptions,
+    ) -> Result<(ParseResult, ReduceReport), PhraseError>;
+
+    // report of what reducing would match, without keeping the reduced tree
+    fn analyze_phrases<Context: PhraseContext + ?Sized>(&self, context: &Context) -> Result<ReduceReport, PhraseError>;
+}
+
+impl ParseResultPhraseExt for ParseResult {
+    fn reduce_phrases<Context: PhraseContext + ?Sized>(&self, context: &Context) -> Result<ParseResult, PhraseError> {
+        reduce_phrases(self, context)
+    }
+
+    fn reduce_phrases_with_options<Context: PhraseContext + ?Sized>(
+        &self,
+        context: &Context,
+        options: &ReduceOptions,
+    ) -> Result<(ParseResult, ReduceReport), PhraseError> {
+        reduce_phrases_with_options(self, context, options)
+    }
+
+    fn analyze_phrases<Context: PhraseContext + ?Sized>(&self, context: &Context) -> Result<ReduceReport, PhraseError> {
+        reduce_phrases_with_options(self, context, &ReduceOptions::default()).map(|(_, report)| report)
+    }
+}
+
+// node walking and splicing used by the reducer, for transforms run before or after it
+pub trait ParseResultExt {
+    // every node with its index, in node list order
+    fn indexed_nodes(&self) -> impl Iterator<Item = (usize, &ParseNode)>;
+
+    // index and text of every identifier node, in node list order
+    fn identifiers(&self) -> Vec<(usize, String)>;
+
+    // errors when links loop, point at nodes that aren't present or a child doesn't link back to its parent
+    fn check_structure(&self) -> Result<(), PhraseError>;
+
+    // copies the node at index and its descendants into target, returns the copy's index which has no parent
+    fn copy_subtree(&self, index: usize, target: &mut ParseResult) -> Result<usize, PhraseError>;
+
+    // appends all nodes of other with their links shifted, returns the offset they were placed at
+    fn append_tree(&mut self, other: &ParseResult) -> usize;
+
+    // points parent, or the root when parent is None, at new where it pointed at old and gives new that parent
+    fn replace_child(&mut self, parent: Option<usize>, old: usize, new: usize) -> Result<(), PhraseError>;
+}
+
+impl ParseResultExt for ParseResult {
+    fn indexed_nodes(&self) -> impl Iterator<Item = (usize, &ParseNode)> {
+        self.get_nodes().iter().enumerate()
+    }
+
+    fn identifiers(&self) -> Vec<(usize, String)> {
+        self.indexed_nodes()
+            .filter(|(_, node)| node.get_definition() == Definition::Identifier)
+            .map(|(index, node)| (index, node_text(node)))
+            .collect()
+    }
+
+    fn check_structure(&self) -> Result<(), PhraseError> {
+        tree::check_structure(self)
+    }
+
+    fn copy_subtree(&self, index: usize, target: &mut ParseResult) -> Result<usize, PhraseError> {
+        tree::copy_subtree(self, index, target).ok_or(PhraseError::MissingNode(index))
+    }
+
+    fn append_tree(&mut self, other: &ParseResult) -> usize {
+        tree::append_tree(self, other)
+    }
+
+    fn replace_child(&mut self, parent: Option<usize>, old: usize, new: usize) -> Result<(), PhraseError> {
+        for index in parent.into_iter().chain([old, new]) {
+            self.get_node(index).ok_or(PhraseError::MissingNode(index))?;
+        }
+
+        self.get_node_mut(new).ok_or(PhraseError::MissingNode(new))?.set_parent(parent);
+        tree::replace_child(self, parent, old, new);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use garnish_lang_compiler::lex::lex;
+    use garnish_lang_compiler::parse::parse;
+
+    use crate::context::SimplePhraseContext;
+    use crate::ext::{ParseResultExt, ParseResultPhraseExt};
+    use crate::reduce_phrases;
+
+    #[test]
+    fn reduce_as_method() {
+        let parsed = parse(&lex("perform 5 task").unwrap()).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+
+        assert_eq!(parsed.reduce_phrases(&context).unwrap(), reduce_phrases(&parsed, &context).unwrap());
+    }
+
+    #[test]
+    fn analyze_as_method() {
+        let parsed = parse(&lex("perform 5 task").unwrap()).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+
+        let report = parsed.analyze_phrases(&context).unwrap();
+
+        assert_eq!(report.matches.len(), 1);
+        assert_eq!(report.matches[0].phrase, "perform_task");
+    }
+
+    #[test]
+    fn splice_reduced_subtree() {
+        let parsed = parse(&lex("value = perform 5 task").unwrap()).unwrap();
+        let (right_side, _) = parsed.identifiers().into_iter().find(|(_, word)| word == "perform").unwrap();
+        let right_side = parsed.get_node(right_side).unwrap().get_parent().unwrap();
+        let right_side = parsed.get_node(right_side).unwrap().get_parent().unwrap();
+        let parent = parsed.get_node(right_side).unwrap().get_parent();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+
+        let mut subtree = garnish_lang_compiler::parse::ParseResult::new();
+        let root = parsed.copy_subtree(right_side, &mut subtree).unwrap();
+        subtree.set_root(root);
+        let reduced = subtree.reduce_phrases(&context).unwrap();
+
+        let mut result = parsed.clone();
+        let offset = result.append_tree(&reduced);
+        result.replace_child(parent, right_side, reduced.get_root() + offset).unwrap();
+
+        assert!(result.check_structure().is_ok());
+        assert_eq!(result.get_node(reduced.get_root() + offset).unwrap().get_parent(), parent);
+        assert!(result.replace_child(parent, right_side, 500).is_err());
+    }
+
+    #[test]
+    fn identifiers_with_indices() {
+        let parsed = parse(&lex("perform 5 task").unwrap()).unwrap();
+
+        assert_eq!(parsed.identifiers(), vec![(0, "perform".to_string()), (4, "task".to_string())]);
+        assert_eq!(parsed.indexed_nodes().count(), parsed.get_nodes().len());
+    }
+}
@@ -0,0 +1,98 @@
+// FNV-1a, used instead of std's hasher so values are stable
+// across rust versions and processes
+const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const PRIME: u64 = 0x100000001b3;
+
+pub(crate) struct Fingerprinter {
+    state: u64,
+}
+
+impl Fingerprinter {
+    pub fn new() -> Self {
+        Fingerprinter { state: OFFSET_BASIS }
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.state ^= value as u64;
+        self.state = self.state.wrapping_mul(PRIME);
+    }
+
+    pub fn write_str(&mut self, value: &str) {
+        for byte in value.bytes() {
+            self.write_u8(byte);
+        }
+
+        // terminate so "ab" + "c" differs from "a" + "bc"
+        self.write_u8(0xff);
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        for byte in value.to_le_bytes() {
+            self.write_u8(byte);
+        }
+    }
+
+    // 0 for None, 1 then the value for Some, so None differs from every value
+    pub fn write_option<T>(&mut self, value: Option<T>, write: impl FnOnce(&mut Self, T)) {
+        match value {
+            None => self.write_u8(0),
+            Some(value) => {
+                self.write_u8(1);
+                write(self, value);
+            }
+        }
+    }
+
+    // length first so items can't run into whatever is written next
+    pub fn write_list<T>(&mut self, items: &[T], mut write: impl FnMut(&mut Self, &T)) {
+        self.write_u64(items.len() as u64);
+        for item in items {
+            write(self, item);
+        }
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fingerprint::Fingerprinter;
+
+    #[test]
+    fn known_value() {
+        let mut fingerprinter = Fingerprinter::new();
+        fingerprinter.write_u8(b'a');
+
+        assert_eq!(fingerprinter.finish(), 0xaf63dc4c8601ec8c);
+    }
+
+    #[test]
+    fn strings_are_delimited() {
+        let mut first = Fingerprinter::new();
+        first.write_str("ab");
+        first.write_str("c");
+
+        let mut second = Fingerprinter::new();
+        second.write_str("a");
+        second.write_str("bc");
+
+        assert_ne!(first.finish(), second.finish());
+    }
+
+    #[test]
+    fn none_differs_from_empty() {
+        let mut none = Fingerprinter::new();
+        none.write_option(None::<&str>, Fingerprinter::write_str);
+
+        let mut empty = Fingerprinter::new();
+        empty.write_option(Some(""), Fingerprinter::write_str);
+
+        let mut empty_list = Fingerprinter::new();
+        empty_list.write_list::<u64>(&[], |fingerprinter, value| fingerprinter.write_u64(*value));
+
+        assert_ne!(none.finish(), empty.finish());
+        assert_ne!(none.finish(), empty_list.finish());
+    }
+}
@@ -0,0 +1,92 @@
+use std::collections::BTreeSet;
+
+use crate::reserved::CollisionSeverity;
+
+// how identifiers generated for matched phrases are named
+// phrases with a target keep it as is, only the joined phrase text is changed
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Hygiene {
+    pub prefix: String,
+    pub suffix: String,
+    // dotted path generated identifiers are accessed through, e.g. "phrases" for phrases.perform_task
+    // only its first segment can collide with a symbol
+    pub namespace: Option<String>,
+    // identifiers already used by the program
+    pub symbols: BTreeSet<String>,
+    pub on_collision: CollisionSeverity,
+}
+
+impl Hygiene {
+    pub fn new() -> Self {
+        Hygiene::default()
+    }
+
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    pub fn with_suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.suffix = suffix.into();
+        self
+    }
+
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    pub fn with_symbols<T: Into<String>>(mut self, symbols: impl IntoIterator<Item = T>) -> Self {
+        self.symbols.extend(symbols.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn with_collision_severity(mut self, severity: CollisionSeverity) -> Self {
+        self.on_collision = severity;
+        self
+    }
+
+    // identifier emitted for phrase, dotted when namespaced
+    pub fn name(&self, phrase: &str) -> String {
+        let name = format!("{}{}{}", self.prefix, phrase, self.suffix);
+        match &self.namespace {
+            None => name,
+            Some(namespace) => format!("{}.{}", namespace, name),
+        }
+    }
+
+    // part of the generated name a program symbol would clash with, if any does
+    pub fn collision(&self, phrase: &str) -> Option<String> {
+        let root = match &self.namespace {
+            None => format!("{}{}{}", self.prefix, phrase, self.suffix),
+            Some(namespace) => namespace.split('.').next().unwrap_or_default().to_string(),
+        };
+
+        self.symbols.contains(&root).then_some(root)
+    }
+
+    pub(crate) fn is_identity(&self) -> bool {
+        self.prefix.is_empty() && self.suffix.is_empty() && self.namespace.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hygiene::Hygiene;
+
+    #[test]
+    fn name_with_prefix_and_namespace() {
+        let hygiene = Hygiene::new().with_prefix("p_").with_suffix("_").with_namespace("lib.phrases");
+
+        assert_eq!(hygiene.name("perform_task"), "lib.phrases.p_perform_task_");
+    }
+
+    #[test]
+    fn collisions() {
+        let hygiene = Hygiene::new().with_symbols(["perform_task", "lib"]);
+
+        assert_eq!(hygiene.collision("perform_task"), Some("perform_task".to_string()));
+        assert_eq!(hygiene.clone().with_prefix("p_").collision("perform_task"), None);
+        assert_eq!(hygiene.with_namespace("lib.phrases").collision("perform_task"), Some("lib".to_string()));
+    }
+}
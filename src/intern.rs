@@ -0,0 +1,67 @@
+use std::collections::HashSet;
+use std::rc::Rc;
+
+// shared copies of words and phrase text, each distinct text is allocated once per reduction
+// cloning an interned string only bumps its count, so phrases in progress, fallbacks and cache keys share one copy
+#[derive(Debug, Default)]
+pub(crate) struct Interner {
+    strings: HashSet<Rc<str>>,
+    // reused when joining so text that was seen before isn't allocated again
+    buffer: String,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Interner::default()
+    }
+
+    pub fn intern(&mut self, text: &str) -> Rc<str> {
+        if let Some(interned) = self.strings.get(text) {
+            return interned.clone();
+        }
+
+        let interned: Rc<str> = Rc::from(text);
+        self.strings.insert(interned.clone());
+        interned
+    }
+
+    // phrase with word added as its next part
+    pub fn join(&mut self, phrase: &str, word: &str) -> Rc<str> {
+        let mut buffer = std::mem::take(&mut self.buffer);
+        buffer.clear();
+        buffer.push_str(phrase);
+        buffer.push('_');
+        buffer.push_str(word);
+
+        let joined = self.intern(&buffer);
+        self.buffer = buffer;
+        joined
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use crate::intern::Interner;
+
+    #[test]
+    fn same_text_shared() {
+        let mut interner = Interner::new();
+        let first = interner.intern("perform");
+        let second = interner.intern("perform");
+
+        assert!(Rc::ptr_eq(&first, &second));
+    }
+
+    #[test]
+    fn joined_text_shared() {
+        let mut interner = Interner::new();
+        let joined = interner.join("perform", "task");
+        let again = interner.join("perform", "task");
+
+        assert_eq!(joined.as_ref(), "perform_task");
+        assert!(Rc::ptr_eq(&joined, &again));
+        assert!(Rc::ptr_eq(&joined, &interner.intern("perform_task")));
+    }
+}
@@ -0,0 +1,210 @@
+use garnish_lang_compiler::parse::{ParseNode, ParseResult};
+use serde::{Deserialize, Serialize};
+
+use crate::error::PhraseError;
+use crate::middleware::{Mutation, MutationMiddleware};
+
+// a mutation the reducer made and the root of what it produced
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub mutation: Mutation,
+    pub node: Option<usize>,
+}
+
+// node changed or added by reduction, before is None for added nodes
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct NodeEdit {
+    pub index: usize,
+    pub before: Option<ParseNode>,
+    pub after: ParseNode,
+}
+
+// mutations of one reduction with the node edits needed to undo or redo all of them
+// lets a tool switch between the phrase and desugared trees without parsing again
+// serialized journals can be replayed with reapply on another copy of the same parse result
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct EditJournal {
+    entries: Vec<JournalEntry>,
+    edits: Vec<NodeEdit>,
+    original_nodes: usize,
+    original_root: usize,
+    reduced_nodes: usize,
+    reduced_root: usize,
+}
+
+impl EditJournal {
+    pub(crate) fn between(original: &ParseResult, reduced: &ParseResult, entries: Vec<JournalEntry>) -> Self {
+        let edits = reduced.get_nodes().iter()
+            .enumerate()
+            .filter_map(|(index, after)| match original.get_node(index) {
+                Some(before) if before == after => None,
+                before => Some(NodeEdit { index, before: before.cloned(), after: after.clone() }),
+            })
+            .collect();
+
+        EditJournal {
+            entries,
+            edits,
+            original_nodes: original.get_nodes().len(),
+            original_root: original.get_root(),
+            reduced_nodes: reduced.get_nodes().len(),
+            reduced_root: reduced.get_root(),
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    pub fn from_json(input: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(input)
+    }
+
+    // in the order they were made
+    pub fn entries(&self) -> &[JournalEntry] {
+        &self.entries
+    }
+
+    // sorted by index
+    pub fn edits(&self) -> &[NodeEdit] {
+        &self.edits
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty() && self.original_root == self.reduced_root && self.original_nodes == self.reduced_nodes
+    }
+
+    // turns the reduced result back into the original it was reduced from
+    pub fn revert(&self, result: &mut ParseResult) -> Result<(), PhraseError> {
+        self.check(result, true)?;
+
+        let mut nodes = result.get_nodes().clone();
+        nodes.truncate(self.original_nodes);
+        for edit in &self.edits {
+            if let Some(before) = &edit.before {
+                nodes[edit.index] = before.clone();
+            }
+        }
+
+        result.set_nodes(nodes);
+        result.set_root(self.original_root);
+        Ok(())
+    }
+
+    // turns the original result into the reduced one again
+    pub fn reapply(&self, result: &mut ParseResult) -> Result<(), PhraseError> {
+        self.check(result, false)?;
+
+        let mut nodes = result.get_nodes().clone();
+        for edit in &self.edits {
+            match edit.before {
+                None => nodes.push(edit.after.clone()),
+                Some(_) => nodes[edit.index] = edit.after.clone(),
+            }
+        }
+
+        result.set_nodes(nodes);
+        result.set_root(self.reduced_root);
+        Ok(())
+    }
+
+    // result must be in the state the edits start from
+    fn check(&self, result: &ParseResult, reduced: bool) -> Result<(), PhraseError> {
+        let expected = match reduced {
+            true => self.reduced_nodes,
+            false => self.original_nodes,
+        };
+        let matches = |edit: &NodeEdit| match (reduced, &edit.before) {
+            (true, _) => result.get_node(edit.index) == Some(&edit.after),
+            (false, None) => true,
+            (false, Some(before)) => result.get_node(edit.index) == Some(before),
+        };
+
+        match result.get_nodes().len() == expected && self.edits.iter().all(matches) {
+            true => Ok(()),
+            false => Err(PhraseError::JournalMismatch),
+        }
+    }
+}
+
+// collects journal entries while passing every call on
+pub(crate) struct Recorder<'a> {
+    pub inner: &'a mut dyn MutationMiddleware,
+    pub entries: Vec<JournalEntry>,
+}
+
+impl MutationMiddleware for Recorder<'_> {
+    fn before(&mut self, mutation: &Mutation, result: &ParseResult) {
+        self.inner.before(mutation, result);
+    }
+
+    fn after(&mut self, mutation: &Mutation, node: Option<usize>, result: &ParseResult) {
+        self.entries.push(JournalEntry { mutation: mutation.clone(), node });
+        self.inner.after(mutation, node, result);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use garnish_lang_compiler::lex::lex;
+    use garnish_lang_compiler::parse::parse;
+
+    use crate::context::SimplePhraseContext;
+    use crate::error::PhraseError;
+    use crate::journal::EditJournal;
+    use crate::options::ReduceOptions;
+    use crate::reduce_phrases_with_journal;
+
+    fn context() -> SimplePhraseContext {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrases(["perform_task", "halt_now"]).unwrap();
+        context
+    }
+
+    #[test]
+    fn revert_and_reapply() {
+        let parsed = parse(&lex("perform (halt now) task").unwrap()).unwrap();
+        let (reduced, _, journal) = reduce_phrases_with_journal(&parsed, &context(), &ReduceOptions::default()).unwrap();
+
+        assert_eq!(journal.entries().len(), 2);
+
+        let mut toggled = reduced.clone();
+        for _ in 0..2 {
+            journal.revert(&mut toggled).unwrap();
+            assert_eq!(toggled, parsed);
+
+            journal.reapply(&mut toggled).unwrap();
+            assert_eq!(toggled, reduced);
+        }
+    }
+
+    #[test]
+    fn replayed_on_other_copy() {
+        let parsed = parse(&lex("perform (halt now) task").unwrap()).unwrap();
+        let (reduced, _, journal) = reduce_phrases_with_journal(&parsed, &context(), &ReduceOptions::default()).unwrap();
+
+        let received = EditJournal::from_json(&journal.to_json().unwrap()).unwrap();
+        let mut copy = parse(&lex("perform (halt now) task").unwrap()).unwrap();
+        received.reapply(&mut copy).unwrap();
+
+        assert_eq!(received, journal);
+        assert_eq!(copy, reduced);
+    }
+
+    #[test]
+    fn nothing_matched_is_empty() {
+        let parsed = parse(&lex("value 5").unwrap()).unwrap();
+        let (_, _, journal) = reduce_phrases_with_journal(&parsed, &context(), &ReduceOptions::default()).unwrap();
+
+        assert!(journal.is_empty());
+    }
+
+    #[test]
+    fn revert_of_other_result_rejected() {
+        let parsed = parse(&lex("perform 5 task").unwrap()).unwrap();
+        let (_, _, journal) = reduce_phrases_with_journal(&parsed, &context(), &ReduceOptions::default()).unwrap();
+
+        let mut original = parsed.clone();
+        assert!(matches!(journal.revert(&mut original), Err(PhraseError::JournalMismatch)));
+    }
+}
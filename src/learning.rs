@@ -0,0 +1,98 @@
+use std::collections::BTreeMap;
+
+use crate::report::ReduceReport;
+use crate::span::Span;
+
+// unfinished phrases collected over many reductions in learning mode
+// frequent ones are likely missing from the dictionary or written differently than registered
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct LearningReport {
+    unfinished: BTreeMap<String, Vec<Span>>,
+}
+
+impl LearningReport {
+    pub fn new() -> Self {
+        LearningReport::default()
+    }
+
+    // report should come from a reduction with ReduceOptions::learn set
+    pub fn add(&mut self, report: &ReduceReport) {
+        for unfinished in &report.unfinished {
+            self.unfinished.entry(unfinished.words.clone()).or_default().push(unfinished.span);
+        }
+    }
+
+    pub fn merge(&mut self, other: LearningReport) {
+        for (words, spans) in other.unfinished {
+            self.unfinished.entry(words).or_default().extend(spans);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.unfinished.is_empty()
+    }
+
+    // times each word sequence was left unfinished, sorted by words
+    pub fn counts(&self) -> BTreeMap<&str, usize> {
+        self.unfinished.iter().map(|(words, spans)| (words.as_str(), spans.len())).collect()
+    }
+
+    // most frequent first, ties sorted by words
+    pub fn most_frequent(&self) -> Vec<(&str, usize)> {
+        let mut counts = self.counts().into_iter().collect::<Vec<(&str, usize)>>();
+        counts.sort_by(|(first_words, first), (second_words, second)| second.cmp(first).then(first_words.cmp(second_words)));
+        counts
+    }
+
+    // where words were left unfinished, in the order they were added
+    pub fn spans(&self, words: &str) -> &[Span] {
+        match self.unfinished.get(words) {
+            None => &[],
+            Some(spans) => spans,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use garnish_lang_compiler::lex::lex;
+    use garnish_lang_compiler::parse::parse;
+
+    use crate::context::SimplePhraseContext;
+    use crate::learning::LearningReport;
+    use crate::options::ReduceOptions;
+    use crate::reduce_phrases_with_options;
+    use crate::span::{Position, Span};
+
+    fn learn(learning: &mut LearningReport, input: &str) {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrases(["perform_the_task", "stop"]).unwrap();
+
+        let parsed = parse(&lex(input).unwrap()).unwrap();
+        let (_, report) = reduce_phrases_with_options(&parsed, &context, &ReduceOptions { learn: true, ..ReduceOptions::default() }).unwrap();
+        learning.add(&report);
+    }
+
+    #[test]
+    fn unfinished_counted() {
+        let mut learning = LearningReport::new();
+        learn(&mut learning, "perform the 5 job");
+        learn(&mut learning, "perform the 10");
+        learn(&mut learning, "perform 5");
+        learn(&mut learning, "perform the 5 task");
+
+        assert_eq!(learning.most_frequent(), vec![("perform_the", 2), ("perform", 1)]);
+        assert_eq!(learning.spans("perform")[0], Span { start: Position { line: 0, column: 0 }, end: Position { line: 0, column: 7 } });
+    }
+
+    #[test]
+    fn nothing_recorded_without_learning() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+
+        let parsed = parse(&lex("perform 5").unwrap()).unwrap();
+        let (_, report) = reduce_phrases_with_options(&parsed, &context, &ReduceOptions::default()).unwrap();
+
+        assert!(report.unfinished.is_empty());
+    }
+}
@@ -1,781 +1,4190 @@
-mod context;
+#[cfg(feature = "compiler-0-0-24")]
+extern crate garnish_lang_compiler_0_0_24 as garnish_lang_compiler;
+#[cfg(all(feature = "compiler-0-0-18", not(feature = "compiler-0-0-24")))]
+extern crate garnish_lang_compiler_0_0_18 as garnish_lang_compiler;
+
+#[cfg(not(any(feature = "compiler-0-0-18", feature = "compiler-0-0-24")))]
+compile_error!("a garnish_lang_compiler version feature must be enabled: compiler-0-0-18 or compiler-0-0-24");
+
+mod analysis;
+mod annotation;
+mod arbitration;
+mod batch;
+mod cache;
+mod chain;
+mod compat;
+#[cfg(feature = "full")]
+pub mod compiler;
+pub mod context;
+mod corpus;
+mod csv;
+mod dictionary;
+mod distance;
+mod duplicate;
+mod error;
+mod ext;
+mod fingerprint;
+mod hygiene;
+mod intern;
+mod journal;
+mod learning;
+mod limits;
+mod lint;
+mod matcher;
+mod meta;
+mod middleware;
+mod naming;
+mod normalize;
+mod options;
+mod partial;
+mod plan;
+mod precedence;
+pub mod prelude;
+mod profile;
+mod project;
+mod report;
+mod region;
+mod reserved;
+mod span;
+mod stream;
+mod traversal;
+mod token;
+mod toml;
+mod tree;
+mod trie;
+mod validation;
+#[cfg(feature = "watch")]
+mod watch;
+
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
 
 use garnish_lang_compiler::lex::{LexerToken, TokenType};
 use garnish_lang_compiler::parse::{Definition, ParseNode, ParseResult, SecondaryDefinition};
+use crate::compat::{node_str, node_text};
 use crate::context::{PhraseContext, PhraseStatus};
+use crate::distance::{edit_distance, SuggestionIndex};
+use crate::intern::Interner;
+use crate::meta::fill_template;
+use crate::partial::shape_partial;
+use crate::profile::Profiler;
+
+// phf map type used by StaticPhraseContext, and the path generated by SimplePhraseContext::static_entries
+pub use phf;
 
+pub use crate::annotation::{phrase_annotations, PhraseAnnotation};
+pub use crate::analysis::{analyze_ambiguity, analyze_reachability, potential_phrase_starts, Ambiguity, PhraseStart, ReachabilityFinding, UnreachableReason};
+pub use crate::arbitration::{arbitrate, arbitrate_weighted, Arbitration, Disambiguation, Disambiguator, MatchCandidate};
+pub use crate::batch::{reduce_all, reduce_all_with_options, BatchReduction};
+pub use crate::cache::{CacheStats, CachingPhraseContext};
+pub use crate::dictionary::{
+    CsvRowError, Dictionary, DictionaryEntry, DictionaryError, DictionaryWarning, CSV_COLUMNS, DICTIONARY_VERSION, MIN_DICTIONARY_VERSION, RICH_FEATURES,
+};
+pub use crate::duplicate::DuplicateWords;
+pub use crate::error::PhraseError;
+pub use crate::ext::{ParseResultExt, ParseResultPhraseExt};
+pub use crate::limits::{MatchLimit, MatchLimits};
+pub use crate::lint::{lint, lint_with_options, LintFinding, LintOptions, LintSeverity};
+pub use crate::matcher::{MatchEvent, PhraseMatcher};
+pub use crate::meta::{ApplyForm, ArgumentKeys, ArgumentOrder, Deprecation, PhraseMeta, PhraseSource, SyntacticPosition, Visibility};
+pub use crate::middleware::{Mutation, MutationMiddleware};
+pub use crate::naming::{IdentifierNamer, NamingPolicy};
+pub use crate::options::ReduceOptions;
+pub use crate::partial::{ExtraArguments, PartialShape};
+pub use crate::plan::{apply, apply_with_options, plan, plan_with_options, PlannedRewrite, RewritePlan};
+pub use crate::precedence::PrecedenceTable;
+pub use crate::profile::ReduceProfile;
+pub use crate::report::{PhraseMatch, ReduceError, ReduceReport, ReduceWarning, UnfinishedPhrase};
+pub use crate::region::{no_reduce_regions, REGION_END, REGION_START};
+pub use crate::reserved::{CollisionSeverity, ReservedKind, SingleWordGuard};
+pub use crate::hygiene::Hygiene;
+pub use crate::journal::{EditJournal, JournalEntry, NodeEdit};
+pub use crate::learning::LearningReport;
+pub use crate::corpus::{CorpusStats, NearPhrase};
+pub use crate::project::{PhraseProject, ProjectDiagnostic, SourceError};
+pub use crate::normalize::{english_singular, identifier_words, Normalization, WordNormalizer};
+pub use crate::span::{Position, Span};
+pub use crate::stream::{StreamUpdate, StreamingReducer, DEFAULT_LOOKAHEAD};
+pub use crate::token::{TokenPosition, TokenRules};
+pub use crate::traversal::{Traversal, TraversalItem, TraversalStrategy};
+pub use crate::trie::TriePhraseContext;
+pub use crate::validation::{MatchValidator, MatchedPhrase, Validation};
+#[cfg(feature = "watch")]
+pub use crate::watch::{DictionaryWatcher, SwappableContext};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
 struct PhraseInfo {
-    phrase_parts: Vec<String>,
+    // joined text of the parts so far, interned so continuing or backing up a phrase doesn't copy it
+    text: Rc<str>,
+    part_nodes: Vec<usize>,
     arguments: Vec<usize>,
+    // each argument with the phrase word written before it
+    argument_words: Vec<(usize, usize)>,
+    // latest word the phrase could have ended at, used when it doesn't grow into a longer phrase
+    fallback: Option<Fallback>,
 }
 
-impl PhraseInfo {
-    pub fn new(part: String) -> Self {
-        PhraseInfo { phrase_parts: vec![part], arguments: vec![] }
-    }
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct Fallback {
+    // phrase as it was before the word, None when the word started the phrase
+    before: Option<Box<PhraseInfo>>,
+    phrase: Rc<str>,
+    node: usize,
+    is_left_of_parent: bool,
+}
 
-    pub fn full_text(&self) -> String {
-        self.phrase_parts.join("_")
+impl PhraseInfo {
+    pub fn new(part: Rc<str>, node: usize) -> Self {
+        PhraseInfo { text: part, part_nodes: vec![node], arguments: vec![], argument_words: vec![], fallback: None }
     }
 
-    pub fn full_text_with(&self, part: &str) -> String {
-        format!("{}_{}", self.full_text(), part)
+    pub fn full_text(&self) -> &str {
+        &self.text
     }
 
-    pub fn add_part(&mut self, part: String) {
-        self.phrase_parts.push(part);
+    // text is the phrase with the new part, as joined by Interner::join
+    pub fn add_part(&mut self, text: Rc<str>, node: usize) {
+        self.text = text;
+        self.part_nodes.push(node);
     }
 
     pub fn add_argument(&mut self, argument: usize) {
         self.arguments.push(argument);
+        if let Some(word) = self.part_nodes.last() {
+            self.argument_words.push((argument, *word));
+        }
     }
 }
 
-pub fn reduce_phrases<Context: PhraseContext>(
+pub fn reduce_phrases<Context: PhraseContext + ?Sized>(
     parse_result: &ParseResult,
     context: &Context,
-) -> Result<ParseResult, String> {
-    let current_index = parse_result.get_root();
-    let mut new_result = parse_result.clone();
-    let mut phrases = vec![];
+) -> Result<ParseResult, PhraseError> {
+    reduce_phrases_with_options(parse_result, context, &ReduceOptions::default())
+        .map(|(result, _)| result)
+}
 
-    // a single node can't be a parent
-    // and only needs a single check
-    if parse_result.get_nodes().len() == 1 {
-        check_node_index_for_phrase(
-            Some(current_index),
-            &mut phrases,
-            context,
-            parse_result,
-            &mut new_result,
-            false,
-        )?;
+pub fn reduce_phrases_with_options<Context: PhraseContext + ?Sized>(
+    parse_result: &ParseResult,
+    context: &Context,
+    options: &ReduceOptions,
+) -> Result<(ParseResult, ReduceReport), PhraseError> {
+    reduce_phrases_with_middleware(parse_result, context, options, &mut ())
+}
 
-        return Ok(new_result);
-    }
+pub fn reduce_phrases_with_middleware<Context: PhraseContext + ?Sized>(
+    parse_result: &ParseResult,
+    context: &Context,
+    options: &ReduceOptions,
+    middleware: &mut dyn MutationMiddleware,
+) -> Result<(ParseResult, ReduceReport), PhraseError> {
+    reduce(parse_result, context, options, middleware, None)
+}
 
-    let mut parent_stack = vec![];
-    let mut process_stack = vec![current_index];
+// also returns a journal that can turn the reduced result back into parse_result and forward again
+pub fn reduce_phrases_with_journal<Context: PhraseContext + ?Sized>(
+    parse_result: &ParseResult,
+    context: &Context,
+    options: &ReduceOptions,
+) -> Result<(ParseResult, ReduceReport, EditJournal), PhraseError> {
+    let mut recorder = journal::Recorder { inner: &mut (), entries: vec![] };
+    let (result, report) = reduce(parse_result, context, options, &mut recorder, None)?;
+    let journal = EditJournal::between(parse_result, &result, recorder.entries);
 
-    while let Some(current_index) = process_stack.pop() {
-        match parse_result.get_node(current_index) {
-            None => Err(format!("Node at index {} not present", current_index))?,
-            Some(node) => {
-                match (node.get_left(), node.get_right()) {
-                    (None, None) => continue, // not a parent, skip
-                    (Some(left_index), Some(right_index)) => {
-                        // process left then right will result in parent stack processing
-                        // left before right
-                        process_stack.push(left_index);
-                        process_stack.push(right_index);
-                    }
-                    (Some(left_index), None) => {
-                        process_stack.push(left_index);
-                    }
-                    (None, Some(right_index)) => {
-                        process_stack.push(right_index);
-                    }
-                }
+    Ok((result, report, journal))
+}
 
-                parent_stack.push(current_index);
-            }
+// also resolves each match to the index of its precompiled expression so a runtime can dispatch without names
+// phrases missing from expressions are reduced as usual and warned about
+pub fn reduce_phrases_with_expressions<Context: PhraseContext + ?Sized>(
+    parse_result: &ParseResult,
+    context: &Context,
+    options: &ReduceOptions,
+    expressions: &HashMap<String, usize>,
+) -> Result<(ParseResult, ReduceReport), PhraseError> {
+    let (result, mut report) = reduce(parse_result, context, options, &mut (), None)?;
+
+    for m in report.matches.iter_mut() {
+        m.expression = expressions.get(&m.phrase).copied();
+        if m.expression.is_none() {
+            report.warnings.push(ReduceWarning::MissingExpression { phrase: m.phrase.clone(), span: m.span });
         }
     }
 
-    while let Some(current_index) = parent_stack.pop() {
-        let current_parent = parse_result.get_node(current_index)
-            .ok_or(format!("Node at index {} not present", current_index))?;
+    Ok((result, report))
+}
 
-        // phrases can only be contained in a list
-        match current_parent.get_definition() {
-            Definition::List => (),
-            _ => continue
-        };
+// reduction making only the rewrites of plan
+pub(crate) fn reduce_planned<Context: PhraseContext + ?Sized>(
+    parse_result: &ParseResult,
+    context: &Context,
+    options: &ReduceOptions,
+    plan: &RewritePlan,
+) -> Result<(ParseResult, ReduceReport), PhraseError> {
+    reduce(parse_result, context, options, &mut (), Some(plan))
+}
 
-        // check left then right for phrases
-        check_node_index_for_phrase(
-            current_parent.get_left(),
-            &mut phrases,
-            context,
-            parse_result,
-            &mut new_result,
-            true
-        )?;
-
-        check_node_index_for_phrase(
-            current_parent.get_right(),
-            &mut phrases,
-            context,
-            parse_result,
-            &mut new_result,
-            false,
-        )?;
+fn reduce<Context: PhraseContext + ?Sized>(
+    parse_result: &ParseResult,
+    context: &Context,
+    options: &ReduceOptions,
+    middleware: &mut dyn MutationMiddleware,
+    plan: Option<&RewritePlan>,
+) -> Result<(ParseResult, ReduceReport), PhraseError> {
+    parse_result.check_structure()?;
+
+    let is_phrase_word = |word: &str| {
+        context.get_phrase_status(word).merge(options.extra_phrases.get_phrase_status(word)) != PhraseStatus::NotAPhrase
+    };
+
+    let duplicates = match options.duplicate_words {
+        DuplicateWords::Independent => vec![],
+        _ => duplicate::duplicate_words(parse_result, is_phrase_word),
+    };
+    if let (DuplicateWords::Deny, Some((word, span))) = (options.duplicate_words, duplicates.first()) {
+        return Err(PhraseError::DuplicateWord { word: word.clone(), span: *span });
     }
 
-    return Ok(new_result);
+    let regrouped = precedence::regroup(parse_result, &options.precedence, is_phrase_word);
+    let parse_result = regrouped.as_ref().unwrap_or(parse_result);
+
+    let (result, mut report) = match options.chain_word.as_deref().and_then(|word| chain::split_chain(parse_result, word)) {
+        None => Reducer::new(parse_result, context, options, middleware, plan).reduce(),
+        Some(chain) => reduce_chain(chain, context, options, middleware, plan),
+    }?;
+
+    report.warnings.extend(duplicates.into_iter().map(|(word, span)| ReduceWarning::DuplicateWord { word, span }));
+    Ok((result, report))
 }
 
-fn check_node_index_for_phrase<Context: PhraseContext>(
-    node_index_opt: Option<usize>,
-    phrases: &mut Vec<PhraseInfo>,
+// reduces only the subtree rooted at node_index, the rest of the tree is left as is
+pub fn reduce_phrases_at<Context: PhraseContext + ?Sized>(
+    parse_result: &ParseResult,
+    node_index: usize,
     context: &Context,
-    original_result: &ParseResult,
-    result: &mut ParseResult,
-    is_left_of_parent: bool,
-) -> Result<(), String> {
-    match node_index_opt {
-        None => Ok(()),
-        Some(index) => match original_result.get_node(index) {
-            None => Ok(()),
-            Some(node) => check_node_for_phrase(
-                node,
-                index,
-                phrases,
-                context,
-                result,
-                is_left_of_parent,
-            )
-        }
-    }
+) -> Result<ParseResult, PhraseError> {
+    reduce_phrases_at_with_options(parse_result, node_index, context, &ReduceOptions::default())
+        .map(|(result, _)| result)
 }
 
-fn check_node_for_phrase<Context: PhraseContext>(
-    node: &ParseNode,
+// match nodes in the report are indices into the returned result
+pub fn reduce_phrases_at_with_options<Context: PhraseContext + ?Sized>(
+    parse_result: &ParseResult,
     node_index: usize,
-    phrases: &mut Vec<PhraseInfo>,
     context: &Context,
-    result: &mut ParseResult,
-    is_left_of_parent: bool,
-) -> Result<(), String> {
-    let arg_index = match node.get_definition() {
-        Definition::Identifier => {
-            // check all identifier's for being a phrase part
-
-            // if there is an existing phrase in progress
-            // check if current identifier can be a part of that phrase
-            let phrase_text = node.get_lex_token().get_text().clone();
-            match phrases.last_mut() {
-                None => {
-                    // no existing phrase
-                    match context.get_phrase_status(&phrase_text) {
-                        PhraseStatus::Incomplete => {
-                            // start new phrase
-                            phrases.push(PhraseInfo::new(phrase_text));
-                            None
-                        }
-                        PhraseStatus::Complete => {
-                            // single word phrase, resolve immediately
-                            resolve_single_word_phrase(
-                                node,
-                                node_index,
-                                result,
-                            )?
-                        }
-                        PhraseStatus::NotAPhrase => Some(node_index) // continue no changes
-                    }
-                }
-                Some(info) => {
-                    // existing phrase, first check if current is continuation
-                    let new_phrase_text = info.full_text_with(&phrase_text);
-                    match context.get_phrase_status(&new_phrase_text) {
-                        PhraseStatus::NotAPhrase => {
-                            // not a continuation
-                            // check if current text can be a phrase on its own
-                            match context.get_phrase_status(&phrase_text) {
-                                PhraseStatus::Incomplete => {
-                                    phrases.push(PhraseInfo::new(phrase_text));
-                                    None
-                                }
-                                PhraseStatus::Complete => {
-                                    resolve_single_word_phrase(
-                                        node,
-                                        node_index,
-                                        result,
-                                    )?
-                                }
-                                PhraseStatus::NotAPhrase => {
-                                    Some(node_index)
-                                } // continue no changes
-                            }
-                        }
-                        PhraseStatus::Incomplete => {
-                            // continuation
-                            info.add_part(phrase_text);
-                            None
-                        }
-                        PhraseStatus::Complete => {
-                            // end of multi-word phrase, resolve now
-                            resolve_top_phrase(
-                                node,
-                                node_index,
-                                is_left_of_parent,
-                                phrases,
-                                result,
-                                Some(new_phrase_text)
-                            )?
-                        }
-                    }
-                }
-            }
+    options: &ReduceOptions,
+) -> Result<(ParseResult, ReduceReport), PhraseError> {
+    let parent = parse_result.get_node(node_index).ok_or(PhraseError::MissingNode(node_index))?.get_parent();
+
+    // reduce a standalone copy so the subtree root is treated like a whole tree
+    let mut subtree = ParseResult::new();
+    let root = parse_result.copy_subtree(node_index, &mut subtree)?;
+    subtree.set_root(root);
+
+    let (reduced, report) = reduce_phrases_with_options(&subtree, context, options)?;
+
+    // splice reduced copy in over the subtree's nodes so reducing the same subtree again doesn't grow the result
+    let mut result = parse_result.clone();
+    let places = tree::place_tree(&mut result, &reduced, &tree::subtree_nodes(parse_result, node_index));
+    result.replace_child(parent, node_index, places[reduced.get_root()])?;
+
+    Ok((result, ReduceReport { matches: report.matches.into_iter().map(|m| m.map_nodes(|node| places[node])).collect(), ..report }))
+}
+
+// reduces each segment alone then joins them, piping each result into the next
+fn reduce_chain<Context: PhraseContext + ?Sized>(
+    chain: chain::Chain,
+    context: &Context,
+    options: &ReduceOptions,
+    middleware: &mut dyn MutationMiddleware,
+    plan: Option<&RewritePlan>,
+) -> Result<(ParseResult, ReduceReport), PhraseError> {
+    let mut result = ParseResult::new();
+    let mut report = ReduceReport::default();
+    let mut previous = None;
+
+    for (segment, link) in chain.segments.iter().zip(std::iter::once(None).chain(chain.links.iter().map(Some))) {
+        let (reduced, segment_report) = Reducer::new(segment, context, options, &mut *middleware, plan).reduce()?;
+        let offset = result.append_tree(&reduced);
+        let root = reduced.get_root() + offset;
+        let is_phrase = segment_report.matches.iter().any(|m| m.node == reduced.get_root());
+
+        report.matches.extend(segment_report.matches.into_iter().map(|m| m.offset(offset)));
+        report.warnings.extend(segment_report.warnings);
+        report.errors.extend(segment_report.errors);
+        report.unfinished.extend(segment_report.unfinished);
+        match (&mut report.profile, segment_report.profile) {
+            (Some(total), Some(profile)) => total.add(&profile),
+            (total, profile) => *total = total.or(profile),
         }
-        // List to left of parent should not be included in arg lists
-        Definition::List if is_left_of_parent => None,
-        _ => Some(node_index)
-    };
 
-    match arg_index {
-        None => (),
-        // add to argument list if there's an existing phrase
-        Some(index) => match phrases.last_mut() {
-            None => (),
-            Some(info) => {
-                info.add_argument(index);
+        previous = match (previous, link) {
+            (Some(previous), Some(link)) => {
+                let mutation = Mutation::Pipe { previous, next: root };
+                middleware.before(&mutation, &result);
+                let node = chain::pipe(&mut result, previous, root, is_phrase, link).ok_or(PhraseError::MissingNode(root))?;
+                middleware.after(&mutation, Some(node), &result);
+
+                Some(node)
             }
-        }
+            _ => Some(root),
+        };
     }
 
-    Ok(())
+    if let Some(root) = previous {
+        result.set_root(root);
+    }
+
+    Ok((result, report))
 }
 
-fn resolve_single_word_phrase(
-    node: &ParseNode,
-    node_index: usize,
-    result: &mut ParseResult,
-) -> Result<Option<usize>, String> {
-    // and add a new empty apply node
-    let new_index = result.get_nodes().len();
-    result.add_node(ParseNode::new(
-        Definition::EmptyApply,
-        SecondaryDefinition::UnarySuffix,
-        node.get_parent(),
-        Some(node_index),
-        None,
-        node.get_lex_token().clone(), // clone so debugging points to identifier
-    ));
+struct Reducer<'a, Context: PhraseContext + ?Sized> {
+    context: &'a Context,
+    options: &'a ReduceOptions,
+    original: &'a ParseResult,
+    result: ParseResult,
+    phrases: Vec<PhraseInfo>,
+    profiler: Profiler,
+    report: ReduceReport,
+    // words and phrase text seen so far, shared by phrases in progress and the caches below
+    strings: Interner,
+    // shared so repeated lookups of a phrase don't clone its meta
+    meta_cache: HashMap<Rc<str>, Option<Rc<PhraseMeta>>>,
+    // overloads looked up alongside meta, extra phrases first
+    overload_cache: HashMap<Rc<str>, Vec<PhraseMeta>>,
+    middleware: &'a mut dyn MutationMiddleware,
+    // only rewrites of the plan are made when set
+    plan: Option<&'a RewritePlan>,
+    // items taken as trailing arguments, already placed so they aren't checked again
+    trailing: HashSet<usize>,
+    // visible registered phrases, indexed the first time a suggestion is needed
+    registered: Option<SuggestionIndex>,
+}
 
-    if result.get_root() == node_index {
-        result.set_root(new_index);
+impl<'a, Context: PhraseContext + ?Sized> Reducer<'a, Context> {
+    fn new(
+        parse_result: &'a ParseResult,
+        context: &'a Context,
+        options: &'a ReduceOptions,
+        middleware: &'a mut dyn MutationMiddleware,
+        plan: Option<&'a RewritePlan>,
+    ) -> Self {
+        Reducer {
+            context,
+            options,
+            original: parse_result,
+            result: parse_result.clone(),
+            phrases: vec![],
+            profiler: Profiler::new(options.profile),
+            report: ReduceReport::default(),
+            strings: Interner::new(),
+            meta_cache: HashMap::new(),
+            overload_cache: HashMap::new(),
+            middleware,
+            plan,
+            trailing: HashSet::new(),
+            registered: None,
+        }
     }
 
-    match result.get_node_mut(node_index) {
-        None => Err(format!("Node at {} not found", node_index))?,
-        Some(node) => {
-            node.set_parent(Some(new_index));
+    fn reduce(mut self) -> Result<(ParseResult, ReduceReport), PhraseError> {
+        let original = self.original;
+        let traversal = &self.options.traversal;
+        let items = self.profiler.traversal(|| traversal.items(original))?;
+
+        for (node_index, is_left_of_parent) in items {
+            self.check_node_index_for_phrase(node_index, is_left_of_parent)?;
         }
-    }
 
-    Ok(Some(new_index))
-}
+        // input ended before longer phrases were finished
+        while self.phrases.last().is_some_and(|info| info.fallback.is_some()) {
+            let index = self.resolve_fallback()?;
+            self.add_argument(index);
+        }
 
-fn resolve_top_phrase(
-    node: &ParseNode,
-    node_index: usize,
-    is_left_of_parent: bool,
-    phrases: &mut Vec<PhraseInfo>,
-    result: &mut ParseResult,
-    text: Option<String>
-) -> Result<Option<usize>, String> {
-    let info = match phrases.last() {
-        None => return Ok(None),
-        Some(i) => i
-    };
+        if self.options.complete_unterminated || self.options.infer_final_word {
+            self.complete_unterminated()?;
+        }
 
-    let new_phrase_text = match text {
-        None =>  info.full_text(),
-        Some(text) => text,
-    };
+        self.record_unfinished();
 
-    // update current node token to be full phrase
-    match result.get_node_mut(node_index) {
-        None => Err(format!("Node at {} not found", node_index))?,
-        Some(node) => {
-            let new_token = LexerToken::new(
-                new_phrase_text,
-                TokenType::Identifier,
-                node.get_lex_token().get_line(),
-                node.get_lex_token().get_column(),
-            );
-            node.set_lex_token(new_token);
+        let Reducer { result, mut report, profiler, .. } = self;
+        report.profile = profiler.finish();
+
+        Ok((result, report))
+    }
+
+    fn status(&mut self, text: &str) -> PhraseStatus {
+        let context = self.context;
+        let extra = &self.options.extra_phrases;
+        let status = self.profiler.lookup(|| context.get_phrase_status(text).merge(extra.get_phrase_status(text)));
+
+        match status {
+            PhraseStatus::Complete if !self.is_visible(text) => PhraseStatus::NotAPhrase,
+            PhraseStatus::CompleteAndExtendable if !self.is_visible(text) => PhraseStatus::Incomplete,
+            status => status,
         }
     }
 
-    let arg = match info.arguments.len() {
-        0 => {
-            let new_index = result.get_nodes().len();
-            let empty_parent = if Some(result.get_root()) == node.get_parent() {
-                None
-            } else {
-                node.get_parent()
-            };
+    fn meta(&mut self, phrase: &str) -> Option<Rc<PhraseMeta>> {
+        if let Some(meta) = self.meta_cache.get(phrase) {
+            return meta.clone();
+        }
 
-            match node.get_parent().and_then(|p| result.get_node_mut(p)) {
-                None => Err(format!("Node at {:?} not found", node.get_parent()))?,
-                Some(parent) => {
-                    match is_left_of_parent {
-                        true => parent.set_left(Some(new_index)),
-                        false => parent.set_right(Some(new_index)),
-                    }
+        let context = self.context;
+        let extra = &self.options.extra_phrases;
+        let versions = &self.options.versions;
+        let (meta, overloads) = self.profiler.lookup(|| {
+            let meta = versions.iter()
+                .find_map(|version| extra.get_versioned_meta(phrase, version).or_else(|| context.get_versioned_meta(phrase, version)))
+                .or_else(|| extra.get_phrase_meta(phrase).or_else(|| context.get_phrase_meta(phrase)));
+            let mut overloads = extra.get_overloads(phrase);
+            overloads.extend(context.get_overloads(phrase));
+            (meta, overloads)
+        });
+        let meta = meta.map(Rc::new);
+        let key = self.strings.intern(phrase);
+        self.meta_cache.insert(key.clone(), meta.clone());
+        self.overload_cache.insert(key, overloads);
 
-                    result.add_node(ParseNode::new(
-                        Definition::EmptyApply,
-                        SecondaryDefinition::UnarySuffix,
-                        empty_parent,
-                        Some(node_index),
-                        None,
-                        node.get_lex_token().clone(), // clone so debugging points to identifier
-                    ));
+        meta
+    }
 
-                    match result.get_node_mut(node_index) {
-                        None => Err(format!("Node at {} not found", node_index))?,
-                        Some(node) => {
-                            node.set_parent(Some(new_index));
-                        }
-                    }
-                }
+    // overload of phrase for the number of arguments found
+    // an exact arity wins, otherwise the only overload that could be partially applied
+    // None when phrase has no overloads or none can take that many arguments
+    fn overload(&mut self, phrase: &str, found: usize) -> Result<Option<Rc<PhraseMeta>>, PhraseError> {
+        self.meta(phrase);
+        let mut overloads = self.overload_cache.get(phrase).cloned().unwrap_or_default();
+        // extra phrases come first and replace the context's overload for the same arity
+        let mut arities = vec![];
+        overloads.retain(|meta| match meta.arity {
+            Some(arity) if !arities.contains(&arity) => {
+                arities.push(arity);
+                true
             }
+            _ => false,
+        });
 
-            if Some(result.get_root()) == node.get_parent() {
-                result.set_root(new_index);
+        if let Some(exact) = overloads.iter().find(|meta| meta.arity == Some(found)) {
+            return Ok(Some(Rc::new(exact.clone())));
+        }
+
+        let mut partial = overloads.into_iter().filter(|meta| meta.arity > Some(found)).collect::<Vec<PhraseMeta>>();
+        match partial.len() {
+            0 => Ok(None),
+            1 => Ok(partial.pop().map(Rc::new)),
+            _ => {
+                let mut arities = partial.iter().filter_map(|meta| meta.arity).collect::<Vec<usize>>();
+                arities.sort();
+                Err(PhraseError::AmbiguousOverload { phrase: phrase.to_string(), found, arities })
             }
+        }
+    }
 
-            Some(new_index)
+    // identifier is what an apply node applies, the form reductions leave phrases in
+    fn is_applied(&self, node: &ParseNode, node_index: usize) -> bool {
+        match node.get_parent().and_then(|parent| self.original.get_node(parent)) {
+            Some(parent) => match parent.get_definition() {
+                Definition::EmptyApply => parent.get_left() == Some(node_index),
+                Definition::ApplyTo => parent.get_right() == Some(node_index),
+                _ => false,
+            },
+            None => false,
         }
-        1 => {
-            match node.get_parent().and_then(|p| result.get_node_mut(p)) {
-                None => Err(format!("Node at {:?} not found", node.get_parent()))?,
-                Some(parent) => {
-                    // Using ApplyTo instead of Apply so no swapping needs to be done
-                    parent.set_definition(Definition::ApplyTo);
+    }
 
-                    // for single argument just replace current left side to point to argument
-                    let new_left = info.arguments.get(0).cloned();
-                    parent.set_left(new_left);
+    // words of a phrase left in front of the apply an earlier reduction made for it, e.g. perform before perform_task
+    // the phrase in progress over those words is dropped instead of being matched again
+    fn drop_applied_words(&mut self, node: &ParseNode) {
+        let applies_identifier = node.get_left()
+            .and_then(|left| self.original.get_node(left))
+            .is_some_and(|left| left.get_definition() == Definition::Identifier);
+        let text = match self.phrases.last() {
+            Some(info) if applies_identifier && info.arguments.is_empty() => info.text.clone(),
+            _ => return,
+        };
 
-                    // update argument to correct parent
-                    match new_left.and_then(|i| result.get_node_mut(i)) {
-                        None => Err(format!("Node at {:?} not found", new_left))?,
-                        Some(left_node) => {
-                            left_node.set_parent(node.get_parent())
-                        }
-                    }
-                }
+        let word = self.word(node);
+        let phrase = self.strings.join(&text, &word);
+        if self.status(&phrase).is_complete() {
+            self.phrases.pop();
+        }
+    }
+
+    fn is_visible(&mut self, phrase: &str) -> bool {
+        match self.meta(phrase).map(|meta| meta.visibility).unwrap_or_default() {
+            Visibility::Public => true,
+            Visibility::Internal => self.options.include_internal,
+        }
+    }
+
+    fn check_node_index_for_phrase(
+        &mut self,
+        node_index_opt: Option<usize>,
+        is_left_of_parent: bool,
+    ) -> Result<(), PhraseError> {
+        let original = self.original;
+        match node_index_opt {
+            None => Ok(()),
+            Some(index) if self.trailing.contains(&index) => Ok(()),
+            Some(index) => match original.get_node(index) {
+                None => Ok(()),
+                Some(node) => self.check_node_for_phrase(node, index, is_left_of_parent),
             }
-            node.get_parent()
         }
-        _n => {
-            let mut next_parent = match node.get_parent().and_then(|p| result.get_node_mut(p)) {
-                None => Err(format!("Node at {:?} not found", node.get_parent()))?,
-                Some(parent) => {
-                    // Using ApplyTo instead of Apply so no swapping needs to be done
-                    parent.set_definition(Definition::ApplyTo);
+    }
 
-                    parent.get_left()
-                }
-            };
+    fn check_node_for_phrase(
+        &mut self,
+        node: &ParseNode,
+        node_index: usize,
+        is_left_of_parent: bool,
+    ) -> Result<(), PhraseError> {
+        let arg_index = match node.get_definition() {
+            // identifier already applied, e.g. by an earlier reduction, stays an argument so reducing again changes nothing
+            Definition::Identifier if self.is_applied(node, node_index) => Some(node_index),
+            Definition::Identifier => {
+                // check all identifier's for being a phrase part
 
-            // descend list attaching arguments in reverse order
-            // last two arguments will have same parent as left and right
-            // end at 1 so the 0th item can always be put on last list's left
-            for i in (1..info.arguments.len()).rev() {
-                let arg_index = *info.arguments.get(i).unwrap();
+                // if there is an existing phrase in progress
+                // check if current identifier can be a part of that phrase
+                let phrase_text = self.word(node);
+                let continuation = self.phrases.last().map(|info| self.strings.join(info.full_text(), &phrase_text));
 
-                // update argument's parent
-                match result.get_node_mut(arg_index) {
-                    None => Err(format!("Node at {} not found", arg_index))?,
-                    Some(right) => {
-                        right.set_parent(next_parent);
+                match continuation.map(|text| (self.status(&text), text)) {
+                    Some((PhraseStatus::Incomplete, new_phrase_text)) => {
+                        // continuation
+                        if let Some(info) = self.phrases.last_mut() {
+                            info.add_part(new_phrase_text, node_index);
+                        }
+                        None
                     }
-                }
-
-                // update parent's right to point to argument
+                    Some((PhraseStatus::CompleteAndExtendable, new_phrase_text)) => {
+                        // could end here, keep going in case a longer phrase is written
+                        if let Some(info) = self.phrases.last_mut() {
+                            let before = PhraseInfo { fallback: None, ..info.clone() };
+                            info.add_part(new_phrase_text.clone(), node_index);
+                            info.fallback = Some(Fallback {
+                                before: Some(Box::new(before)),
+                                phrase: new_phrase_text,
+                                node: node_index,
+                                is_left_of_parent,
+                            });
+                        }
+                        None
+                    }
+                    Some((PhraseStatus::Complete, new_phrase_text)) if self.prefers_fallback(node, node_index, &new_phrase_text)? => {
+                        // shorter phrase chosen over this one, this word is checked again on its own
+                        let index = self.resolve_fallback()?;
+                        self.add_argument(index);
+                        self.check_phrase_start(node, node_index, is_left_of_parent, phrase_text)?
+                    }
+                    Some((PhraseStatus::Complete, new_phrase_text)) => {
+                        // end of multi-word phrase, resolve now
+                        self.resolve_top_phrase(node, node_index, is_left_of_parent, new_phrase_text.to_string())?
+                    }
+                    // no existing phrase or not a continuation
+                    // check if current text can be a phrase on its own
+                    continuation => {
+                        // longer phrase wasn't written, end it where it was last complete
+                        if continuation.is_some() && self.phrases.last().is_some_and(|info| info.fallback.is_some()) {
+                            let index = self.resolve_fallback()?;
+                            self.add_argument(index);
+                        }
+
+                        self.check_phrase_start(node, node_index, is_left_of_parent, phrase_text)?
+                    }
+                }
+            }
+            // List to left of parent should not be included in arg lists
+            Definition::List if is_left_of_parent => None,
+            Definition::EmptyApply => {
+                self.drop_applied_words(node);
+                Some(node_index)
+            }
+            _ => Some(node_index)
+        };
+
+        self.add_argument(arg_index);
+
+        self.check_limits(node)
+    }
+
+    // fails once the top phrase or the phrases in progress grow past ReduceOptions::limits
+    fn check_limits(&self, node: &ParseNode) -> Result<(), PhraseError> {
+        let info = match self.phrases.last() {
+            None => return Ok(()),
+            Some(info) => info,
+        };
+
+        match self.options.limits.exceeded(info.part_nodes.len(), info.arguments.len(), self.phrases.len()) {
+            None => Ok(()),
+            Some((limit, max)) => Err(PhraseError::LimitExceeded { limit, max, phrase: info.full_text().to_string(), span: self.phrase_span(node) }),
+        }
+    }
+
+    // text of node as phrase words, as written unless only a split or normalized form matches
+    // continuing the top phrase is preferred over starting a new one
+    fn word(&mut self, node: &ParseNode) -> Rc<str> {
+        // only the written form, interned straight from the token
+        if !self.options.split_identifiers && matches!(self.options.normalization, Normalization::None) && self.options.typo_distance == 0 {
+            return self.strings.intern(&node_str(node));
+        }
+
+        let word = self.word_form(node);
+        self.strings.intern(&word)
+    }
+
+    fn word_form(&mut self, node: &ParseNode) -> String {
+        let written = node_text(node);
+        let mut forms = vec![written.clone()];
+
+        if self.options.split_identifiers {
+            forms.push(identifier_words(&written).join("_"));
+        }
+        if let Some(normalized) = forms.last().and_then(|form| self.options.normalization.normalize(form)) {
+            forms.push(normalized);
+        }
+        forms.dedup();
+
+        if forms.len() == 1 && self.options.typo_distance == 0 {
+            return written;
+        }
+
+        let info = self.phrases.last().cloned();
+        if let Some(info) = &info {
+            if let Some(form) = forms.iter().find(|form| {
+                let text = self.strings.join(info.full_text(), form);
+                self.status(&text) != PhraseStatus::NotAPhrase
+            }) {
+                return form.clone();
+            }
+        }
+
+        if let Some(form) = forms.iter().find(|form| self.status(form) != PhraseStatus::NotAPhrase) {
+            return form.clone();
+        }
+
+        // misspelled continuation of the phrase in progress
+        let corrected = info.and_then(|info| self.correction(info.full_text(), forms.last().unwrap_or(&written)));
+        match corrected {
+            None => written,
+            Some(corrected) => {
+                self.report.warnings.push(ReduceWarning::Corrected { written, corrected: corrected.clone(), span: Span::of_node(node) });
+                corrected
+            }
+        }
+    }
+
+    // closest next word of a phrase continuing prefix, within typo_distance edits of word
+    // never a whole word away, so short arguments aren't read as phrase words
+    fn correction(&mut self, prefix: &str, word: &str) -> Option<String> {
+        if self.options.typo_distance == 0 {
+            return None;
+        }
+
+        let context = self.context;
+        let mut completions = self.profiler.lookup(|| context.completions(prefix));
+        completions.extend(self.options.extra_phrases.completions(prefix));
+
+        let mut best: Option<(usize, String)> = None;
+        for completion in completions {
+            let next = completion[prefix.len() + 1..].split('_').next().unwrap_or_default().to_string();
+            let distance = edit_distance(word, &next);
+            let close = distance <= self.options.typo_distance && distance < word.chars().count() && distance < next.chars().count();
+            let better = best.as_ref().is_none_or(|(best_distance, best_word)| (distance, &next) < (*best_distance, best_word));
+
+            if close && better {
+                best = Some((distance, next));
+            }
+        }
+
+        best.map(|(_, next)| next)
+    }
+
+    fn check_phrase_start(
+        &mut self,
+        node: &ParseNode,
+        node_index: usize,
+        is_left_of_parent: bool,
+        phrase_text: Rc<str>,
+    ) -> Result<Option<usize>, PhraseError> {
+        Ok(match self.status(&phrase_text) {
+            PhraseStatus::Incomplete => {
+                // start new phrase
+                self.phrases.push(PhraseInfo::new(phrase_text, node_index));
+                None
+            }
+            PhraseStatus::CompleteAndExtendable => {
+                // single word phrase unless a longer one is written
+                let mut info = PhraseInfo::new(phrase_text.clone(), node_index);
+                info.fallback = Some(Fallback { before: None, phrase: phrase_text, node: node_index, is_left_of_parent });
+                self.phrases.push(info);
+                None
+            }
+            PhraseStatus::Complete => {
+                // single word phrase, resolve immediately
+                self.resolve_single_word_phrase(node, node_index, phrase_text.to_string())?
+            }
+            PhraseStatus::NotAPhrase => {
+                // continue no changes
+                if self.options.suggest_phrases {
+                    self.suggest_phrases(node, &phrase_text);
+                }
+                Some(node_index)
+            }
+        })
+    }
+
+    fn add_argument(&mut self, arg_index: Option<usize>) {
+        match arg_index {
+            None => (),
+            // add to argument list if there's an existing phrase
+            Some(index) => match self.phrases.last_mut() {
+                None => (),
+                Some(info) => {
+                    info.add_argument(index);
+                }
+            }
+        }
+    }
+
+    // whether the top phrase should end at its fallback instead of at node as phrase
+    fn prefers_fallback(&mut self, node: &ParseNode, node_index: usize, phrase: &str) -> Result<bool, PhraseError> {
+        let (info, fallback) = match self.phrases.last().and_then(|info| info.fallback.as_ref().map(|fallback| (info.clone(), fallback.clone()))) {
+            None => return Ok(false),
+            Some(found) => found,
+        };
+        let fallback_node = self.original.get_node(fallback.node).ok_or(PhraseError::MissingNode(fallback.node))?;
+
+        let mut words = info.part_nodes.clone();
+        words.push(node_index);
+        let longer = MatchCandidate::new(phrase, words, info.arguments.clone(), self.phrase_span(node));
+
+        let shorter = match &fallback.before {
+            None => MatchCandidate::new(&fallback.phrase, vec![fallback.node], vec![], Span::of_node(fallback_node)),
+            Some(before) => {
+                let mut words = before.part_nodes.clone();
+                words.push(fallback.node);
+                MatchCandidate::new(&fallback.phrase, words, before.arguments.clone(), self.phrase_span(fallback_node))
+            }
+        };
+
+        let candidates = [
+            longer.with_weight(self.weight(phrase)).with_priority(self.priority(phrase)),
+            shorter.with_weight(self.weight(&fallback.phrase)).with_priority(self.priority(&fallback.phrase)),
+        ];
+        self.warn_ties(&candidates);
+
+        Ok(self.options.disambiguation.choose(&candidates) == Some(1))
+    }
+
+    fn weight(&mut self, phrase: &str) -> i64 {
+        self.meta(phrase).and_then(|meta| meta.weight).unwrap_or_default()
+    }
+
+    fn priority(&mut self, phrase: &str) -> i64 {
+        self.meta(phrase).and_then(|meta| meta.priority).unwrap_or_default()
+    }
+
+    // reports candidates weighted disambiguation couldn't tell apart, returns whether there were any
+    fn warn_ties(&mut self, candidates: &[MatchCandidate]) -> bool {
+        let phrases = self.options.disambiguation.ties(candidates);
+        if phrases.is_empty() {
+            return false;
+        }
+
+        let span = candidates.iter().map(|candidate| candidate.span).reduce(|first, other| Span {
+            start: first.start.min(other.start),
+            end: first.end.max(other.end),
+        }).unwrap_or_default();
+        self.report.warnings.push(ReduceWarning::TiedWeights { phrases, span });
+        true
+    }
+
+    // ends the top phrase at its fallback, anything after that word is left as written
+    fn resolve_fallback(&mut self) -> Result<Option<usize>, PhraseError> {
+        let original = self.original;
+        let fallback = match self.phrases.pop().and_then(|info| info.fallback) {
+            None => return Ok(None),
+            Some(fallback) => fallback,
+        };
+        let node = original.get_node(fallback.node).ok_or(PhraseError::MissingNode(fallback.node))?;
+
+        match fallback.before {
+            None => self.resolve_single_word_phrase(node, fallback.node, fallback.phrase.to_string()),
+            Some(before) => {
+                self.phrases.push(*before);
+                self.resolve_top_phrase(node, fallback.node, fallback.is_left_of_parent, fallback.phrase.to_string())
+            }
+        }
+    }
+
+    fn resolve_single_word_phrase(
+        &mut self,
+        node: &ParseNode,
+        node_index: usize,
+        phrase: String,
+    ) -> Result<Option<usize>, PhraseError> {
+        if self.has_trailing_items(&phrase, node_index)? {
+            let text = self.strings.intern(&phrase);
+            self.phrases.push(PhraseInfo::new(text, node_index));
+            return self.resolve_top_phrase(node, node_index, false, phrase);
+        }
+
+        let span = Span::of_node(node);
+        let meta = self.meta(&phrase);
+        let guard = &self.options.single_word_guard;
+        if guard.blocks(&phrase) || guard.blocks(&node_text(node)) || !self.allowed_at(&phrase, meta.as_deref(), node_index, span) {
+            return Ok(Some(node_index));
+        }
+        let matched = MatchedPhrase { phrase: phrase.clone(), words: vec![node_index], arguments: vec![], span };
+        if !self.options.validation.accepts(&matched, &self.result) {
+            return Ok(Some(node_index));
+        }
+        let checked = self.check_template(&phrase, meta.as_deref(), &[0])
+            .and_then(|_| self.partial_shape(&phrase, meta.as_deref(), 0));
+        let partial = match checked {
+            Ok(partial) => partial,
+            Err(error) => {
+                // left as written, continues like any other argument
+                self.recover(error, span)?;
+                return Ok(Some(node_index));
+            }
+        };
+        let target = match meta.as_deref().and_then(declared_target) {
+            _ if self.options.preserve_words => None,
+            // word was split or normalized to match
+            None => self.generated_name(&phrase, span)?.or_else(|| (node_text(node) != phrase).then(|| phrase.clone())),
+            target => target,
+        };
+        let added_from = self.result.get_nodes().len();
+        let mutation = Mutation::Phrase { phrase: phrase.clone(), words: vec![node_index], arguments: vec![], span };
+        self.middleware.before(&mutation, &self.result);
+
+        let new_index = self.profiler.mutation(|| {
+            let new_index = resolve_single_word_phrase(node, node_index, &mut self.result)?;
+
+            if let Some(target) = target {
+                emit_target(&mut self.result, node_index, &target)?;
+            }
+
+            match (new_index, partial) {
+                (Some(apply_index), Some(shape)) => {
+                    Ok(Some(shape_partial(&mut self.result, apply_index, node_index, false, shape)?))
+                }
+                _ => Ok::<Option<usize>, PhraseError>(new_index),
+            }
+        })?;
+
+        self.place_tokens(node_index, added_from, node_index, node);
+        self.middleware.after(&mutation, new_index, &self.result);
+        self.record_match(phrase, meta, new_index, span, vec![node_index]);
+
+        Ok(new_index)
+    }
+
+    fn resolve_top_phrase(
+        &mut self,
+        node: &ParseNode,
+        node_index: usize,
+        is_left_of_parent: bool,
+        phrase: String,
+    ) -> Result<Option<usize>, PhraseError> {
+        // words written in another order of a free order phrase or an alias
+        let phrase = self.context.free_order_phrase(&phrase)
+            .or_else(|| self.options.extra_phrases.free_order_phrase(&phrase))
+            .or_else(|| self.context.alias_target(&phrase))
+            .or_else(|| self.options.extra_phrases.alias_target(&phrase))
+            .unwrap_or(phrase);
+        let moved = self.take_trailing_items(&phrase, node_index)?;
+        let (node, is_left_of_parent) = match &moved {
+            None => (node, is_left_of_parent),
+            Some(moved) => (moved, false),
+        };
+        let span = self.phrase_span(node);
+        let argument_count = self.phrases.last().map(|info| info.arguments.len()).unwrap_or_default();
+        let has_arguments = argument_count > 0;
+        let overload = self.overload(&phrase, argument_count);
+        let meta = match &overload {
+            Ok(Some(meta)) => Some(meta.clone()),
+            _ => self.meta(&phrase),
+        };
+        let first_word = self.phrases.last().and_then(|info| info.part_nodes.first().copied()).unwrap_or(node_index);
+        if !self.allowed_at(&phrase, meta.as_deref(), first_word, span) || !self.validate(&phrase, node_index, span) {
+            // left as written like a phrase with errors
+            self.phrases.pop();
+            return Ok(None);
+        }
+        let written = self.written_slots(node_index);
+        let checked = overload
+            .and_then(|_| self.check_template(&phrase, meta.as_deref(), &written))
+            .and_then(|_| self.check_extra_arguments(&phrase, meta.as_deref(), argument_count, span))
+            .and_then(|_| self.partial_shape(&phrase, meta.as_deref(), argument_count))
+            .and_then(|partial| self.arrange_arguments(&phrase, meta.as_deref()).map(|_| partial));
+        let partial = match checked {
+            Ok(partial) => partial,
+            Err(error) => {
+                self.recover(error, span)?;
+                self.phrases.pop();
+                return Ok(None);
+            }
+        };
+
+        let form = meta.as_ref().map(|meta| meta.apply).unwrap_or_default();
+        let target = match meta.as_deref().and_then(declared_target) {
+            _ if self.options.preserve_words => None,
+            None => self.generated_name(&phrase, span)?,
+            target => target,
+        };
+        let target = target.map(|target| match (form, has_arguments) {
+            // property can't be a chain
+            (ApplyForm::Access, true) => target.rsplit('.').next().unwrap_or_default().to_string(),
+            _ => target,
+        });
+
+        let mut words = self.phrases.last().map(|info| info.part_nodes.clone()).unwrap_or_default();
+        words.push(node_index);
+        let arguments = self.phrases.last().map(|info| info.arguments.clone()).unwrap_or_default();
+        let mutation = Mutation::Phrase { phrase: phrase.clone(), words: words.clone(), arguments, span };
+        self.middleware.before(&mutation, &self.result);
+        let preserve_words = self.options.preserve_words;
+        if self.options.named_arguments {
+            self.name_arguments(meta.as_deref())?;
+        }
+        let added_from = self.result.get_nodes().len();
+
+        let new_index = self.profiler.mutation(|| {
+            let new_index = resolve_top_phrase(
+                node,
+                node_index,
+                is_left_of_parent,
+                &mut self.phrases,
+                &mut self.result,
+                Some(phrase.clone()),
+            )?;
+
+            if preserve_words {
+                if let Some(word) = self.result.get_node_mut(node_index) {
+                    word.set_lex_token(node.get_lex_token());
+                }
+            }
+
+            if let Some(target) = &target {
+                emit_target(&mut self.result, node_index, target)?;
+            }
+
+            match (new_index, partial) {
+                (Some(apply_index), Some(shape)) => {
+                    Ok(Some(shape_partial(&mut self.result, apply_index, node_index, has_arguments, shape)?))
+                }
+                (Some(apply_index), None) if has_arguments => {
+                    apply_form(&mut self.result, apply_index, node_index, form)?;
+                    Ok(new_index)
+                }
+                _ => Ok::<Option<usize>, PhraseError>(new_index),
+            }
+        })?;
+
+        self.place_tokens(node_index, added_from, first_word, node);
+        self.middleware.after(&mutation, new_index, &self.result);
+        self.record_match(phrase, meta, new_index, span, words);
+
+        Ok(new_index)
+    }
+
+    // applies ReduceOptions::tokens to the phrase identifier and the nodes added for the phrase
+    fn place_tokens(&mut self, phrase_index: usize, added_from: usize, first_word: usize, last_word: &ParseNode) {
+        let last = last_word.get_lex_token();
+        let first = self.original.get_node(first_word).map(|node| node.get_lex_token()).unwrap_or_else(|| last.clone());
+        let phrase_node = (!self.options.preserve_words).then_some(phrase_index);
+        let nodes = phrase_node.into_iter().chain(added_from..self.result.get_nodes().len());
+
+        self.options.tokens.place(&mut self.result, nodes, &first, &last);
+    }
+
+    fn arrange_arguments(&mut self, phrase: &str, meta: Option<&PhraseMeta>) -> Result<(), PhraseError> {
+        let order = meta
+            .and_then(|meta| meta.argument_order.as_ref())
+            .unwrap_or(&self.options.argument_order);
+        if let Some(info) = self.phrases.last_mut() {
+            info.arguments = order.arrange(&info.arguments).ok_or_else(|| PhraseError::ArgumentOrder {
+                phrase: phrase.to_string(),
+                arguments: info.arguments.len(),
+            })?;
+        }
+
+        Ok(())
+    }
+
+    // replaces each argument of the top phrase with a pair of its name and the argument
+    fn name_arguments(&mut self, meta: Option<&PhraseMeta>) -> Result<(), PhraseError> {
+        let default_meta = PhraseMeta::default();
+        let meta = meta.unwrap_or(&default_meta);
+        let arguments = self.phrases.last().map(|info| info.arguments.clone()).unwrap_or_default();
+        let keys = match self.options.argument_keys {
+            ArgumentKeys::Position => (0..arguments.len()).map(|position| meta.argument_name(position)).collect(),
+            ArgumentKeys::PrecedingWord => self.preceding_words(&arguments),
+        };
+
+        let mut pairs = vec![];
+        for (argument, key) in arguments.into_iter().zip(keys) {
+            let token = self.result.get_node(argument).ok_or(PhraseError::MissingNode(argument))?.get_lex_token();
+            let name = self.result.get_nodes().len();
+            self.result.add_node(ParseNode::new(
+                Definition::Identifier,
+                SecondaryDefinition::Identifier,
+                None,
+                None,
+                None,
+                LexerToken::new(key, TokenType::Identifier, token.get_line(), token.get_column()),
+            ));
+
+            let pair = tree::add_joining_node(&mut self.result, Definition::Pair, name, argument, "=", TokenType::Pair, &token);
+            self.result.get_node_mut(pair).ok_or(PhraseError::MissingNode(pair))?.set_secondary_definition(compat::PAIR_SECONDARY);
+            pairs.push(pair);
+        }
+
+        if let Some(info) = self.phrases.last_mut() {
+            info.arguments = pairs;
+        }
+
+        Ok(())
+    }
+
+    // text of the phrase word before each argument of the top phrase
+    // arguments after the same word are numbered from 2 in the order they were written
+    fn preceding_words(&self, arguments: &[usize]) -> Vec<String> {
+        let info = match self.phrases.last() {
+            None => return vec![],
+            Some(info) => info,
+        };
+
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        let mut keys: HashMap<usize, String> = HashMap::new();
+        for (argument, word) in info.argument_words.iter() {
+            let word = self.original.get_node(*word).map(|word| word.get_lex_token().get_text().clone()).unwrap_or_default();
+            let count = seen.entry(word.clone()).or_default();
+            *count += 1;
+            keys.insert(*argument, match count {
+                1 => word,
+                count => format!("{}{}", word, count),
+            });
+        }
+
+        arguments.iter().map(|argument| keys.remove(argument).unwrap_or_default()).collect()
+    }
+
+    fn complete_unterminated(&mut self) -> Result<(), PhraseError> {
+        let prefix = match self.phrases.last() {
+            None => return Ok(()),
+            Some(info) => info.full_text().to_string(),
+        };
+
+        let context = self.context;
+        let extra = &self.options.extra_phrases;
+        let mut completions = self.profiler.lookup(|| context.completions(&prefix));
+        completions.extend(extra.completions(&prefix));
+        completions.sort();
+        completions.dedup();
+        completions.retain(|phrase| self.is_visible(phrase));
+
+        // inferring alone only adds a single word and never picks between phrases
+        if !self.options.complete_unterminated {
+            completions.retain(|phrase| !phrase[prefix.len() + 1..].contains('_'));
+            if completions.len() != 1 {
+                return Ok(());
+            }
+        }
+
+        let completion = match (completions.as_slice(), &self.options.disambiguation) {
+            ([], _) => return Ok(()),
+            ([completion], _) => completion.clone(),
+            (_, Disambiguation::Arbitrate) => {
+                // only a single highest priority completion settles it
+                let priorities = completions.iter().map(|phrase| self.priority(phrase)).collect::<Vec<i64>>();
+                let highest = priorities.iter().copied().max().unwrap_or_default();
+                match priorities.iter().filter(|priority| **priority == highest).count() {
+                    1 => completions[priorities.iter().position(|priority| *priority == highest).unwrap_or_default()].clone(),
+                    _ => return Ok(()),
+                }
+            }
+            (_, disambiguation) => {
+                let info = self.phrases[self.phrases.len() - 1].clone();
+                let last = info.part_nodes.len() - 1;
+                let last = self.original.get_node(info.part_nodes[last]).ok_or(PhraseError::MissingNode(info.part_nodes[last]))?;
+                let span = self.phrase_span(last);
+                let candidates = completions.iter()
+                    .map(|phrase| {
+                        MatchCandidate::new(phrase, info.part_nodes.clone(), info.arguments.clone(), span)
+                            .with_weight(self.weight(phrase))
+                            .with_priority(self.priority(phrase))
+                    })
+                    .collect::<Vec<MatchCandidate>>();
+
+                // completions all claim the same words, a tie can't be settled by structure
+                if self.warn_ties(&candidates) {
+                    return Ok(());
+                }
+
+                let choice = match disambiguation {
+                    Disambiguation::Custom(disambiguator) => disambiguator.choose(&candidates),
+                    disambiguation => disambiguation.choose(&candidates),
+                };
+                match choice.and_then(|index| completions.get(index)) {
+                    None => return Ok(()),
+                    Some(completion) => completion.clone(),
+                }
+            }
+        };
+
+        // final word is placed after the last token of the input and listed after the whole tree
+        let mut last = self.original.get_root();
+        while let Some(right) = self.original.get_node(last).and_then(|node| node.get_right()) {
+            last = right;
+        }
+        let last_token = self.original.get_node(last).ok_or(PhraseError::MissingNode(last))?.get_lex_token();
+        let token = LexerToken::new(
+            completion[prefix.len() + 1..].to_string(),
+            TokenType::Identifier,
+            last_token.get_line(),
+            last_token.get_column() + last_token.get_text().len() + 1,
+        );
+
+        let word_index = self.result.get_nodes().len();
+        self.result.add_node(ParseNode::new(Definition::Identifier, SecondaryDefinition::Identifier, None, None, None, token.clone()));
+        let previous_root = self.result.get_root();
+        let root = tree::add_joining_node(&mut self.result, Definition::List, previous_root, word_index, " ", TokenType::Whitespace, &token);
+        self.result.set_root(root);
+
+        let word = self.result.get_node(word_index).cloned().ok_or(PhraseError::MissingNode(word_index))?;
+        let matched = self.report.matches.len();
+        self.resolve_top_phrase(&word, word_index, false, completion)?;
+
+        for m in self.report.matches.iter_mut().skip(matched) {
+            m.recovered = true;
+        }
+
+        if self.options.infer_final_word {
+            let inferred = self.report.matches.iter()
+                .skip(matched)
+                .map(|m| ReduceWarning::InferredWord { phrase: m.phrase.clone(), word: token.get_text().clone(), span: m.span })
+                .collect::<Vec<ReduceWarning>>();
+            self.report.warnings.extend(inferred);
+        }
+
+        Ok(())
+    }
+
+    // warns about phrases still in progress once the input is done, also recorded in ReduceReport::unfinished when learning
+    fn record_unfinished(&mut self) {
+        let mut unfinished = vec![];
+        for info in &self.phrases {
+            let first = info.part_nodes.first().and_then(|index| self.original.get_node(*index));
+            let last = info.part_nodes.last().and_then(|index| self.original.get_node(*index));
+            if let (Some(first), Some(last)) = (first, last) {
+                unfinished.push((info.full_text().to_string(), Span::between(first, last)));
+            }
+        }
+
+        for (words, span) in unfinished {
+            let suggestions = self.suggestions(&words);
+            let phrase = UnfinishedPhrase { words, span, suggestions };
+            if self.options.learn {
+                self.report.unfinished.push(phrase.clone());
+            }
+            self.report.warnings.push(ReduceWarning::Unfinished { phrase });
+        }
+    }
+
+    // warns about a word that isn't a phrase word when it's close to the start of registered phrases
+    fn suggest_phrases(&mut self, node: &ParseNode, word: &str) {
+        let suggestions = self.suggestions(word);
+        if !suggestions.is_empty() {
+            self.report.warnings.push(ReduceWarning::NotAPhrase { word: word.to_string(), span: Span::of_node(node), suggestions });
+        }
+    }
+
+    // registered phrases closest to words by edit distance of their leading words
+    fn suggestions(&mut self, words: &str) -> Vec<String> {
+        if self.registered.is_none() {
+            let mut phrases = self.context.iter_phrases().chain(self.options.extra_phrases.iter_phrases()).collect::<Vec<String>>();
+            phrases.sort();
+            phrases.dedup();
+            phrases.retain(|phrase| self.is_visible(phrase));
+            self.registered = Some(SuggestionIndex::new(phrases));
+        }
+
+        match &self.registered {
+            Some(index) if !index.is_empty() => index.suggestions(words),
+            // contexts that can't list their phrases still suggest completions
+            _ => {
+                let completions = self.context.completions(words).into_iter().chain(self.options.extra_phrases.completions(words));
+                distance::suggestions(words, completions)
+            }
+        }
+    }
+
+    // records errors the reduction can continue past when collecting them, others are returned
+    fn recover(&mut self, error: PhraseError, span: Span) -> Result<(), PhraseError> {
+        let recovered = match error {
+            PhraseError::MissingArguments { phrase, expected, found } if self.options.collect_errors => {
+                ReduceError::MissingArguments { phrase, expected, found, span }
+            }
+            PhraseError::ExtraArguments { phrase, expected, found } if self.options.collect_errors => {
+                ReduceError::ExtraArguments { phrase, expected, found, span }
+            }
+            PhraseError::ArgumentOrder { phrase, arguments } if self.options.collect_errors => {
+                ReduceError::ArgumentOrder { phrase, arguments, span }
+            }
+            PhraseError::TemplateMismatch { phrase, template, found } if self.options.collect_errors => {
+                ReduceError::TemplateMismatch { phrase, template, found, span }
+            }
+            error => return Err(error),
+        };
+
+        self.report.errors.push(recovered);
+        Ok(())
+    }
+
+    // shape to emit when phrase is given fewer arguments than its arity
+    fn partial_shape(
+        &self,
+        phrase: &str,
+        meta: Option<&PhraseMeta>,
+        found: usize,
+    ) -> Result<Option<PartialShape>, PhraseError> {
+        match meta.and_then(|meta| meta.arity) {
+            Some(expected) if found < expected => match self.options.partial_shape {
+                PartialShape::Error => Err(PhraseError::MissingArguments { phrase: phrase.to_string(), expected, found }),
+                shape => Ok(Some(shape)),
+            },
+            _ => Ok(None),
+        }
+    }
+
+    fn has_trailing_items(&mut self, phrase: &str, node_index: usize) -> Result<bool, PhraseError> {
+        if !self.meta(phrase).is_some_and(|meta| meta.trailing_arguments) {
+            return Ok(false);
+        }
+
+        let (_, items) = tree::list_spine(&self.result, node_index)?;
+        Ok(items.last() != Some(&node_index))
+    }
+
+    // adds the items after the final word of a phrase with trailing arguments to its arguments
+    // the final word is moved behind them so they're arranged like arguments written before it
+    // returns the final word with its new parent, None when nothing was taken
+    fn take_trailing_items(&mut self, phrase: &str, node_index: usize) -> Result<Option<ParseNode>, PhraseError> {
+        if !self.has_trailing_items(phrase, node_index)? {
+            return Ok(None);
+        }
+
+        let (spine, mut items) = tree::list_spine(&self.result, node_index)?;
+        let position = items.iter().position(|item| *item == node_index).ok_or(PhraseError::MissingNode(node_index))?;
+        let trailing = items.split_off(position + 1);
+        items.pop();
+        items.extend(trailing.iter().copied());
+        items.push(node_index);
+        tree::relink_list(&mut self.result, &spine, &items)?;
+
+        if let Some(info) = self.phrases.last_mut() {
+            for item in trailing.iter() {
+                info.arguments.push(*item);
+                info.argument_words.push((*item, node_index));
+            }
+        }
+        self.trailing.extend(trailing);
+
+        let mut moved = self.original.get_node(node_index).ok_or(PhraseError::MissingNode(node_index))?.clone();
+        moved.set_parent(spine.last().copied());
+        Ok(Some(moved))
+    }
+
+    // number of arguments written after each word of the top phrase ending at last_word
+    fn written_slots(&self, last_word: usize) -> Vec<usize> {
+        let info = match self.phrases.last() {
+            None => return vec![0],
+            Some(info) => info,
+        };
+
+        // a single word phrase taking trailing arguments starts with its only word
+        let mut words = info.part_nodes.clone();
+        if words.last() != Some(&last_word) {
+            words.push(last_word);
+        }
+        let mut slots = vec![0; words.len()];
+        for (_, word) in info.argument_words.iter() {
+            if let Some(position) = words.iter().position(|part| part == word) {
+                slots[position] += 1;
+            }
+        }
+
+        slots
+    }
+
+    // rejects a phrase added as a template whose arguments aren't written in its slots
+    fn check_template(&self, phrase: &str, meta: Option<&PhraseMeta>, written: &[usize]) -> Result<(), PhraseError> {
+        match meta.and_then(|meta| meta.template(phrase)) {
+            Some(template) if meta.is_some_and(|meta| meta.slots != written) => Err(PhraseError::TemplateMismatch {
+                phrase: phrase.to_string(),
+                template,
+                found: fill_template(phrase, written),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    // warns about or rejects a phrase given more arguments than it takes
+    fn check_extra_arguments(&mut self, phrase: &str, meta: Option<&PhraseMeta>, found: usize, span: Span) -> Result<(), PhraseError> {
+        let expected = match meta.and_then(PhraseMeta::max_arguments) {
+            Some(expected) if found > expected => expected,
+            _ => return Ok(()),
+        };
+
+        match self.options.extra_arguments {
+            ExtraArguments::Allow => Ok(()),
+            ExtraArguments::Warn => {
+                self.report.warnings.push(ReduceWarning::ExtraArguments { phrase: phrase.to_string(), expected, found, span });
+                Ok(())
+            }
+            ExtraArguments::Deny => Err(PhraseError::ExtraArguments { phrase: phrase.to_string(), expected, found }),
+        }
+    }
+
+    // whether meta lets a phrase starting at first_word match where it's written
+    fn allowed_at(&self, phrase: &str, meta: Option<&PhraseMeta>, first_word: usize, span: Span) -> bool {
+        self.plan.is_none_or(|plan| plan.allows(phrase, span))
+            && !self.in_skip_region(span)
+            && meta.is_none_or(|meta| meta.allows_position(tree::syntactic_position(self.original, first_word)))
+    }
+
+    // asks ReduceOptions::validation about the top phrase ending at last_word
+    fn validate(&self, phrase: &str, last_word: usize, span: Span) -> bool {
+        if matches!(self.options.validation, Validation::None) {
+            return true;
+        }
+
+        let info = self.phrases.last();
+        let mut words = info.map(|info| info.part_nodes.clone()).unwrap_or_default();
+        words.push(last_word);
+        let arguments = info.map(|info| info.arguments.clone()).unwrap_or_default();
+
+        self.options.validation.accepts(&MatchedPhrase { phrase: phrase.to_string(), words, arguments, span }, &self.result)
+    }
+
+    // first or last word of the phrase is in a region of ReduceOptions::skip_regions
+    // a region between the words, e.g. inside a group argument, only keeps phrases in the group from matching
+    fn in_skip_region(&self, span: Span) -> bool {
+        self.options.skip_regions.iter().any(|region| region.contains(span.start) || (region.start < span.end && span.end <= region.end))
+    }
+
+    // identifier for a phrase without a target, None when the phrase text is used as is
+    fn generated_name(&mut self, phrase: &str, span: Span) -> Result<Option<String>, PhraseError> {
+        let separator = self.options.separator.clone().or_else(|| self.context.separator()).unwrap_or_else(|| "_".to_string());
+        let joined = self.options.naming.name(phrase, &separator);
+
+        let hygiene = &self.options.hygiene;
+        if let Some(name) = hygiene.collision(&joined) {
+            match hygiene.on_collision {
+                CollisionSeverity::Allow => (),
+                CollisionSeverity::Warn => {
+                    self.report.warnings.push(ReduceWarning::NameCollision { phrase: phrase.to_string(), name, span });
+                }
+                CollisionSeverity::Deny => return Err(PhraseError::NameCollision { phrase: phrase.to_string(), name }),
+            }
+        }
+
+        Ok((!hygiene.is_identity() || joined != phrase).then(|| hygiene.name(&joined)))
+    }
+
+    // span from first word of top phrase to given final word
+    fn phrase_span(&self, last: &ParseNode) -> Span {
+        let first = self.phrases.last()
+            .and_then(|info| info.part_nodes.first())
+            .and_then(|index| self.original.get_node(*index))
+            .unwrap_or(last);
+
+        Span::between(first, last)
+    }
+
+    fn record_match(&mut self, phrase: String, meta: Option<Rc<PhraseMeta>>, node: Option<usize>, span: Span, words: Vec<usize>) {
+        if let Some(node) = node {
+            if let Some(deprecation) = meta.as_ref().and_then(|meta| meta.deprecated.as_ref()) {
+                self.report.warnings.push(ReduceWarning::Deprecated {
+                    phrase: phrase.clone(),
+                    replacement: deprecation.replacement.clone(),
+                    span,
+                    source: meta.as_ref().and_then(|meta| meta.source.clone()),
+                });
+            }
+
+            if self.options.annotate {
+                annotation::annotate(&mut self.result, node, &phrase, span);
+            }
+
+            self.report.matches.push(PhraseMatch { phrase, node, span, meta: meta.map(|meta| meta.as_ref().clone()), words, recovered: false, expression: None });
+        }
+    }
+}
+
+// flattens the tree into the sequence of list items the matcher checks
+// paired with whether the item is on the left side of its parent
+pub(crate) fn collect_phrase_items(parse_result: &ParseResult) -> Result<Vec<TraversalItem>, PhraseError> {
+    let current_index = parse_result.get_root();
+
+    // a single node can't be a parent
+    // and only needs a single check
+    if parse_result.get_nodes().len() == 1 {
+        return Ok(vec![(Some(current_index), false)]);
+    }
+
+    let mut parent_stack = vec![];
+    let mut process_stack = vec![current_index];
+
+    while let Some(current_index) = process_stack.pop() {
+        match parse_result.get_node(current_index) {
+            None => Err(PhraseError::MissingNode(current_index))?,
+            Some(node) => {
+                match (node.get_left(), node.get_right()) {
+                    (None, None) => continue, // not a parent, skip
+                    (Some(left_index), Some(right_index)) => {
+                        // process left then right will result in parent stack processing
+                        // left before right
+                        process_stack.push(left_index);
+                        process_stack.push(right_index);
+                    }
+                    (Some(left_index), None) => {
+                        process_stack.push(left_index);
+                    }
+                    (None, Some(right_index)) => {
+                        process_stack.push(right_index);
+                    }
+                }
+
+                parent_stack.push(current_index);
+            }
+        }
+    }
+
+    let mut items = vec![];
+
+    while let Some(current_index) = parent_stack.pop() {
+        let current_parent = parse_result.get_node(current_index)
+            .ok_or(PhraseError::MissingNode(current_index))?;
+
+        // phrases can only be contained in a list
+        match current_parent.get_definition() {
+            Definition::List => (),
+            _ => continue
+        };
+
+        // check left then right for phrases
+        items.push((current_parent.get_left(), true));
+        items.push((current_parent.get_right(), false));
+    }
+
+    Ok(items)
+}
+
+fn resolve_single_word_phrase(
+    node: &ParseNode,
+    node_index: usize,
+    result: &mut ParseResult,
+) -> Result<Option<usize>, PhraseError> {
+    // and add a new empty apply node in the identifier's place
+    let parent = result.get_node(node_index).ok_or(PhraseError::MissingNode(node_index))?.get_parent();
+    let new_index = result.get_nodes().len();
+    result.add_node(ParseNode::new(
+        Definition::EmptyApply,
+        SecondaryDefinition::UnarySuffix,
+        parent,
+        Some(node_index),
+        None,
+        node.get_lex_token().clone(), // clone so debugging points to identifier
+    ));
+
+    tree::replace_child(result, parent, node_index, new_index);
+
+    match result.get_node_mut(node_index) {
+        None => Err(PhraseError::MissingNode(node_index))?,
+        Some(node) => {
+            node.set_parent(Some(new_index));
+        }
+    }
+
+    Ok(Some(new_index))
+}
+
+fn resolve_top_phrase(
+    node: &ParseNode,
+    node_index: usize,
+    is_left_of_parent: bool,
+    phrases: &mut Vec<PhraseInfo>,
+    result: &mut ParseResult,
+    text: Option<String>
+) -> Result<Option<usize>, PhraseError> {
+    let info = match phrases.last() {
+        None => return Ok(None),
+        Some(i) => i
+    };
+
+    let new_phrase_text = match text {
+        None =>  info.full_text().to_string(),
+        Some(text) => text,
+    };
+
+    // update current node token to be full phrase
+    match result.get_node_mut(node_index) {
+        None => Err(PhraseError::MissingNode(node_index))?,
+        Some(node) => {
+            let new_token = LexerToken::new(
+                new_phrase_text,
+                TokenType::Identifier,
+                node.get_lex_token().get_line(),
+                node.get_lex_token().get_column(),
+            );
+            node.set_lex_token(new_token);
+        }
+    }
+
+    let arg = match info.arguments.len() {
+        0 => {
+            let new_index = result.get_nodes().len();
+            let empty_parent = if Some(result.get_root()) == node.get_parent() {
+                None
+            } else {
+                node.get_parent()
+            };
+
+            match node.get_parent().and_then(|p| result.get_node_mut(p)) {
+                None => Err(missing_parent(node, node_index))?,
+                Some(parent) => {
+                    match is_left_of_parent {
+                        true => parent.set_left(Some(new_index)),
+                        false => parent.set_right(Some(new_index)),
+                    }
+
+                    result.add_node(ParseNode::new(
+                        Definition::EmptyApply,
+                        SecondaryDefinition::UnarySuffix,
+                        empty_parent,
+                        Some(node_index),
+                        None,
+                        node.get_lex_token().clone(), // clone so debugging points to identifier
+                    ));
+
+                    match result.get_node_mut(node_index) {
+                        None => Err(PhraseError::MissingNode(node_index))?,
+                        Some(node) => {
+                            node.set_parent(Some(new_index));
+                        }
+                    }
+                }
+            }
+
+            if Some(result.get_root()) == node.get_parent() {
+                result.set_root(new_index);
+            }
+
+            Some(new_index)
+        }
+        1 => {
+            match node.get_parent().and_then(|p| result.get_node_mut(p)) {
+                None => Err(missing_parent(node, node_index))?,
+                Some(parent) => {
+                    // Using ApplyTo instead of Apply so no swapping needs to be done
+                    parent.set_definition(Definition::ApplyTo);
+
+                    // for single argument just replace current left side to point to argument
+                    let new_left = info.arguments.first().cloned();
+                    parent.set_left(new_left);
+
+                    // update argument to correct parent
+                    match new_left.and_then(|i| result.get_node_mut(i)) {
+                        None => Err(PhraseError::MissingNode(new_left.unwrap_or_default()))?,
+                        Some(left_node) => {
+                            left_node.set_parent(node.get_parent())
+                        }
+                    }
+                }
+            }
+            node.get_parent()
+        }
+        _n => {
+            let mut current_parent = node.get_parent().unwrap_or_default();
+            let mut next_parent = match node.get_parent().and_then(|p| result.get_node_mut(p)) {
+                None => Err(missing_parent(node, node_index))?,
+                Some(parent) => {
+                    // Using ApplyTo instead of Apply so no swapping needs to be done
+                    parent.set_definition(Definition::ApplyTo);
+
+                    parent.get_left()
+                }
+            };
+
+            // descend list attaching arguments in reverse order
+            // last two arguments will have same parent as left and right
+            // end at 1 so the 0th item can always be put on last list's left
+            for i in (1..info.arguments.len()).rev() {
+                let arg_index = *info.arguments.get(i).unwrap();
+
+                // update argument's parent
+                match result.get_node_mut(arg_index) {
+                    None => Err(PhraseError::MissingNode(arg_index))?,
+                    Some(right) => {
+                        right.set_parent(next_parent);
+                    }
+                }
+
+                // update parent's right to point to argument
                 // and set next parent to left
-                let left = match next_parent.and_then(|i| result.get_node_mut(i)) {
-                    None => Err(format!("Node at {:?} not found", next_parent))?,
+                let list_index = next_parent.ok_or(PhraseError::MissingLeft(current_parent))?;
+                let left = match result.get_node_mut(list_index) {
+                    None => Err(PhraseError::MissingNode(list_index))?,
                     Some(parent) => {
                         parent.set_right(Some(arg_index));
                         let left = parent.get_left();
 
-                        // if on second to last arg
-                        // grab last arg and update it and parent
-                        if i == 1 {
-                            let arg_index = *info.arguments.get(0).unwrap();
-                            parent.set_left(Some(arg_index));
+                        // if on second to last arg
+                        // grab last arg and update it and parent
+                        if i == 1 {
+                            let arg_index = *info.arguments.first().unwrap();
+                            parent.set_left(Some(arg_index));
+
+                            match result.get_node_mut(arg_index) {
+                                None => Err(PhraseError::MissingNode(arg_index))?,
+                                Some(left) => {
+                                    left.set_parent(next_parent);
+                                    break;
+                                }
+                            }
+                        }
+
+                        left
+                    }
+                };
+
+                current_parent = list_index;
+                next_parent = left;
+            }
+
+            node.get_parent()
+        }
+    };
+
+    phrases.pop();
+
+    Ok(arg)
+}
+
+// rewrites the ApplyTo node made for a phrase with arguments into the phrase's declared form
+fn apply_form(
+    result: &mut ParseResult,
+    apply_index: usize,
+    phrase_index: usize,
+    form: ApplyForm,
+) -> Result<(), PhraseError> {
+    match form {
+        ApplyForm::ApplyTo => (),
+        ApplyForm::Apply => {
+            let apply = result.get_node_mut(apply_index).ok_or(PhraseError::MissingNode(apply_index))?;
+            let arguments = apply.get_left();
+            apply.set_definition(Definition::Apply);
+            apply.set_left(Some(phrase_index));
+            apply.set_right(arguments);
+        }
+        ApplyForm::Access => {
+            result.get_node_mut(apply_index)
+                .ok_or(PhraseError::MissingNode(apply_index))?
+                .set_definition(Definition::Access);
+            result.get_node_mut(phrase_index)
+                .ok_or(PhraseError::MissingNode(phrase_index))?
+                .set_definition(Definition::Property);
+        }
+        ApplyForm::EmptyApply => {
+            let new_index = result.get_nodes().len();
+            let token = result.get_node(phrase_index)
+                .ok_or(PhraseError::MissingNode(phrase_index))?
+                .get_lex_token()
+                .clone();
+
+            result.add_node(ParseNode::new(
+                Definition::EmptyApply,
+                SecondaryDefinition::UnarySuffix,
+                Some(apply_index),
+                Some(phrase_index),
+                None,
+                token,
+            ));
+
+            result.get_node_mut(apply_index)
+                .ok_or(PhraseError::MissingNode(apply_index))?
+                .set_right(Some(new_index));
+            result.get_node_mut(phrase_index)
+                .ok_or(PhraseError::MissingNode(phrase_index))?
+                .set_parent(Some(new_index));
+        }
+    }
+
+    Ok(())
+}
+
+// declared targets may separate namespaces with double colons, tasks::perform is emitted as tasks.perform
+// generated names are left alone so a :: separator stays one identifier
+fn declared_target(meta: &PhraseMeta) -> Option<String> {
+    meta.target.as_ref().map(|target| target.replace("::", "."))
+}
+
+// replaces the phrase identifier's text with its target
+// a dotted target turns the identifier node into the root of an access chain
+fn emit_target(result: &mut ParseResult, phrase_index: usize, target: &str) -> Result<(), PhraseError> {
+    let token = result.get_node(phrase_index)
+        .ok_or(PhraseError::MissingNode(phrase_index))?
+        .get_lex_token();
+    let make_token = |text: &str, token_type: TokenType| {
+        LexerToken::new(text.to_string(), token_type, token.get_line(), token.get_column())
+    };
+
+    let segments = target.split('.').collect::<Vec<&str>>();
+
+    let (last, namespace) = match segments.split_last() {
+        Some((last, namespace)) if !namespace.is_empty() => (*last, namespace),
+        _ => {
+            let node = result.get_node_mut(phrase_index).ok_or(PhraseError::MissingNode(phrase_index))?;
+            node.set_lex_token(make_token(target, TokenType::Identifier));
+            return Ok(());
+        }
+    };
+
+    // build chain for namespace from the left, first segment is a plain identifier
+    let mut left = result.get_nodes().len();
+    result.add_node(ParseNode::new(
+        Definition::Identifier,
+        SecondaryDefinition::Identifier,
+        None,
+        None,
+        None,
+        make_token(namespace[0], TokenType::Identifier),
+    ));
+
+    for segment in namespace.iter().skip(1) {
+        let property = result.get_nodes().len();
+        let access = property + 1;
+
+        result.add_node(ParseNode::new(
+            Definition::Property,
+            SecondaryDefinition::Identifier,
+            Some(access),
+            None,
+            None,
+            make_token(segment, TokenType::Identifier),
+        ));
+        result.add_node(ParseNode::new(
+            Definition::Access,
+            SecondaryDefinition::BinaryLeftToRight,
+            None,
+            Some(left),
+            Some(property),
+            make_token(".", TokenType::Period),
+        ));
+
+        if let Some(node) = result.get_node_mut(left) {
+            node.set_parent(Some(access));
+        }
+
+        left = access;
+    }
+
+    // phrase node becomes the final access so its parent needs no changes
+    let property = result.get_nodes().len();
+    result.add_node(ParseNode::new(
+        Definition::Property,
+        SecondaryDefinition::Identifier,
+        Some(phrase_index),
+        None,
+        None,
+        make_token(last, TokenType::Identifier),
+    ));
+
+    if let Some(node) = result.get_node_mut(left) {
+        node.set_parent(Some(phrase_index));
+    }
+
+    let node = result.get_node_mut(phrase_index).ok_or(PhraseError::MissingNode(phrase_index))?;
+    node.set_definition(Definition::Access);
+    node.set_secondary_definition(SecondaryDefinition::BinaryLeftToRight);
+    node.set_left(Some(left));
+    node.set_right(Some(property));
+    node.set_lex_token(make_token(".", TokenType::Period));
+
+    Ok(())
+}
+
+fn missing_parent(node: &ParseNode, node_index: usize) -> PhraseError {
+    match node.get_parent() {
+        None => PhraseError::MissingParent(node_index),
+        Some(parent) => PhraseError::MissingNode(parent),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use garnish_lang_compiler::lex::lex;
+    use garnish_lang_compiler::parse::{Definition, parse};
+    use crate::{
+        reduce_phrases, reduce_phrases_at, reduce_phrases_with_middleware, reduce_phrases_with_options, ApplyForm, ArgumentOrder, Disambiguation, MatchCandidate, Mutation,
+        MutationMiddleware, PartialShape, PhraseMeta, Traversal, TraversalItem, TraversalStrategy, PhraseSource, Position, ReduceError, ReduceOptions, ReduceWarning, Span,
+        Visibility, CollisionSeverity, Hygiene, Normalization, PhraseError, SyntacticPosition,
+    };
+    use crate::context::SimplePhraseContext;
+
+    #[test]
+    fn simple_phrase() {
+        let input = "perform task";
+
+        let tokens = lex(input).unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+
+        let phrased_tokens = reduce_phrases(&parsed, &context).unwrap();
+
+        let apply_token = phrased_tokens.get_node(3).unwrap();
+
+        assert_eq!(phrased_tokens.get_root(), 3);
+        assert_eq!(apply_token.get_definition(), Definition::EmptyApply);
+        assert_eq!(apply_token.get_left(), Some(2));
+        assert_eq!(apply_token.get_right(), None);
+        assert_eq!(apply_token.get_parent(), None);
+
+        let identifier_token = phrased_tokens.get_node(2).unwrap();
+        assert_eq!(identifier_token.get_definition(), Definition::Identifier);
+        assert_eq!(identifier_token.get_left(), None);
+        assert_eq!(identifier_token.get_right(), None);
+        assert_eq!(identifier_token.get_parent(), Some(3));
+        assert_eq!(identifier_token.get_lex_token().get_text(), "perform_task");
+    }
+
+    #[test]
+    fn simple_phrase_with_argument() {
+        let input = "perform 5 task";
+
+        let tokens = lex(input).unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+
+        let phrased_tokens = reduce_phrases(&parsed, &context).unwrap();
+
+        let apply_token = phrased_tokens.get_node(3).unwrap();
+
+        assert_eq!(phrased_tokens.get_root(), 3);
+        assert_eq!(apply_token.get_definition(), Definition::ApplyTo);
+        assert_eq!(apply_token.get_left(), Some(2));
+        assert_eq!(apply_token.get_right(), Some(4));
+        assert_eq!(apply_token.get_parent(), None);
+
+        let identifier_token = phrased_tokens.get_node(2).unwrap();
+        assert_eq!(identifier_token.get_definition(), Definition::Number);
+        assert_eq!(identifier_token.get_left(), None);
+        assert_eq!(identifier_token.get_right(), None);
+        assert_eq!(identifier_token.get_parent(), Some(3));
+        assert_eq!(identifier_token.get_lex_token().get_text(), "5");
+
+        let identifier_token = phrased_tokens.get_node(4).unwrap();
+        assert_eq!(identifier_token.get_definition(), Definition::Identifier);
+        assert_eq!(identifier_token.get_left(), None);
+        assert_eq!(identifier_token.get_right(), None);
+        assert_eq!(identifier_token.get_parent(), Some(3));
+        assert_eq!(identifier_token.get_lex_token().get_text(), "perform_task");
+    }
+
+    #[test]
+    fn simple_phrase_with_two_arguments() {
+        let input = "perform 5 10 task";
+
+        let tokens = lex(input).unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+
+        let phrased_tokens = reduce_phrases(&parsed, &context).unwrap();
+
+        let apply_token = phrased_tokens.get_node(5).unwrap();
+
+        assert_eq!(apply_token.get_definition(), Definition::ApplyTo);
+        assert_eq!(apply_token.get_left(), Some(3));
+        assert_eq!(apply_token.get_right(), Some(6));
+        assert_eq!(apply_token.get_parent(), None);
+
+        let identifier_token = phrased_tokens.get_node(6).unwrap();
+        assert_eq!(identifier_token.get_definition(), Definition::Identifier);
+        assert_eq!(identifier_token.get_left(), None);
+        assert_eq!(identifier_token.get_right(), None);
+        assert_eq!(identifier_token.get_parent(), Some(5));
+        assert_eq!(identifier_token.get_lex_token().get_text(), "perform_task");
+
+        let identifier_token = phrased_tokens.get_node(3).unwrap();
+        assert_eq!(identifier_token.get_definition(), Definition::List);
+        assert_eq!(identifier_token.get_left(), Some(2));
+        assert_eq!(identifier_token.get_right(), Some(4));
+        assert_eq!(identifier_token.get_parent(), Some(5));
+
+        let identifier_token = phrased_tokens.get_node(2).unwrap();
+        assert_eq!(identifier_token.get_definition(), Definition::Number);
+        assert_eq!(identifier_token.get_left(), None);
+        assert_eq!(identifier_token.get_right(), None);
+        assert_eq!(identifier_token.get_parent(), Some(3));
+        assert_eq!(identifier_token.get_lex_token().get_text(), "5");
+
+        let identifier_token = phrased_tokens.get_node(4).unwrap();
+        assert_eq!(identifier_token.get_definition(), Definition::Number);
+        assert_eq!(identifier_token.get_left(), None);
+        assert_eq!(identifier_token.get_right(), None);
+        assert_eq!(identifier_token.get_parent(), Some(3));
+        assert_eq!(identifier_token.get_lex_token().get_text(), "10");
+    }
+
+    #[test]
+    fn simple_phrase_with_three_arguments() {
+        let input = "perform 5 10 15 task";
+
+        let tokens = lex(input).unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+
+        let phrased_tokens = reduce_phrases(&parsed, &context).unwrap();
+
+        let apply_token = phrased_tokens.get_node(7).unwrap();
+
+        assert_eq!(apply_token.get_definition(), Definition::ApplyTo);
+        assert_eq!(apply_token.get_left(), Some(5));
+        assert_eq!(apply_token.get_right(), Some(8));
+        assert_eq!(apply_token.get_parent(), None);
+
+        let identifier_token = phrased_tokens.get_node(8).unwrap();
+        assert_eq!(identifier_token.get_definition(), Definition::Identifier);
+        assert_eq!(identifier_token.get_left(), None);
+        assert_eq!(identifier_token.get_right(), None);
+        assert_eq!(identifier_token.get_parent(), Some(7));
+        assert_eq!(identifier_token.get_lex_token().get_text(), "perform_task");
+
+        let identifier_token = phrased_tokens.get_node(5).unwrap();
+        assert_eq!(identifier_token.get_definition(), Definition::List);
+        assert_eq!(identifier_token.get_left(), Some(3));
+        assert_eq!(identifier_token.get_right(), Some(6));
+        assert_eq!(identifier_token.get_parent(), Some(7));
+
+        let identifier_token = phrased_tokens.get_node(6).unwrap();
+        assert_eq!(identifier_token.get_definition(), Definition::Number);
+        assert_eq!(identifier_token.get_left(), None);
+        assert_eq!(identifier_token.get_right(), None);
+        assert_eq!(identifier_token.get_parent(), Some(5));
+        assert_eq!(identifier_token.get_lex_token().get_text(), "15");
+
+        let identifier_token = phrased_tokens.get_node(3).unwrap();
+        assert_eq!(identifier_token.get_definition(), Definition::List);
+        assert_eq!(identifier_token.get_left(), Some(2));
+        assert_eq!(identifier_token.get_right(), Some(4));
+        assert_eq!(identifier_token.get_parent(), Some(5));
+
+        let identifier_token = phrased_tokens.get_node(2).unwrap();
+        assert_eq!(identifier_token.get_definition(), Definition::Number);
+        assert_eq!(identifier_token.get_left(), None);
+        assert_eq!(identifier_token.get_right(), None);
+        assert_eq!(identifier_token.get_parent(), Some(3));
+        assert_eq!(identifier_token.get_lex_token().get_text(), "5");
+
+        let identifier_token = phrased_tokens.get_node(4).unwrap();
+        assert_eq!(identifier_token.get_definition(), Definition::Number);
+        assert_eq!(identifier_token.get_left(), None);
+        assert_eq!(identifier_token.get_right(), None);
+        assert_eq!(identifier_token.get_parent(), Some(3));
+        assert_eq!(identifier_token.get_lex_token().get_text(), "10");
+    }
+
+    #[test]
+    fn three_word_two_arg_phrase() {
+        let input = "perform 5 special 10 task";
+
+        let tokens = lex(input).unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_special_task").unwrap();
+
+        let phrased_tokens = reduce_phrases(&parsed, &context).unwrap();
+
+        let apply_token = phrased_tokens.get_node(7).unwrap();
+
+        assert_eq!(apply_token.get_definition(), Definition::ApplyTo);
+        assert_eq!(apply_token.get_left(), Some(5));
+        assert_eq!(apply_token.get_right(), Some(8));
+        assert_eq!(apply_token.get_parent(), None);
+
+        let identifier_token = phrased_tokens.get_node(8).unwrap();
+        assert_eq!(identifier_token.get_definition(), Definition::Identifier);
+        assert_eq!(identifier_token.get_left(), None);
+        assert_eq!(identifier_token.get_right(), None);
+        assert_eq!(identifier_token.get_parent(), Some(7));
+        assert_eq!(identifier_token.get_lex_token().get_text(), "perform_special_task");
+
+        let identifier_token = phrased_tokens.get_node(5).unwrap();
+        assert_eq!(identifier_token.get_definition(), Definition::List);
+        assert_eq!(identifier_token.get_left(), Some(2));
+        assert_eq!(identifier_token.get_right(), Some(6));
+        assert_eq!(identifier_token.get_parent(), Some(7));
+
+        let identifier_token = phrased_tokens.get_node(2).unwrap();
+        assert_eq!(identifier_token.get_definition(), Definition::Number);
+        assert_eq!(identifier_token.get_left(), None);
+        assert_eq!(identifier_token.get_right(), None);
+        assert_eq!(identifier_token.get_parent(), Some(5));
+        assert_eq!(identifier_token.get_lex_token().get_text(), "5");
+
+        let identifier_token = phrased_tokens.get_node(6).unwrap();
+        assert_eq!(identifier_token.get_definition(), Definition::Number);
+        assert_eq!(identifier_token.get_left(), None);
+        assert_eq!(identifier_token.get_right(), None);
+        assert_eq!(identifier_token.get_parent(), Some(5));
+        assert_eq!(identifier_token.get_lex_token().get_text(), "10");
+    }
+
+    #[test]
+    fn nested_two_word_phrase() {
+        let input = "perform super special task";
+
+        let tokens = lex(input).unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+        context.add_phrase("super_special").unwrap();
+
+        let phrased_tokens = reduce_phrases(&parsed, &context).unwrap();
+
+        let apply_token = phrased_tokens.get_node(5).unwrap();
+
+        assert_eq!(phrased_tokens.get_nodes().len(), 8);
+
+        assert_eq!(phrased_tokens.get_root(), 5);
+        assert_eq!(apply_token.get_definition(), Definition::ApplyTo);
+        assert_eq!(apply_token.get_left(), Some(7));
+        assert_eq!(apply_token.get_right(), Some(6));
+        assert_eq!(apply_token.get_parent(), None);
+
+        let identifier_token = phrased_tokens.get_node(6).unwrap();
+        assert_eq!(identifier_token.get_definition(), Definition::Identifier);
+        assert_eq!(identifier_token.get_left(), None);
+        assert_eq!(identifier_token.get_right(), None);
+        assert_eq!(identifier_token.get_parent(), Some(5));
+        assert_eq!(identifier_token.get_lex_token().get_text(), "perform_task");
+
+        let identifier_token = phrased_tokens.get_node(7).unwrap();
+        assert_eq!(identifier_token.get_definition(), Definition::EmptyApply);
+        assert_eq!(identifier_token.get_left(), Some(4));
+        assert_eq!(identifier_token.get_right(), None);
+        assert_eq!(identifier_token.get_parent(), Some(5));
+
+        let identifier_token = phrased_tokens.get_node(4).unwrap();
+        assert_eq!(identifier_token.get_definition(), Definition::Identifier);
+        assert_eq!(identifier_token.get_left(), None);
+        assert_eq!(identifier_token.get_right(), None);
+        assert_eq!(identifier_token.get_parent(), Some(7));
+        assert_eq!(identifier_token.get_lex_token().get_text(), "super_special");
+    }
+
+    #[test]
+    fn operator_terminates_phrase() {
+        let input = "perform + task";
+
+        let tokens = lex(input).unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+
+        let phrased_tokens = reduce_phrases(&parsed, &context).unwrap();
+
+        let apply_token = phrased_tokens.get_node(1).unwrap();
+
+        assert_eq!(phrased_tokens.get_nodes().len(), 3);
+
+        assert_eq!(phrased_tokens.get_root(), 1);
+        assert_eq!(apply_token.get_definition(), Definition::Addition);
+        assert_eq!(apply_token.get_left(), Some(0));
+        assert_eq!(apply_token.get_right(), Some(2));
+        assert_eq!(apply_token.get_parent(), None);
+
+        let identifier_token = phrased_tokens.get_node(0).unwrap();
+        assert_eq!(identifier_token.get_definition(), Definition::Identifier);
+        assert_eq!(identifier_token.get_left(), None);
+        assert_eq!(identifier_token.get_right(), None);
+        assert_eq!(identifier_token.get_parent(), Some(1));
+        assert_eq!(identifier_token.get_lex_token().get_text(), "perform");
+
+        let identifier_token = phrased_tokens.get_node(2).unwrap();
+        assert_eq!(identifier_token.get_definition(), Definition::Identifier);
+        assert_eq!(identifier_token.get_left(), None);
+        assert_eq!(identifier_token.get_right(), None);
+        assert_eq!(identifier_token.get_parent(), Some(1));
+        assert_eq!(identifier_token.get_lex_token().get_text(), "task");
+    }
+
+    #[test]
+    fn phrase_operator_arg() {
+        let input = "perform 5 + 10 task";
+
+        let tokens = lex(input).unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+
+        let phrased_tokens = reduce_phrases(&parsed, &context).unwrap();
+
+        let apply_token = phrased_tokens.get_node(5).unwrap();
+
+        assert_eq!(apply_token.get_definition(), Definition::ApplyTo);
+        assert_eq!(apply_token.get_left(), Some(3));
+        assert_eq!(apply_token.get_right(), Some(6));
+        assert_eq!(apply_token.get_parent(), None);
+
+        let identifier_token = phrased_tokens.get_node(6).unwrap();
+        assert_eq!(identifier_token.get_definition(), Definition::Identifier);
+        assert_eq!(identifier_token.get_left(), None);
+        assert_eq!(identifier_token.get_right(), None);
+        assert_eq!(identifier_token.get_parent(), Some(5));
+        assert_eq!(identifier_token.get_lex_token().get_text(), "perform_task");
+
+        let identifier_token = phrased_tokens.get_node(3).unwrap();
+        assert_eq!(identifier_token.get_definition(), Definition::Addition);
+        assert_eq!(identifier_token.get_left(), Some(2));
+        assert_eq!(identifier_token.get_right(), Some(4));
+        assert_eq!(identifier_token.get_parent(), Some(5));
+        assert_eq!(identifier_token.get_lex_token().get_text(), "+");
+
+        let identifier_token = phrased_tokens.get_node(2).unwrap();
+        assert_eq!(identifier_token.get_definition(), Definition::Number);
+        assert_eq!(identifier_token.get_left(), None);
+        assert_eq!(identifier_token.get_right(), None);
+        assert_eq!(identifier_token.get_parent(), Some(3));
+        assert_eq!(identifier_token.get_lex_token().get_text(), "5");
+
+        let identifier_token = phrased_tokens.get_node(4).unwrap();
+        assert_eq!(identifier_token.get_definition(), Definition::Number);
+        assert_eq!(identifier_token.get_left(), None);
+        assert_eq!(identifier_token.get_right(), None);
+        assert_eq!(identifier_token.get_parent(), Some(3));
+        assert_eq!(identifier_token.get_lex_token().get_text(), "10");
+    }
+
+    #[test]
+    fn profile_not_recorded_by_default() {
+        let input = "perform 5 task";
+
+        let tokens = lex(input).unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+
+        let (_, report) = reduce_phrases_with_options(&parsed, &context, &ReduceOptions::default()).unwrap();
+
+        assert_eq!(report.profile, None);
+    }
+
+    #[test]
+    fn profile_counts_lookups_and_mutations() {
+        let input = "perform 5 task";
+
+        let tokens = lex(input).unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+
+        let options = ReduceOptions { profile: true, ..ReduceOptions::default() };
+        let (phrased_tokens, report) = reduce_phrases_with_options(&parsed, &context, &options).unwrap();
+
+        let profile = report.profile.unwrap();
+
+        // perform, perform_task and its meta
+        assert_eq!(profile.context_lookups, 3);
+        assert_eq!(profile.mutations, 1);
+        assert_eq!(phrased_tokens, reduce_phrases(&parsed, &context).unwrap());
+    }
+
+    #[test]
+    fn match_includes_doc() {
+        let input = "perform 5 task";
+
+        let tokens = lex(input).unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("perform_task", PhraseMeta::new().with_doc("Performs a task.")).unwrap();
+
+        let (_, report) = reduce_phrases_with_options(&parsed, &context, &ReduceOptions::default()).unwrap();
+
+        assert_eq!(report.matches.len(), 1);
+        assert_eq!(report.matches[0].phrase, "perform_task");
+        assert_eq!(report.matches[0].node, 3);
+        assert_eq!(report.matches[0].doc(), Some("Performs a task."));
+    }
+
+    #[test]
+    fn deprecated_phrase_reduces_with_warning() {
+        let input = "do 5 task";
+
+        let tokens = lex(input).unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("do_task", PhraseMeta::new().with_deprecation(Some("perform_task"))).unwrap();
+
+        let (phrased_tokens, report) = reduce_phrases_with_options(&parsed, &context, &ReduceOptions::default()).unwrap();
+
+        assert_eq!(phrased_tokens.get_node(4).unwrap().get_lex_token().get_text(), "do_task");
+        assert_eq!(report.warnings, vec![ReduceWarning::Deprecated {
+            phrase: "do_task".to_string(),
+            replacement: Some("perform_task".to_string()),
+            span: Span { start: Position { line: 0, column: 0 }, end: Position { line: 0, column: 9 } },
+            source: None,
+        }]);
+        assert_eq!(report.warnings[0].to_string(), "phrase 'do_task' at 1:1 is deprecated, use 'perform_task' instead");
+    }
+
+    #[test]
+    fn deprecate_registered_phrase() {
+        let parsed = parse(&lex("do 5 task").unwrap()).unwrap();
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("do_task", PhraseMeta::new().with_doc("Does a task.")).unwrap();
+
+        assert!(context.deprecate("do_task", None));
+        assert!(!context.deprecate("do_job", None));
+        assert_eq!(context.doc("do_task"), Some("Does a task."));
+
+        let (_, report) = reduce_phrases_with_options(&parsed, &context, &ReduceOptions::default()).unwrap();
+        assert_eq!(report.warnings[0].to_string(), "phrase 'do_task' at 1:1 is deprecated");
+    }
+
+    #[test]
+    fn internal_phrase_not_matched_by_default() {
+        let input = "perform 5 task";
+
+        let tokens = lex(input).unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("perform_task", PhraseMeta::new().with_visibility(Visibility::Internal)).unwrap();
+
+        let (phrased_tokens, report) = reduce_phrases_with_options(&parsed, &context, &ReduceOptions::default()).unwrap();
+
+        assert_eq!(phrased_tokens, parsed);
+        assert!(report.matches.is_empty());
+    }
+
+    #[test]
+    fn internal_phrase_matched_when_included() {
+        let input = "perform 5 task";
+
+        let tokens = lex(input).unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("perform_task", PhraseMeta::new().with_visibility(Visibility::Internal)).unwrap();
+
+        let options = ReduceOptions { include_internal: true, ..ReduceOptions::default() };
+        let (phrased_tokens, _) = reduce_phrases_with_options(&parsed, &context, &options).unwrap();
+
+        assert_eq!(phrased_tokens.get_node(4).unwrap().get_lex_token().get_text(), "perform_task");
+    }
+
+    #[test]
+    fn internal_phrase_falls_back_to_public_word() {
+        let input = "perform task";
+
+        let tokens = lex(input).unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("perform_task", PhraseMeta::new().with_visibility(Visibility::Internal)).unwrap();
+        context.add_phrase("task").unwrap();
+
+        let (_, report) = reduce_phrases_with_options(&parsed, &context, &ReduceOptions::default()).unwrap();
+
+        assert_eq!(report.matches.len(), 1);
+        assert_eq!(report.matches[0].phrase, "task");
+    }
+
+    #[test]
+    fn match_reports_source() {
+        let input = "perform task";
+
+        let tokens = lex(input).unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("perform_task", PhraseMeta::new().with_source(PhraseSource::Library("tasks".to_string()))).unwrap();
+
+        let (_, report) = reduce_phrases_with_options(&parsed, &context, &ReduceOptions::default()).unwrap();
+
+        assert_eq!(report.matches[0].source(), Some(&PhraseSource::Library("tasks".to_string())));
+    }
+
+    fn reduce_with_form(input: &str, form: ApplyForm) -> garnish_lang_compiler::parse::ParseResult {
+        let tokens = lex(input).unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("perform_task", PhraseMeta::new().with_apply(form)).unwrap();
+
+        reduce_phrases(&parsed, &context).unwrap()
+    }
+
+    #[test]
+    fn apply_form_apply() {
+        let reduced = reduce_with_form("perform 5 task", ApplyForm::Apply);
+
+        let apply_token = reduced.get_node(3).unwrap();
+        assert_eq!(apply_token.get_definition(), Definition::Apply);
+        assert_eq!(apply_token.get_left(), Some(4));
+        assert_eq!(apply_token.get_right(), Some(2));
+        assert_eq!(reduced.get_node(4).unwrap().get_lex_token().get_text(), "perform_task");
+    }
+
+    #[test]
+    fn apply_form_access() {
+        let reduced = reduce_with_form("perform 5 10 task", ApplyForm::Access);
+
+        let access_token = reduced.get_node(5).unwrap();
+        assert_eq!(access_token.get_definition(), Definition::Access);
+        assert_eq!(access_token.get_left(), Some(3));
+        assert_eq!(access_token.get_right(), Some(6));
+        assert_eq!(reduced.get_node(6).unwrap().get_definition(), Definition::Property);
+    }
+
+    #[test]
+    fn apply_form_empty_apply() {
+        let reduced = reduce_with_form("perform 5 task", ApplyForm::EmptyApply);
+
+        let apply_token = reduced.get_node(3).unwrap();
+        assert_eq!(apply_token.get_definition(), Definition::ApplyTo);
+        assert_eq!(apply_token.get_left(), Some(2));
+        assert_eq!(apply_token.get_right(), Some(5));
+
+        let empty_apply_token = reduced.get_node(5).unwrap();
+        assert_eq!(empty_apply_token.get_definition(), Definition::EmptyApply);
+        assert_eq!(empty_apply_token.get_parent(), Some(3));
+        assert_eq!(empty_apply_token.get_left(), Some(4));
+        assert_eq!(reduced.get_node(4).unwrap().get_parent(), Some(5));
+    }
+
+    #[test]
+    fn apply_form_ignored_without_arguments() {
+        let reduced = reduce_with_form("perform task", ApplyForm::Apply);
+
+        assert_eq!(reduced.get_node(3).unwrap().get_definition(), Definition::EmptyApply);
+    }
+
+    fn reduce_with_target(input: &str, meta: PhraseMeta) -> garnish_lang_compiler::parse::ParseResult {
+        let tokens = lex(input).unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("perform_task", meta).unwrap();
+
+        reduce_phrases(&parsed, &context).unwrap()
+    }
+
+    #[test]
+    fn flat_target_replaces_text() {
+        let reduced = reduce_with_target("perform 5 task", PhraseMeta::new().with_target("run"));
+
+        assert_eq!(reduced.get_node(4).unwrap().get_lex_token().get_text(), "run");
+        assert_eq!(reduced.get_node(4).unwrap().get_definition(), Definition::Identifier);
+    }
+
+    #[test]
+    fn namespaced_target_emits_access_chain() {
+        let reduced = reduce_with_target("perform 5 task", PhraseMeta::new().with_target("jobs.tasks.perform"));
+
+        let apply_token = reduced.get_node(3).unwrap();
+        assert_eq!(apply_token.get_definition(), Definition::ApplyTo);
+        assert_eq!(apply_token.get_right(), Some(4));
+
+        let outer = reduced.get_node(4).unwrap();
+        assert_eq!(outer.get_definition(), Definition::Access);
+        assert_eq!(outer.get_parent(), Some(3));
+        assert_eq!(outer.get_left(), Some(7));
+        assert_eq!(outer.get_right(), Some(8));
+        assert_eq!(reduced.get_node(8).unwrap().get_lex_token().get_text(), "perform");
+        assert_eq!(reduced.get_node(8).unwrap().get_definition(), Definition::Property);
+
+        let inner = reduced.get_node(7).unwrap();
+        assert_eq!(inner.get_definition(), Definition::Access);
+        assert_eq!(inner.get_parent(), Some(4));
+        assert_eq!(inner.get_left(), Some(5));
+        assert_eq!(inner.get_right(), Some(6));
+        assert_eq!(reduced.get_node(5).unwrap().get_lex_token().get_text(), "jobs");
+        assert_eq!(reduced.get_node(5).unwrap().get_definition(), Definition::Identifier);
+        assert_eq!(reduced.get_node(6).unwrap().get_lex_token().get_text(), "tasks");
+    }
+
+    #[test]
+    fn double_colon_namespace_same_as_period() {
+        let periods = reduce_with_target("perform 5 task", PhraseMeta::new().with_target("jobs.tasks.perform_task"));
+        let colons = reduce_with_target("perform 5 task", PhraseMeta::new().with_target("jobs::tasks::perform_task"));
+
+        assert_eq!(colons, periods);
+        assert_eq!(colons.get_node(8).unwrap().get_lex_token().get_text(), "perform_task");
+    }
+
+    #[test]
+    fn namespaced_target_for_single_word() {
+        let tokens = lex("stop").unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("stop", PhraseMeta::new().with_target("jobs.stop")).unwrap();
+
+        let reduced = reduce_phrases(&parsed, &context).unwrap();
+
+        assert_eq!(reduced.get_node(1).unwrap().get_definition(), Definition::EmptyApply);
+        assert_eq!(reduced.get_node(0).unwrap().get_definition(), Definition::Access);
+        assert_eq!(reduced.get_node(0).unwrap().get_left(), Some(2));
+        assert_eq!(reduced.get_node(0).unwrap().get_right(), Some(3));
+    }
+
+    #[test]
+    fn single_word_in_list_linked_from_list() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("stop").unwrap();
+        context.add_phrase_with_meta("halt", PhraseMeta::new().with_target("jobs.halt")).unwrap();
+
+        for input in ["5 stop", "5 halt"] {
+            let reduced = reduce_phrases(&parse(&lex(input).unwrap()).unwrap(), &context).unwrap();
+
+            let list = reduced.get_node(reduced.get_root()).unwrap();
+            let apply = reduced.get_node(list.get_right().unwrap()).unwrap();
+            assert_eq!(apply.get_definition(), Definition::EmptyApply, "{}", input);
+            assert_eq!(apply.get_parent(), Some(reduced.get_root()), "{}", input);
+            assert!(crate::ParseResultExt::check_structure(&reduced).is_ok(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn access_form_uses_last_target_segment() {
+        let meta = PhraseMeta::new().with_apply(ApplyForm::Access).with_target("tasks.perform");
+        let reduced = reduce_with_target("perform 5 task", meta);
+
+        assert_eq!(reduced.get_node(4).unwrap().get_definition(), Definition::Property);
+        assert_eq!(reduced.get_node(4).unwrap().get_lex_token().get_text(), "perform");
+    }
+
+    fn reduce_chained(input: &str) -> (garnish_lang_compiler::parse::ParseResult, crate::ReduceReport) {
+        let tokens = lex(input).unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("load_file").unwrap();
+        context.add_phrase("parse_content").unwrap();
+        context.add_phrase("print_result").unwrap();
+
+        let options = ReduceOptions { chain_word: Some("then".to_string()), ..ReduceOptions::default() };
+
+        reduce_phrases_with_options(&parsed, &context, &options).unwrap()
+    }
+
+    fn text(result: &garnish_lang_compiler::parse::ParseResult, index: Option<usize>) -> String {
+        result.get_node(index.unwrap()).unwrap().get_lex_token().get_text().clone()
+    }
+
+    #[test]
+    fn chain_without_arguments() {
+        let (reduced, report) = reduce_chained("load file then parse content then print result");
+
+        // (load_file~~ ~> parse_content) ~> print_result
+        let outer = reduced.get_node(reduced.get_root()).unwrap();
+        assert_eq!(outer.get_definition(), Definition::ApplyTo);
+        assert_eq!(outer.get_parent(), None);
+        assert_eq!(text(&reduced, outer.get_right()), "print_result");
+
+        let inner = reduced.get_node(outer.get_left().unwrap()).unwrap();
+        assert_eq!(inner.get_definition(), Definition::ApplyTo);
+        assert_eq!(inner.get_parent(), Some(reduced.get_root()));
+        assert_eq!(text(&reduced, inner.get_right()), "parse_content");
+
+        let first = reduced.get_node(inner.get_left().unwrap()).unwrap();
+        assert_eq!(first.get_definition(), Definition::EmptyApply);
+        assert_eq!(text(&reduced, first.get_left()), "load_file");
+
+        assert_eq!(report.matches.iter().map(|m| m.phrase.as_str()).collect::<Vec<&str>>(), vec![
+            "load_file",
+            "parse_content",
+            "print_result"
+        ]);
+    }
+
+    #[test]
+    fn chain_result_is_first_argument() {
+        let (reduced, _) = reduce_chained("load file then parse 5 content");
+
+        // (load_file~~ 5) ~> parse_content
+        let apply = reduced.get_node(reduced.get_root()).unwrap();
+        assert_eq!(apply.get_definition(), Definition::ApplyTo);
+        assert_eq!(text(&reduced, apply.get_right()), "parse_content");
+
+        let arguments = reduced.get_node(apply.get_left().unwrap()).unwrap();
+        assert_eq!(arguments.get_definition(), Definition::List);
+        assert_eq!(arguments.get_parent(), Some(reduced.get_root()));
+        assert_eq!(reduced.get_node(arguments.get_left().unwrap()).unwrap().get_definition(), Definition::EmptyApply);
+        assert_eq!(text(&reduced, arguments.get_right()), "5");
+    }
+
+    #[test]
+    fn chain_into_non_phrase() {
+        let (reduced, _) = reduce_chained("load file then handler");
+
+        let apply = reduced.get_node(reduced.get_root()).unwrap();
+        assert_eq!(apply.get_definition(), Definition::ApplyTo);
+        assert_eq!(text(&reduced, apply.get_right()), "handler");
+        assert_eq!(text(&reduced, apply.get_left()), "file");
+    }
+
+    #[test]
+    fn chain_word_at_end_not_chained() {
+        let (reduced, _) = reduce_chained("load file then");
+
+        assert!(reduced.get_nodes().iter().all(|node| node.get_definition() != Definition::ApplyTo));
+    }
+
+    #[derive(Default)]
+    struct RecordingMiddleware {
+        before: Vec<Mutation>,
+        after: Vec<(Option<usize>, usize)>,
+    }
+
+    impl MutationMiddleware for RecordingMiddleware {
+        fn before(&mut self, mutation: &Mutation, _result: &garnish_lang_compiler::parse::ParseResult) {
+            self.before.push(mutation.clone());
+        }
+
+        fn after(&mut self, _mutation: &Mutation, node: Option<usize>, result: &garnish_lang_compiler::parse::ParseResult) {
+            self.after.push((node, result.get_nodes().len()));
+        }
+    }
+
+    #[test]
+    fn middleware_sees_each_mutation() {
+        let tokens = lex("perform 5 task").unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+
+        let mut middleware = RecordingMiddleware::default();
+        reduce_phrases_with_middleware(&parsed, &context, &ReduceOptions::default(), &mut middleware).unwrap();
+
+        assert_eq!(middleware.before, vec![Mutation::Phrase {
+            phrase: "perform_task".to_string(),
+            words: vec![0, 4],
+            arguments: vec![2],
+            span: Span { start: Position { line: 0, column: 0 }, end: Position { line: 0, column: 14 } },
+        }]);
+        assert_eq!(middleware.after, vec![(Some(3), 5)]);
+    }
+
+    #[test]
+    fn middleware_sees_pipes() {
+        let tokens = lex("stop then stop").unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("stop").unwrap();
+
+        let options = ReduceOptions { chain_word: Some("then".to_string()), ..ReduceOptions::default() };
+        let mut middleware = RecordingMiddleware::default();
+        reduce_phrases_with_middleware(&parsed, &context, &options, &mut middleware).unwrap();
+
+        assert_eq!(middleware.before.len(), 3);
+        assert_eq!(middleware.before[2], Mutation::Pipe { previous: 1, next: 3 });
+    }
+
+    fn reduce_divide(input: &str, meta: PhraseMeta, options: &ReduceOptions) -> Result<garnish_lang_compiler::parse::ParseResult, crate::PhraseError> {
+        let tokens = lex(input).unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("divide_by_evenly", meta).unwrap();
+
+        reduce_phrases_with_options(&parsed, &context, options).map(|(result, _)| result)
+    }
+
+    #[test]
+    fn arguments_as_written() {
+        let reduced = reduce_divide("divide 10 by 2 evenly", PhraseMeta::new(), &ReduceOptions::default()).unwrap();
+
+        let list = reduced.get_node(reduced.get_node(reduced.get_root()).unwrap().get_left().unwrap()).unwrap();
+        assert_eq!(text(&reduced, list.get_left()), "10");
+        assert_eq!(text(&reduced, list.get_right()), "2");
+    }
+
+    #[test]
+    fn arguments_reversed_by_phrase() {
+        let meta = PhraseMeta::new().with_argument_order(ArgumentOrder::Reversed);
+        let reduced = reduce_divide("divide 10 by 2 evenly", meta, &ReduceOptions::default()).unwrap();
+
+        let list = reduced.get_node(reduced.get_node(reduced.get_root()).unwrap().get_left().unwrap()).unwrap();
+        assert_eq!(text(&reduced, list.get_left()), "2");
+        assert_eq!(text(&reduced, list.get_right()), "10");
+        assert_eq!(reduced.get_node(list.get_left().unwrap()).unwrap().get_parent(), reduced.get_node(list.get_right().unwrap()).unwrap().get_parent());
+    }
+
+    #[test]
+    fn phrase_order_overrides_default() {
+        let options = ReduceOptions { argument_order: ArgumentOrder::Reversed, ..ReduceOptions::default() };
+        let meta = PhraseMeta::new().with_argument_order(ArgumentOrder::AsWritten);
+        let reduced = reduce_divide("divide 10 by 2 evenly", meta, &options).unwrap();
+
+        let list = reduced.get_node(reduced.get_node(reduced.get_root()).unwrap().get_left().unwrap()).unwrap();
+        assert_eq!(text(&reduced, list.get_left()), "10");
+    }
+
+    #[test]
+    fn bad_permutation_errors() {
+        let meta = PhraseMeta::new().with_argument_order(ArgumentOrder::Permutation(vec![0]));
+        let result = reduce_divide("divide 10 by 2 evenly", meta, &ReduceOptions::default());
+
+        assert!(matches!(result, Err(crate::PhraseError::ArgumentOrder { arguments: 2, .. })));
+    }
+
+    fn reduce_partial(input: &str, shape: PartialShape) -> Result<garnish_lang_compiler::parse::ParseResult, crate::PhraseError> {
+        let tokens = lex(input).unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("scale_by", PhraseMeta::new().with_arity(2)).unwrap();
+        context.add_phrase_with_meta("double", PhraseMeta::new().with_arity(1)).unwrap();
+
+        let options = ReduceOptions { partial_shape: shape, ..ReduceOptions::default() };
+        reduce_phrases_with_options(&parsed, &context, &options).map(|(result, _)| result)
+    }
+
+    #[test]
+    fn under_applied_errors_by_default() {
+        let result = reduce_partial("scale 5 by", PartialShape::Error);
+
+        assert!(matches!(result, Err(crate::PhraseError::MissingArguments { expected: 2, found: 1, .. })));
+    }
+
+    #[test]
+    fn fully_applied_with_arity() {
+        let reduced = reduce_partial("scale 5 10 by", PartialShape::Error).unwrap();
+
+        assert_eq!(reduced.get_node(reduced.get_root()).unwrap().get_definition(), Definition::ApplyTo);
+    }
+
+    #[test]
+    fn over_applied_checked_by_option() {
+        let parsed = parse(&lex("scale 5 10 by").unwrap()).unwrap();
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("scale_by", PhraseMeta::new().with_arity(1)).unwrap();
+
+        let options = ReduceOptions { extra_arguments: crate::ExtraArguments::Allow, ..ReduceOptions::default() };
+        let (_, report) = reduce_phrases_with_options(&parsed, &context, &options).unwrap();
+        assert!(report.warnings.is_empty());
+
+        let options = ReduceOptions { extra_arguments: crate::ExtraArguments::Deny, ..ReduceOptions::default() };
+        let result = reduce_phrases_with_options(&parsed, &context, &options);
+        assert!(matches!(result, Err(crate::PhraseError::ExtraArguments { expected: 1, found: 2, .. })));
+
+        let options = ReduceOptions { extra_arguments: crate::ExtraArguments::Deny, collect_errors: true, ..ReduceOptions::default() };
+        let (reduced, report) = reduce_phrases_with_options(&parsed, &context, &options).unwrap();
+        assert!(matches!(report.errors[..], [ReduceError::ExtraArguments { expected: 1, found: 2, .. }]));
+        assert_eq!(reduced, parsed);
+    }
+
+    #[test]
+    fn over_applied_warns_by_default() {
+        let parsed = parse(&lex("perform 5 10 task").unwrap()).unwrap();
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("perform_task", PhraseMeta::new().with_arity(1)).unwrap();
+
+        let (_, report) = reduce_phrases_with_options(&parsed, &context, &ReduceOptions::default()).unwrap();
+
+        assert_eq!(report.warnings.len(), 1);
+        assert_eq!(report.warnings[0].to_string(), "phrase 'perform_task' at 1:1 takes at most 1 argument(s) but was given 2");
+    }
+
+    #[test]
+    fn arity_range_allows_optional_arguments() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("scale_by", PhraseMeta::new().with_arity_range(1, 2)).unwrap();
+        let options = ReduceOptions { extra_arguments: crate::ExtraArguments::Deny, ..ReduceOptions::default() };
+
+        for (input, allowed) in [("scale 5 by", true), ("scale 5 10 by", true), ("scale 5 10 20 by", false)] {
+            let parsed = parse(&lex(input).unwrap()).unwrap();
+            assert_eq!(reduce_phrases_with_options(&parsed, &context, &options).is_ok(), allowed, "{}", input);
+        }
+    }
+
+    #[test]
+    fn under_applied_as_expression() {
+        let reduced = reduce_partial("scale 5 by", PartialShape::Expression).unwrap();
+
+        // { 5 $ ~> scale_by }
+        let expression = reduced.get_node(reduced.get_root()).unwrap();
+        assert_eq!(expression.get_definition(), Definition::NestedExpression);
+        assert_eq!(expression.get_parent(), None);
+
+        let apply = reduced.get_node(expression.get_right().unwrap()).unwrap();
+        assert_eq!(apply.get_definition(), Definition::ApplyTo);
+        assert_eq!(apply.get_parent(), Some(reduced.get_root()));
+        assert_eq!(text(&reduced, apply.get_right()), "scale_by");
+
+        let arguments = reduced.get_node(apply.get_left().unwrap()).unwrap();
+        assert_eq!(arguments.get_definition(), Definition::List);
+        assert_eq!(text(&reduced, arguments.get_left()), "5");
+        assert_eq!(reduced.get_node(arguments.get_right().unwrap()).unwrap().get_definition(), Definition::Value);
+    }
+
+    #[test]
+    fn single_word_under_applied_as_expression() {
+        let reduced = reduce_partial("double", PartialShape::Expression).unwrap();
+
+        // { $ ~> double }
+        let expression = reduced.get_node(reduced.get_root()).unwrap();
+        assert_eq!(expression.get_definition(), Definition::NestedExpression);
+
+        let apply = reduced.get_node(expression.get_right().unwrap()).unwrap();
+        assert_eq!(apply.get_definition(), Definition::ApplyTo);
+        assert_eq!(reduced.get_node(apply.get_left().unwrap()).unwrap().get_definition(), Definition::Value);
+        assert_eq!(text(&reduced, apply.get_right()), "double");
+    }
+
+    #[cfg(feature = "compiler-0-0-24")]
+    #[test]
+    fn under_applied_as_partial_apply() {
+        let reduced = reduce_partial("scale 5 by", PartialShape::PartialApply).unwrap();
+
+        let apply = reduced.get_node(reduced.get_root()).unwrap();
+        assert_eq!(apply.get_definition(), Definition::PartialApply);
+        assert_eq!(text(&reduced, apply.get_left()), "scale_by");
+        assert_eq!(text(&reduced, apply.get_right()), "5");
+    }
+
+    #[cfg(feature = "compiler-0-0-24")]
+    #[test]
+    fn single_word_under_applied_as_partial_apply() {
+        let reduced = reduce_partial("double", PartialShape::PartialApply).unwrap();
+
+        let phrase = reduced.get_node(reduced.get_root()).unwrap();
+        assert_eq!(phrase.get_definition(), Definition::Identifier);
+        assert_eq!(phrase.get_parent(), None);
+    }
+
+    #[cfg(not(feature = "compiler-0-0-24"))]
+    #[test]
+    fn partial_apply_needs_newer_compiler() {
+        let error = reduce_partial("scale 5 by", PartialShape::PartialApply).unwrap_err();
+
+        assert!(matches!(error, PhraseError::UnsupportedPartialApply));
+    }
+
+    #[test]
+    fn preserve_words_keeps_final_token() {
+        let tokens = lex("perform 5 task").unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("perform_task", PhraseMeta::new().with_target("tasks.perform")).unwrap();
+
+        let options = ReduceOptions { preserve_words: true, ..ReduceOptions::default() };
+        let (reduced, report) = reduce_phrases_with_options(&parsed, &context, &options).unwrap();
+
+        let apply = reduced.get_node(3).unwrap();
+        assert_eq!(apply.get_definition(), Definition::ApplyTo);
+        assert_eq!(text(&reduced, apply.get_right()), "task");
+        assert_eq!(reduced.get_node(4).unwrap().get_definition(), Definition::Identifier);
+        assert_eq!(text(&reduced, Some(0)), "perform");
+
+        assert_eq!(report.matches[0].words, vec![0, 4]);
+        assert_eq!(report.linked_phrase(0).map(|m| m.phrase.as_str()), Some("perform_task"));
+        assert_eq!(report.linked_phrase(4).map(|m| m.node), Some(3));
+        assert_eq!(report.linked_phrase(2), None);
+    }
+
+    #[test]
+    fn words_recorded_without_preserving() {
+        let tokens = lex("perform 5 task").unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+
+        let (reduced, report) = reduce_phrases_with_options(&parsed, &context, &ReduceOptions::default()).unwrap();
+
+        assert_eq!(text(&reduced, Some(4)), "perform_task");
+        assert_eq!(report.matches[0].words, vec![0, 4]);
+    }
+
+    #[test]
+    fn in_order_traversal_reduces() {
+        let tokens = lex("perform 5 10 task").unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+
+        let options = ReduceOptions { traversal: Traversal::InOrder, ..ReduceOptions::default() };
+        let (in_order, _) = reduce_phrases_with_options(&parsed, &context, &options).unwrap();
+
+        assert_eq!(in_order, reduce_phrases(&parsed, &context).unwrap());
+    }
+
+    struct WordsOnly;
+
+    impl TraversalStrategy for WordsOnly {
+        fn items(&self, parse_result: &garnish_lang_compiler::parse::ParseResult) -> Result<Vec<TraversalItem>, crate::PhraseError> {
+            Ok(parse_result.get_nodes().iter().enumerate()
+                .filter(|(_, node)| node.get_definition() == Definition::Identifier)
+                .map(|(index, _)| (Some(index), false))
+                .collect())
+        }
+    }
+
+    #[test]
+    fn custom_traversal_controls_argument_capture() {
+        let tokens = lex("perform 5 task").unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+
+        let options = ReduceOptions { traversal: Traversal::Custom(std::sync::Arc::new(WordsOnly)), ..ReduceOptions::default() };
+        let (reduced, _) = reduce_phrases_with_options(&parsed, &context, &options).unwrap();
+
+        // number never offered as an argument so phrase is reduced without one
+        assert_eq!(reduced.get_node(reduced.get_root()).unwrap().get_definition(), Definition::EmptyApply);
+    }
+
+    #[test]
+    fn reduce_subtree_only() {
+        let tokens = lex("(perform 5 task), perform 6 task").unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+
+        // group's list is reduced, right side of the comma list is left alone
+        let group = parsed.get_nodes().iter().position(|node| node.get_definition() == Definition::Group).unwrap();
+        let list = parsed.get_node(group).unwrap().get_right().unwrap();
+
+        let reduced = reduce_phrases_at(&parsed, list, &context).unwrap();
+
+        assert_eq!(reduced.get_root(), parsed.get_root());
+
+        let new_list = reduced.get_node(group).unwrap().get_right().unwrap();
+        let apply = reduced.get_node(new_list).unwrap();
+        assert_eq!(apply.get_definition(), Definition::ApplyTo);
+        assert_eq!(apply.get_parent(), Some(group));
+        assert_eq!(text(&reduced, apply.get_right()), "perform_task");
+
+        let untouched = parsed.get_node(parsed.get_root()).unwrap().get_right();
+        assert_eq!(reduced.get_node(parsed.get_root()).unwrap().get_right(), untouched);
+        assert_eq!(reduced.get_node(untouched.unwrap()), parsed.get_node(untouched.unwrap()));
+    }
+
+    #[test]
+    fn reduce_subtree_again_keeps_size() {
+        let tokens = lex("(perform 5 task), perform 6 task").unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+
+        let group = parsed.get_nodes().iter().position(|node| node.get_definition() == Definition::Group).unwrap();
+        let list = parsed.get_node(group).unwrap().get_right().unwrap();
+
+        let (once, report) = crate::reduce_phrases_at_with_options(&parsed, list, &context, &ReduceOptions::default()).unwrap();
+        assert!(once.get_nodes().len() <= parsed.get_nodes().len());
+        assert_eq!(once.get_node(report.matches[0].node).unwrap().get_definition(), Definition::ApplyTo);
+        assert_eq!(text(&once, once.get_node(report.matches[0].node).unwrap().get_right()), "perform_task");
+        assert!(crate::ParseResultExt::check_structure(&once).is_ok());
+
+        let mut current = once.clone();
+        for _ in 0..3 {
+            let subtree = current.get_node(group).unwrap().get_right().unwrap();
+            current = reduce_phrases_at(&current, subtree, &context).unwrap();
+        }
+        assert_eq!(current.get_nodes().len(), once.get_nodes().len());
+        assert!(crate::ParseResultExt::check_structure(&current).is_ok());
+    }
+
+    #[test]
+    fn reduce_at_root_same_as_whole() {
+        let tokens = lex("perform 5 task").unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+
+        let reduced = reduce_phrases_at(&parsed, parsed.get_root(), &context).unwrap();
+        let root = reduced.get_node(reduced.get_root()).unwrap();
+
+        assert_eq!(root.get_definition(), Definition::ApplyTo);
+        assert_eq!(root.get_parent(), None);
+        assert_eq!(text(&reduced, root.get_right()), "perform_task");
+    }
+
+    #[test]
+    fn reduce_at_missing_node() {
+        let tokens = lex("perform 5 task").unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let result = reduce_phrases_at(&parsed, 100, &SimplePhraseContext::new());
+
+        assert!(matches!(result, Err(crate::PhraseError::MissingNode(100))));
+    }
+
+    #[test]
+    fn reduce_with_trait_object() {
+        let tokens = lex("perform 5 task").unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+        let provider: Box<dyn crate::context::PhraseContext> = Box::new(context.clone());
+
+        assert_eq!(reduce_phrases(&parsed, provider.as_ref()).unwrap(), reduce_phrases(&parsed, &context).unwrap());
+        assert_eq!(reduce_phrases(&parsed, &provider).unwrap(), reduce_phrases(&parsed, &context).unwrap());
+    }
+
+    #[test]
+    fn collect_missing_arguments() {
+        let tokens = lex("scale 5 by").unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("scale_by", PhraseMeta::new().with_arity(2)).unwrap();
+
+        let options = ReduceOptions { collect_errors: true, ..ReduceOptions::default() };
+        let (reduced, report) = reduce_phrases_with_options(&parsed, &context, &options).unwrap();
+
+        assert_eq!(reduced, parsed);
+        assert!(report.matches.is_empty());
+        assert_eq!(report.errors, vec![ReduceError::MissingArguments {
+            phrase: "scale_by".to_string(),
+            expected: 2,
+            found: 1,
+            span: Span { start: Position { line: 0, column: 0 }, end: Position { line: 0, column: 10 } },
+        }]);
+    }
+
+    #[test]
+    fn collect_bad_argument_order() {
+        let tokens = lex("divide 10 by 2 evenly").unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("divide_by_evenly", PhraseMeta::new().with_argument_order(ArgumentOrder::Permutation(vec![0]))).unwrap();
+
+        let options = ReduceOptions { collect_errors: true, ..ReduceOptions::default() };
+        let (reduced, report) = reduce_phrases_with_options(&parsed, &context, &options).unwrap();
+
+        assert_eq!(reduced, parsed);
+        assert!(matches!(report.errors.as_slice(), [ReduceError::ArgumentOrder { arguments: 2, .. }]));
+        assert_eq!(report.errors[0].to_string(), "argument order for phrase 'divide_by_evenly' at 1:1 doesn't fit its 2 argument(s)");
+    }
+
+    fn reduce_unterminated(input: &str, phrases: &[&str]) -> (garnish_lang_compiler::parse::ParseResult, crate::ReduceReport) {
+        let tokens = lex(input).unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrases(phrases).unwrap();
+
+        let options = ReduceOptions { complete_unterminated: true, ..ReduceOptions::default() };
+        reduce_phrases_with_options(&parsed, &context, &options).unwrap()
+    }
+
+    #[test]
+    fn complete_unterminated_with_argument() {
+        let (reduced, report) = reduce_unterminated("perform 5", &["perform_task"]);
+
+        let root = reduced.get_node(reduced.get_root()).unwrap();
+        assert_eq!(root.get_definition(), Definition::ApplyTo);
+        assert_eq!(root.get_parent(), None);
+        assert_eq!(text(&reduced, root.get_left()), "5");
+        assert_eq!(text(&reduced, root.get_right()), "perform_task");
+        assert_eq!(reduced.get_node(root.get_left().unwrap()).unwrap().get_parent(), Some(reduced.get_root()));
+
+        assert_eq!(report.matches.len(), 1);
+        assert!(report.matches[0].recovered);
+        assert_eq!(report.matches[0].span.end, Position { line: 0, column: 14 });
+    }
+
+    #[test]
+    fn complete_unterminated_without_arguments() {
+        let (reduced, report) = reduce_unterminated("perform", &["perform_the_task"]);
+
+        let root = reduced.get_node(reduced.get_root()).unwrap();
+        assert_eq!(root.get_definition(), Definition::EmptyApply);
+        assert_eq!(text(&reduced, root.get_left()), "perform_the_task");
+        assert!(report.matches[0].recovered);
+    }
+
+    #[test]
+    fn ambiguous_unterminated_left_as_is() {
+        let (reduced, report) = reduce_unterminated("perform 5", &["perform_task", "perform_the_task"]);
+
+        assert_eq!(reduced, parse(&lex("perform 5").unwrap()).unwrap());
+        assert!(report.matches.is_empty());
+    }
+
+    #[test]
+    fn unterminated_not_completed_by_default() {
+        let tokens = lex("perform 5").unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+
+        assert_eq!(reduce_phrases(&parsed, &context).unwrap(), parsed);
+    }
+
+    #[test]
+    fn extra_phrases_for_one_reduction() {
+        let tokens = lex("perform 5 task").unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let context = SimplePhraseContext::new();
+        let options = ReduceOptions { extra_phrases: SimplePhraseContext::try_from(&["perform_task"][..]).unwrap(), ..ReduceOptions::default() };
+
+        let (reduced, report) = reduce_phrases_with_options(&parsed, &context, &options).unwrap();
+
+        assert_eq!(text(&reduced, reduced.get_node(reduced.get_root()).unwrap().get_right()), "perform_task");
+        assert_eq!(report.matches.len(), 1);
+        assert_eq!(context.phrase_count(), 0);
+    }
+
+    #[test]
+    fn extra_phrase_extends_context_phrase() {
+        let tokens = lex("perform 5 task").unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("perform", PhraseMeta::new().with_doc("performs")).unwrap();
+        let mut extra = SimplePhraseContext::new();
+        extra.add_phrase_with_meta("perform_task", PhraseMeta::new().with_doc("performs a task")).unwrap();
+        let options = ReduceOptions { extra_phrases: extra, ..ReduceOptions::default() };
+
+        let (_, report) = reduce_phrases_with_options(&parsed, &context, &options).unwrap();
+
+        assert_eq!(report.matches[0].phrase, "perform_task");
+        assert_eq!(report.matches[0].doc(), Some("performs a task"));
+    }
+
+    fn reduce_longest(input: &str) -> (garnish_lang_compiler::parse::ParseResult, crate::ReduceReport) {
+        let tokens = lex(input).unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.set_overlap_policy(crate::context::OverlapPolicy::AllowCompletePrefix);
+        context.add_phrases(["perform_task", "perform_task_quickly", "halt", "halt_now"]).unwrap();
+
+        reduce_phrases_with_options(&parsed, &context, &ReduceOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn longest_phrase_matched() {
+        let (reduced, report) = reduce_longest("perform 5 task quickly");
+
+        let root = reduced.get_node(reduced.get_root()).unwrap();
+        assert_eq!(root.get_definition(), Definition::ApplyTo);
+        assert_eq!(text(&reduced, root.get_left()), "5");
+        assert_eq!(text(&reduced, root.get_right()), "perform_task_quickly");
+        assert_eq!(report.matches.len(), 1);
+    }
+
+    #[test]
+    fn shorter_phrase_matched_at_end_of_input() {
+        let (reduced, report) = reduce_longest("perform 5 task");
+
+        let root = reduced.get_node(reduced.get_root()).unwrap();
+        assert_eq!(root.get_definition(), Definition::ApplyTo);
+        assert_eq!(text(&reduced, root.get_left()), "5");
+        assert_eq!(text(&reduced, root.get_right()), "perform_task");
+        assert_eq!(report.matches[0].words.len(), 2);
+    }
+
+    #[test]
+    fn shorter_phrase_matched_when_longer_not_written() {
+        let (reduced, report) = reduce_longest("perform 5 task 10");
+
+        // (5 ~> perform_task) 10
+        let root = reduced.get_node(reduced.get_root()).unwrap();
+        assert_eq!(root.get_definition(), Definition::List);
+        assert_eq!(text(&reduced, root.get_right()), "10");
+
+        let apply = reduced.get_node(root.get_left().unwrap()).unwrap();
+        assert_eq!(apply.get_definition(), Definition::ApplyTo);
+        assert_eq!(apply.get_parent(), Some(reduced.get_root()));
+        assert_eq!(text(&reduced, apply.get_left()), "5");
+        assert_eq!(text(&reduced, apply.get_right()), "perform_task");
+        assert_eq!(report.matches.len(), 1);
+    }
+
+    #[test]
+    fn single_word_phrase_that_is_also_a_prefix() {
+        let (reduced, _) = reduce_longest("halt");
+        let root = reduced.get_node(reduced.get_root()).unwrap();
+        assert_eq!(root.get_definition(), Definition::EmptyApply);
+        assert_eq!(text(&reduced, root.get_left()), "halt");
+
+        let (reduced, _) = reduce_longest("halt now");
+        let root = reduced.get_node(reduced.get_root()).unwrap();
+        assert_eq!(root.get_definition(), Definition::EmptyApply);
+        assert_eq!(text(&reduced, root.get_left()), "halt_now");
+    }
+
+    #[test]
+    fn disambiguation_picks_shorter_phrase() {
+        let tokens = lex("perform 5 task quickly").unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.set_overlap_policy(crate::context::OverlapPolicy::AllowCompletePrefix);
+        context.add_phrases(["perform_task", "perform_task_quickly"]).unwrap();
+
+        let asked = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        let recorded = asked.clone();
+        let disambiguation = Disambiguation::Custom(std::sync::Arc::new(move |candidates: &[MatchCandidate]| {
+            recorded.lock().unwrap().extend(candidates.iter().map(|candidate| (candidate.phrase.clone(), candidate.span)));
+            candidates.iter().position(|candidate| candidate.phrase == "perform_task")
+        }));
+        let options = ReduceOptions { disambiguation, ..ReduceOptions::default() };
+
+        let (reduced, report) = reduce_phrases_with_options(&parsed, &context, &options).unwrap();
+
+        assert_eq!(asked.lock().unwrap().iter().map(|(phrase, _)| phrase.as_str()).collect::<Vec<&str>>(), vec!["perform_task_quickly", "perform_task"]);
+        assert_eq!(asked.lock().unwrap()[1].1.end, Position { line: 0, column: 14 });
+
+        // (5 ~> perform_task) quickly
+        let root = reduced.get_node(reduced.get_root()).unwrap();
+        assert_eq!(text(&reduced, root.get_right()), "quickly");
+        assert_eq!(text(&reduced, reduced.get_node(root.get_left().unwrap()).unwrap().get_right()), "perform_task");
+        assert_eq!(report.matches.len(), 1);
+    }
+
+    #[test]
+    fn disambiguation_picks_completion() {
+        let tokens = lex("perform 5").unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrases(["perform_task", "perform_the_task"]).unwrap();
+
+        let disambiguation = Disambiguation::Custom(std::sync::Arc::new(|candidates: &[MatchCandidate]| {
+            candidates.iter().position(|candidate| candidate.phrase == "perform_the_task")
+        }));
+        let options = ReduceOptions { complete_unterminated: true, disambiguation, ..ReduceOptions::default() };
+
+        let (reduced, report) = reduce_phrases_with_options(&parsed, &context, &options).unwrap();
+
+        assert_eq!(text(&reduced, reduced.get_node(reduced.get_root()).unwrap().get_right()), "perform_the_task");
+        assert!(report.matches[0].recovered);
+    }
+
+    fn reduce_hygienic(input: &str, hygiene: Hygiene) -> Result<(garnish_lang_compiler::parse::ParseResult, crate::ReduceReport), PhraseError> {
+        let tokens = lex(input).unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrases(["perform_task", "stop"]).unwrap();
+        context.add_phrase_with_meta("halt_now", PhraseMeta::new().with_target("halt")).unwrap();
+
+        reduce_phrases_with_options(&parsed, &context, &ReduceOptions { hygiene, ..ReduceOptions::default() })
+    }
+
+    #[test]
+    fn hygiene_prefix_and_suffix() {
+        let (reduced, _) = reduce_hygienic("perform 5 task", Hygiene::new().with_prefix("__").with_suffix("_p")).unwrap();
+
+        assert_eq!(reduced.get_node(4).unwrap().get_lex_token().get_text(), "__perform_task_p");
+    }
+
+    #[test]
+    fn hygiene_leaves_targets() {
+        let (reduced, _) = reduce_hygienic("halt now", Hygiene::new().with_prefix("__")).unwrap();
+
+        assert_eq!(reduced.get_node(2).unwrap().get_lex_token().get_text(), "halt");
+    }
+
+    #[test]
+    fn hygiene_namespace_for_single_word() {
+        let (reduced, _) = reduce_hygienic("stop", Hygiene::new().with_namespace("phrases")).unwrap();
+
+        let access = reduced.get_node(0).unwrap();
+        assert_eq!(access.get_definition(), Definition::Access);
+        assert_eq!(text(&reduced, access.get_left()), "phrases");
+        assert_eq!(text(&reduced, access.get_right()), "stop");
+    }
+
+    #[test]
+    fn hygiene_collision_warns() {
+        let (_, report) = reduce_hygienic("perform 5 task", Hygiene::new().with_symbols(["perform_task"])).unwrap();
+
+        assert_eq!(report.warnings, vec![ReduceWarning::NameCollision {
+            phrase: "perform_task".to_string(),
+            name: "perform_task".to_string(),
+            span: Span { start: Position { line: 0, column: 0 }, end: Position { line: 0, column: 14 } },
+        }]);
+        assert_eq!(report.warnings[0].to_string(), "phrase 'perform_task' at 1:1 generates 'perform_task' which is already used");
+    }
+
+    #[test]
+    fn hygiene_prefix_avoids_collision() {
+        let hygiene = Hygiene::new().with_prefix("p_").with_symbols(["perform_task"]);
+        let (_, report) = reduce_hygienic("perform 5 task", hygiene).unwrap();
+
+        assert!(report.warnings.is_empty());
+    }
+
+    #[test]
+    fn hygiene_collision_denied() {
+        let hygiene = Hygiene::new().with_symbols(["stop"]).with_collision_severity(CollisionSeverity::Deny);
+        let error = reduce_hygienic("stop", hygiene).unwrap_err();
+
+        assert!(matches!(error, PhraseError::NameCollision { phrase, name } if phrase == "stop" && name == "stop"));
+    }
+
+    fn reduce_normalized(input: &str, phrases: &[&str], normalization: Normalization) -> (garnish_lang_compiler::parse::ParseResult, crate::ReduceReport) {
+        let tokens = lex(input).unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrases(phrases.iter().copied()).unwrap();
+
+        reduce_phrases_with_options(&parsed, &context, &ReduceOptions { normalization, ..ReduceOptions::default() }).unwrap()
+    }
+
+    #[test]
+    fn plural_matches_singular_phrase() {
+        let (reduced, report) = reduce_normalized("perform 5 tasks", &["perform_task"], Normalization::EnglishPlural);
+
+        assert_eq!(reduced.get_node(4).unwrap().get_lex_token().get_text(), "perform_task");
+        assert_eq!(report.matches[0].phrase, "perform_task");
+    }
+
+    #[test]
+    fn plural_not_matched_without_normalization() {
+        let (_, report) = reduce_normalized("perform 5 tasks", &["perform_task"], Normalization::None);
+
+        assert!(report.matches.is_empty());
+    }
+
+    #[test]
+    fn written_word_preferred_over_normalized() {
+        let (_, report) = reduce_normalized("perform 5 tasks now", &["perform_task", "perform_tasks_now"], Normalization::EnglishPlural);
+
+        assert_eq!(report.matches.iter().map(|m| m.phrase.as_str()).collect::<Vec<&str>>(), vec!["perform_tasks_now"]);
+
+        let (_, report) = reduce_normalized("boxes", &["box", "boxes"], Normalization::EnglishPlural);
+
+        assert_eq!(report.matches[0].phrase, "boxes");
+    }
+
+    #[test]
+    fn custom_normalization() {
+        let normalization = Normalization::Custom(std::sync::Arc::new(|word: &str| word.to_lowercase()));
+        let (_, report) = reduce_normalized("perform 5 Task", &["perform_task"], normalization);
+
+        assert_eq!(report.matches[0].phrase, "perform_task");
+    }
+
+    fn reduce_split(input: &str) -> (garnish_lang_compiler::parse::ParseResult, crate::ReduceReport) {
+        let tokens = lex(input).unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrases(["perform_task_now", "load_file"]).unwrap();
+
+        let options = ReduceOptions { split_identifiers: true, normalization: Normalization::EnglishPlural, ..ReduceOptions::default() };
+        reduce_phrases_with_options(&parsed, &context, &options).unwrap()
+    }
+
+    #[test]
+    fn camel_case_identifier_matches_phrase() {
+        let (reduced, report) = reduce_split("loadFile");
+
+        assert_eq!(reduced.get_node(0).unwrap().get_lex_token().get_text(), "load_file");
+        assert_eq!(reduced.get_node(1).unwrap().get_definition(), Definition::EmptyApply);
+        assert_eq!(report.matches[0].phrase, "load_file");
+    }
+
+    #[test]
+    fn split_identifier_finishes_phrase() {
+        let (_, report) = reduce_split("perform 5 taskNow");
+
+        assert_eq!(report.matches[0].phrase, "perform_task_now");
+        assert_eq!(report.matches[0].words, vec![0, 4]);
+    }
+
+    #[test]
+    fn split_identifier_normalized() {
+        let (_, report) = reduce_split("LoadFiles");
+
+        assert_eq!(report.matches[0].phrase, "load_file");
+    }
 
-                            match result.get_node_mut(arg_index) {
-                                None => Err(format!("Node at {:?} not found", arg_index))?,
-                                Some(left) => {
-                                    left.set_parent(next_parent);
-                                    break;
-                                }
-                            }
-                        }
+    #[test]
+    fn active_version_selects_meta() {
+        let tokens = lex("perform 5 task").unwrap();
+        let parsed = parse(&tokens).unwrap();
 
-                        left
-                    }
-                };
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("perform_task", PhraseMeta::new().with_target("run")).unwrap();
+        context.add_phrase_version("perform_task", "v1", PhraseMeta::new().with_target("run_task")).unwrap();
+        context.add_phrase_version("perform_task", "v2", PhraseMeta::new().with_target("start").with_arity(1)).unwrap();
 
-                next_parent = left;
-            }
+        let target = |versions: &[&str]| {
+            let options = ReduceOptions {
+                versions: versions.iter().map(|version| version.to_string()).collect(),
+                ..ReduceOptions::default()
+            };
+            let (reduced, report) = reduce_phrases_with_options(&parsed, &context, &options).unwrap();
+            (reduced.get_node(4).unwrap().get_lex_token().get_text().clone(), report.matches[0].meta.as_ref().and_then(|meta| meta.arity))
+        };
 
-            node.get_parent()
+        assert_eq!(target(&[]), ("run".to_string(), None));
+        assert_eq!(target(&["v3", "v1"]), ("run_task".to_string(), None));
+        assert_eq!(target(&["v2", "v1"]), ("start".to_string(), Some(1)));
+    }
+
+    fn matched_with_positions(input: &str, positions: &[SyntacticPosition]) -> Vec<String> {
+        let tokens = lex(input).unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let meta = positions.iter().fold(PhraseMeta::new(), |meta, position| meta.with_position(*position));
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("perform_task", meta.clone()).unwrap();
+        context.add_phrase_with_meta("stop", meta).unwrap();
+
+        let (_, report) = reduce_phrases_with_options(&parsed, &context, &ReduceOptions::default()).unwrap();
+        report.matches.into_iter().map(|m| m.phrase).collect()
+    }
+
+    #[test]
+    fn phrase_restricted_to_expression_root() {
+        assert_eq!(matched_with_positions("perform 5 task", &[SyntacticPosition::ExpressionRoot]), vec!["perform_task"]);
+        assert_eq!(matched_with_positions("stop", &[SyntacticPosition::ExpressionRoot]), vec!["stop"]);
+        assert!(matched_with_positions("(perform 5 task)", &[SyntacticPosition::ExpressionRoot, SyntacticPosition::ListElement]).is_empty());
+    }
+
+    #[test]
+    fn phrase_restricted_to_nested() {
+        assert!(matched_with_positions("perform 5 task", &[SyntacticPosition::Nested]).is_empty());
+        assert!(matched_with_positions("stop", &[SyntacticPosition::Nested]).is_empty());
+        assert_eq!(matched_with_positions("(perform 5 task)", &[SyntacticPosition::Nested]), vec!["perform_task"]);
+    }
+
+    #[test]
+    fn restricted_phrase_left_as_written() {
+        let tokens = lex("(perform 5 task)").unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("perform_task", PhraseMeta::new().with_position(SyntacticPosition::ExpressionRoot)).unwrap();
+
+        assert_eq!(reduce_phrases(&parsed, &context).unwrap(), parsed);
+    }
+
+    fn reduce_weighted(input: &str, weights: &[(&str, i64)], complete_unterminated: bool) -> crate::ReduceReport {
+        let tokens = lex(input).unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.set_overlap_policy(crate::context::OverlapPolicy::AllowCompletePrefix);
+        for (phrase, weight) in weights {
+            context.add_phrase_with_meta(phrase, PhraseMeta::new().with_weight(*weight)).unwrap();
         }
-    };
 
-    phrases.pop();
+        let options = ReduceOptions { disambiguation: Disambiguation::Weighted, complete_unterminated, ..ReduceOptions::default() };
+        reduce_phrases_with_options(&parsed, &context, &options).unwrap().1
+    }
 
-    Ok(arg)
-}
+    #[test]
+    fn heavier_shorter_phrase_preferred() {
+        let report = reduce_weighted("perform 5 task quickly", &[("perform_task", 5), ("perform_task_quickly", 1)], false);
 
-#[cfg(test)]
-mod tests {
-    use garnish_lang_compiler::lex::lex;
-    use garnish_lang_compiler::parse::{Definition, parse};
-    use crate::reduce_phrases;
-    use crate::context::SimplePhraseContext;
+        assert_eq!(report.matches.iter().map(|m| m.phrase.as_str()).collect::<Vec<&str>>(), vec!["perform_task"]);
+        assert!(report.warnings.is_empty());
+
+        let report = reduce_weighted("perform 5 task quickly", &[("perform_task", 1), ("perform_task_quickly", 5)], false);
+
+        assert_eq!(report.matches.iter().map(|m| m.phrase.as_str()).collect::<Vec<&str>>(), vec!["perform_task_quickly"]);
+    }
 
     #[test]
-    fn simple_phrase() {
-        let input = "perform task";
+    fn higher_priority_wins_overlap() {
+        let parsed = parse(&lex("perform 5 task quickly").unwrap()).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task_quickly").unwrap();
+        context.add_phrase_with_meta("perform_task", PhraseMeta::new().with_priority(2)).unwrap();
+
+        let (_, report) = reduce_phrases_with_options(&parsed, &context, &ReduceOptions::default()).unwrap();
+        assert_eq!(report.matches[0].phrase, "perform_task");
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("perform_task_quickly", PhraseMeta::new().with_priority(3)).unwrap();
+        context.add_phrase_with_meta("perform_task", PhraseMeta::new().with_priority(2)).unwrap();
+
+        let (_, report) = reduce_phrases_with_options(&parsed, &context, &ReduceOptions::default()).unwrap();
+        assert_eq!(report.matches[0].phrase, "perform_task_quickly");
+    }
+
+    #[test]
+    fn completion_chosen_by_priority() {
+        let parsed = parse(&lex("perform 5").unwrap()).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+        context.add_phrase_with_meta("perform_the_task", PhraseMeta::new().with_priority(1)).unwrap();
+
+        let options = ReduceOptions { complete_unterminated: true, ..ReduceOptions::default() };
+        let (_, report) = reduce_phrases_with_options(&parsed, &context, &options).unwrap();
+        assert_eq!(report.matches[0].phrase, "perform_the_task");
+    }
+
+    #[test]
+    fn equal_weights_reported() {
+        let report = reduce_weighted("perform 5 task quickly", &[("perform_task", 2), ("perform_task_quickly", 2)], false);
+
+        assert_eq!(report.matches[0].phrase, "perform_task_quickly");
+        assert_eq!(report.warnings, vec![ReduceWarning::TiedWeights {
+            phrases: vec!["perform_task".to_string(), "perform_task_quickly".to_string()],
+            span: Span { start: Position { line: 0, column: 0 }, end: Position { line: 0, column: 22 } },
+        }]);
+        assert_eq!(report.warnings[0].to_string(), "phrases 'perform_task', 'perform_task_quickly' at 1:1 have the same weight");
+    }
+
+    #[test]
+    fn completion_chosen_by_weight() {
+        let report = reduce_weighted("perform 5", &[("perform_task", 1), ("perform_the_task", 3)], true);
+
+        assert_eq!(report.matches[0].phrase, "perform_the_task");
+
+        let report = reduce_weighted("perform 5", &[("perform_task", 3), ("perform_the_task", 3)], true);
+
+        assert!(report.matches.is_empty());
+        assert_eq!(report.warnings.len(), 2);
+        assert!(matches!(report.warnings[0], ReduceWarning::TiedWeights { .. }));
+        assert!(matches!(report.warnings[1], ReduceWarning::Unfinished { .. }));
+    }
+
+    fn reduce_with_typos(input: &str, typo_distance: usize) -> (garnish_lang_compiler::parse::ParseResult, crate::ReduceReport) {
+        let tokens = lex(input).unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrases(["perform_task", "perform_the_job"]).unwrap();
+
+        reduce_phrases_with_options(&parsed, &context, &ReduceOptions { typo_distance, ..ReduceOptions::default() }).unwrap()
+    }
+
+    #[test]
+    fn misspelled_word_corrected() {
+        let (reduced, report) = reduce_with_typos("perform 5 tsak", 2);
+
+        assert_eq!(text(&reduced, Some(4)), "perform_task");
+        assert_eq!(report.warnings, vec![ReduceWarning::Corrected {
+            written: "tsak".to_string(),
+            corrected: "task".to_string(),
+            span: Span { start: Position { line: 0, column: 10 }, end: Position { line: 0, column: 14 } },
+        }]);
+        assert_eq!(report.warnings[0].to_string(), "word 'tsak' at 1:11 was read as 'task'");
+    }
+
+    #[test]
+    fn corrections_within_distance_only() {
+        let (_, report) = reduce_with_typos("perform 5 tsak", 1);
+        assert!(report.matches.is_empty());
+
+        // left unfinished instead of corrected
+        let (_, report) = reduce_with_typos("perform 5 tsak", 0);
+        assert_eq!(report.warnings.len(), 1);
+        assert_eq!(report.warnings[0].to_string(), "phrase 'perform' at 1:1 is unfinished, did you mean 'perform task' or 'perform the job'?");
+    }
+
+    #[test]
+    fn middle_word_corrected() {
+        let (_, report) = reduce_with_typos("perform 5 thee jb", 1);
+
+        assert_eq!(report.matches[0].phrase, "perform_the_job");
+        assert_eq!(report.warnings.len(), 2);
+    }
+
+    #[test]
+    fn short_argument_not_corrected() {
+        // tk is two edits from task, as many as it has letters
+        let (_, report) = reduce_with_typos("perform tk task", 2);
+
+        assert_eq!(report.matches[0].phrase, "perform_task");
+        assert!(report.warnings.is_empty());
+    }
 
+    fn reduce_with_precedence(input: &str, precedence: crate::PrecedenceTable) -> (garnish_lang_compiler::parse::ParseResult, crate::ReduceReport) {
         let tokens = lex(input).unwrap();
         let parsed = parse(&tokens).unwrap();
 
         let mut context = SimplePhraseContext::new();
         context.add_phrase("perform_task").unwrap();
 
-        let phrased_tokens = reduce_phrases(&parsed, &context).unwrap();
+        reduce_phrases_with_options(&parsed, &context, &ReduceOptions { precedence, ..ReduceOptions::default() }).unwrap()
+    }
 
-        let apply_token = phrased_tokens.get_node(3).unwrap();
+    fn looser_multiplication() -> crate::PrecedenceTable {
+        crate::PrecedenceTable::new(5).with_level(Definition::MultiplicationSign, 3)
+    }
 
-        assert_eq!(phrased_tokens.get_root(), 3);
-        assert_eq!(apply_token.get_definition(), Definition::EmptyApply);
-        assert_eq!(apply_token.get_left(), Some(2));
-        assert_eq!(apply_token.get_right(), None);
-        assert_eq!(apply_token.get_parent(), None);
+    #[test]
+    fn tighter_operator_captured_whole() {
+        let (reduced, report) = reduce_with_precedence("perform 2 * 3 task", crate::PrecedenceTable::default());
 
-        let identifier_token = phrased_tokens.get_node(2).unwrap();
-        assert_eq!(identifier_token.get_definition(), Definition::Identifier);
-        assert_eq!(identifier_token.get_left(), None);
-        assert_eq!(identifier_token.get_right(), None);
-        assert_eq!(identifier_token.get_parent(), Some(3));
-        assert_eq!(identifier_token.get_lex_token().get_text(), "perform_task");
+        let apply = reduced.get_node(report.matches[0].node).unwrap();
+        let argument = reduced.get_node(apply.get_left().unwrap()).unwrap();
+        assert_eq!(argument.get_definition(), Definition::MultiplicationSign);
+
+        let (_, report) = reduce_with_precedence("perform 2 task * 3", crate::PrecedenceTable::default());
+        assert!(report.matches.is_empty());
     }
 
     #[test]
-    fn simple_phrase_with_argument() {
-        let input = "perform 5 task";
+    fn looser_operator_applied_to_phrase_result() {
+        let (reduced, report) = reduce_with_precedence("perform 2 task * 3", looser_multiplication());
+
+        let root = reduced.get_node(reduced.get_root()).unwrap();
+        assert_eq!(root.get_definition(), Definition::MultiplicationSign);
+        assert_eq!(root.get_left(), Some(report.matches[0].node));
+        assert_eq!(text(&reduced, root.get_right()), "3");
+    }
+
+    #[test]
+    fn looser_operator_argument_split() {
+        let (reduced, report) = reduce_with_precedence("perform 2 * 3 task", looser_multiplication());
+
+        let apply = reduced.get_node(report.matches[0].node).unwrap();
+        assert_eq!(apply.get_definition(), Definition::ApplyTo);
+        assert_eq!(text(&reduced, apply.get_left()), "2");
+
+        let root = reduced.get_node(reduced.get_root()).unwrap();
+        assert_eq!(root.get_definition(), Definition::MultiplicationSign);
+        assert_eq!(root.get_left(), Some(report.matches[0].node));
+    }
+
+    #[test]
+    fn looser_operator_before_phrase() {
+        let (reduced, report) = reduce_with_precedence("5 * perform 2 task", looser_multiplication());
+
+        let root = reduced.get_node(reduced.get_root()).unwrap();
+        assert_eq!(root.get_definition(), Definition::MultiplicationSign);
+        assert_eq!(text(&reduced, root.get_left()), "5");
+        assert_eq!(root.get_right(), Some(report.matches[0].node));
+    }
+
+    #[test]
+    fn lists_without_phrase_words_not_regrouped() {
+        let tokens = lex("value 2 * 3").unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let (reduced, _) = reduce_with_precedence("value 2 * 3", looser_multiplication());
+
+        assert_eq!(reduced.get_root(), parsed.get_root());
+        assert_eq!(reduced.get_node(reduced.get_root()).unwrap().get_definition(), Definition::List);
+    }
+
+    #[test]
+    fn applies_annotated_with_phrase() {
+        let tokens = lex("perform 5 task").unwrap();
+        let parsed = parse(&tokens).unwrap();
 
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+
+        let (plain, _) = reduce_phrases_with_options(&parsed, &context, &ReduceOptions::default()).unwrap();
+        let (reduced, report) = reduce_phrases_with_options(&parsed, &context, &ReduceOptions { annotate: true, ..ReduceOptions::default() }).unwrap();
+
+        assert_eq!(crate::phrase_annotations(&reduced), vec![crate::PhraseAnnotation {
+            node: report.matches[0].node,
+            phrase: "perform_task".to_string(),
+            span: report.matches[0].span,
+        }]);
+        assert!(crate::phrase_annotations(&plain).is_empty());
+
+        // annotation isn't reachable from the root, the tree is otherwise unchanged
+        assert_eq!(reduced.get_nodes().len(), plain.get_nodes().len() + 1);
+        assert_eq!(&reduced.get_nodes()[..plain.get_nodes().len()], plain.get_nodes());
+        assert_eq!(reduced.get_root(), plain.get_root());
+    }
+
+    fn reduce_named(input: &str, meta: PhraseMeta) -> (garnish_lang_compiler::parse::ParseResult, crate::ReduceReport) {
         let tokens = lex(input).unwrap();
         let parsed = parse(&tokens).unwrap();
 
         let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("copy_to", meta).unwrap();
+
+        reduce_phrases_with_options(&parsed, &context, &ReduceOptions { named_arguments: true, ..ReduceOptions::default() }).unwrap()
+    }
+
+    #[test]
+    fn arguments_paired_with_generated_names() {
+        let (reduced, report) = reduce_named("copy 5 10 to", PhraseMeta::new());
+
+        let apply = reduced.get_node(report.matches[0].node).unwrap();
+        let list = reduced.get_node(apply.get_left().unwrap()).unwrap();
+        assert_eq!(list.get_definition(), Definition::List);
+
+        for (pair, (name, value)) in [list.get_left(), list.get_right()].into_iter().zip([("arg1", "5"), ("arg2", "10")]) {
+            let pair = reduced.get_node(pair.unwrap()).unwrap();
+            assert_eq!(pair.get_definition(), Definition::Pair);
+            assert_eq!(text(&reduced, pair.get_left()), name);
+            assert_eq!(text(&reduced, pair.get_right()), value);
+        }
+    }
+
+    #[test]
+    fn arguments_paired_with_declared_names() {
+        let meta = PhraseMeta::new().with_argument_names(&["source"]).with_argument_order(ArgumentOrder::Reversed);
+        let (reduced, report) = reduce_named("copy 5 10 to", meta);
+
+        let apply = reduced.get_node(report.matches[0].node).unwrap();
+        let list = reduced.get_node(apply.get_left().unwrap()).unwrap();
+        let first = reduced.get_node(list.get_left().unwrap()).unwrap();
+        let second = reduced.get_node(list.get_right().unwrap()).unwrap();
+
+        // names follow the rewritten order
+        assert_eq!((text(&reduced, first.get_left()), text(&reduced, first.get_right())), ("source".to_string(), "10".to_string()));
+        assert_eq!((text(&reduced, second.get_left()), text(&reduced, second.get_right())), ("arg2".to_string(), "5".to_string()));
+    }
+
+    #[test]
+    fn arguments_keyed_by_preceding_word() {
+        let parsed = parse(&lex("copy 5 10 to 20 into").unwrap()).unwrap();
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("copy_to_into", PhraseMeta::new().with_argument_order(ArgumentOrder::Reversed)).unwrap();
+
+        let options = ReduceOptions { named_arguments: true, argument_keys: crate::ArgumentKeys::PrecedingWord, ..ReduceOptions::default() };
+        let (reduced, report) = reduce_phrases_with_options(&parsed, &context, &options).unwrap();
+
+        let apply = reduced.get_node(report.matches[0].node).unwrap();
+        let mut pairs = vec![];
+        let mut list = vec![apply.get_left().unwrap()];
+        while let Some(index) = list.pop() {
+            let node = reduced.get_node(index).unwrap();
+            match node.get_definition() {
+                Definition::List => list.extend([node.get_right().unwrap(), node.get_left().unwrap()]),
+                _ => pairs.push((text(&reduced, node.get_left()), text(&reduced, node.get_right()))),
+            }
+        }
+
+        let pairs = pairs.iter().map(|(key, value)| (key.as_str(), value.as_str())).collect::<Vec<(&str, &str)>>();
+        assert_eq!(pairs, vec![("to", "20"), ("copy2", "10"), ("copy", "5")]);
+    }
+
+    fn reduce_trailing(input: &str) -> (garnish_lang_compiler::parse::ParseResult, crate::ReduceReport) {
+        let parsed = parse(&lex(input).unwrap()).unwrap();
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("add_to_list", PhraseMeta::new().with_trailing_arguments()).unwrap();
+        context.add_phrase_with_meta("print", PhraseMeta::new().with_trailing_arguments()).unwrap();
         context.add_phrase("perform_task").unwrap();
 
-        let phrased_tokens = reduce_phrases(&parsed, &context).unwrap();
+        reduce_phrases_with_options(&parsed, &context, &ReduceOptions::default()).unwrap()
+    }
 
-        let apply_token = phrased_tokens.get_node(3).unwrap();
+    // items of the argument list of the apply made for a match, in order
+    fn applied_arguments(reduced: &garnish_lang_compiler::parse::ParseResult, apply: usize) -> Vec<String> {
+        let apply = reduced.get_node(apply).unwrap();
+        assert_eq!(apply.get_definition(), Definition::ApplyTo);
 
-        assert_eq!(phrased_tokens.get_root(), 3);
-        assert_eq!(apply_token.get_definition(), Definition::ApplyTo);
-        assert_eq!(apply_token.get_left(), Some(2));
-        assert_eq!(apply_token.get_right(), Some(4));
-        assert_eq!(apply_token.get_parent(), None);
+        let mut items = vec![];
+        let mut current = apply.get_left();
+        while let Some(node) = current.and_then(|index| reduced.get_node(index)) {
+            match node.get_definition() {
+                Definition::List => {
+                    items.push(text(reduced, node.get_right()));
+                    current = node.get_left();
+                }
+                _ => {
+                    items.push(text(reduced, current));
+                    current = None;
+                }
+            }
+        }
 
-        let identifier_token = phrased_tokens.get_node(2).unwrap();
-        assert_eq!(identifier_token.get_definition(), Definition::Number);
-        assert_eq!(identifier_token.get_left(), None);
-        assert_eq!(identifier_token.get_right(), None);
-        assert_eq!(identifier_token.get_parent(), Some(3));
-        assert_eq!(identifier_token.get_lex_token().get_text(), "5");
+        items.reverse();
+        items
+    }
 
-        let identifier_token = phrased_tokens.get_node(4).unwrap();
-        assert_eq!(identifier_token.get_definition(), Definition::Identifier);
-        assert_eq!(identifier_token.get_left(), None);
-        assert_eq!(identifier_token.get_right(), None);
-        assert_eq!(identifier_token.get_parent(), Some(3));
-        assert_eq!(identifier_token.get_lex_token().get_text(), "perform_task");
+    #[test]
+    fn trailing_arguments_after_final_word() {
+        let (reduced, report) = reduce_trailing("add to list 5 10");
+
+        assert_eq!(report.matches[0].phrase, "add_to_list");
+        assert_eq!(report.matches[0].node, reduced.get_root());
+        assert_eq!(applied_arguments(&reduced, report.matches[0].node), vec!["5", "10"]);
+        assert_eq!(text(&reduced, reduced.get_node(reduced.get_root()).unwrap().get_right()), "add_to_list");
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("add_to_list", PhraseMeta::new().with_trailing_arguments()).unwrap();
+        assert_eq!(reduce_phrases(&reduced, &context).unwrap(), reduced);
+        assert!(reduce_phrases(&parse(&lex("perform add to list 5 task").unwrap()).unwrap(), &context).is_ok());
+    }
+
+    #[test]
+    fn trailing_arguments_follow_written_ones() {
+        let (reduced, report) = reduce_trailing("add 1 to 2 list 3");
+        assert_eq!(applied_arguments(&reduced, report.matches[0].node), vec!["1", "2", "3"]);
+
+        let (reduced, report) = reduce_trailing("print 5 (perform 6 task) 7");
+        let print = report.matches.iter().find(|m| m.phrase == "print").unwrap();
+        assert_eq!(applied_arguments(&reduced, print.node), vec!["5", "(", "7"]);
+        assert!(report.matches.iter().any(|m| m.phrase == "perform_task"));
+    }
+
+    #[test]
+    fn trailing_arguments_stay_in_their_list() {
+        let (reduced, report) = reduce_trailing("print 5 10, 20");
+
+        assert_eq!(applied_arguments(&reduced, report.matches[0].node), vec!["5", "10"]);
+        let root = reduced.get_node(reduced.get_root()).unwrap();
+        assert_eq!(root.get_definition(), Definition::CommaList);
+        assert_eq!(root.get_left(), Some(report.matches[0].node));
+
+        // nothing after it is left as a single word phrase
+        let (reduced, report) = reduce_trailing("print");
+        assert_eq!(reduced.get_node(report.matches[0].node).unwrap().get_definition(), Definition::EmptyApply);
+    }
+
+    fn reduce_template(input: &str, options: &ReduceOptions) -> Result<(garnish_lang_compiler::parse::ParseResult, crate::ReduceReport), crate::PhraseError> {
+        let parsed = parse(&lex(input).unwrap()).unwrap();
+        let mut context = SimplePhraseContext::new();
+        context.add_template("move _ to _", PhraseMeta::new()).unwrap();
+        context.add_template("turn _ on", PhraseMeta::new()).unwrap();
+
+        reduce_phrases_with_options(&parsed, &context, options)
+    }
+
+    #[test]
+    fn template_arguments_in_slots() {
+        let (reduced, report) = reduce_template("move 5 to 10", &ReduceOptions::default()).unwrap();
+        assert_eq!(applied_arguments(&reduced, report.matches[0].node), vec!["5", "10"]);
+
+        let (reduced, report) = reduce_template("turn lights on", &ReduceOptions::default()).unwrap();
+        assert_eq!(applied_arguments(&reduced, report.matches[0].node), vec!["lights"]);
+    }
+
+    #[test]
+    fn template_arguments_out_of_slots() {
+        let result = reduce_template("move 5 10 to", &ReduceOptions::default());
+        assert!(matches!(result, Err(crate::PhraseError::TemplateMismatch { ref template, ref found, .. }) if template == "move _ to _" && found == "move _ _ to"));
+
+        let options = ReduceOptions { collect_errors: true, ..ReduceOptions::default() };
+        let (_, report) = reduce_template("turn on lights", &options).unwrap();
+        assert!(report.matches.is_empty());
+        assert!(matches!(&report.errors[..], [ReduceError::TemplateMismatch { found, .. }] if found == "turn on"));
+    }
+
+    fn reduce_inferring(input: &str, phrases: &[&str]) -> (garnish_lang_compiler::parse::ParseResult, crate::ReduceReport) {
+        let tokens = lex(input).unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrases(phrases).unwrap();
+
+        let options = ReduceOptions { infer_final_word: true, ..ReduceOptions::default() };
+        reduce_phrases_with_options(&parsed, &context, &options).unwrap()
+    }
+
+    #[test]
+    fn final_word_inferred() {
+        let (reduced, report) = reduce_inferring("perform 5", &["perform_task"]);
+
+        assert_eq!(text(&reduced, reduced.get_node(reduced.get_root()).unwrap().get_right()), "perform_task");
+        assert_eq!(report.warnings, vec![ReduceWarning::InferredWord {
+            phrase: "perform_task".to_string(),
+            word: "task".to_string(),
+            span: Span { start: Position { line: 0, column: 0 }, end: Position { line: 0, column: 14 } },
+        }]);
+        assert_eq!(report.warnings[0].to_string(), "phrase 'perform_task' at 1:1 is missing its final word, 'task' was assumed");
+    }
+
+    #[test]
+    fn final_word_not_inferred_when_ambiguous() {
+        let (_, report) = reduce_inferring("perform 5", &["perform_task", "perform_job"]);
+        assert!(report.matches.is_empty());
+
+        // more than one word missing
+        let (_, report) = reduce_inferring("perform 5", &["perform_the_task"]);
+        assert!(report.matches.is_empty());
+        assert_eq!(report.warnings.len(), 1);
+        assert!(matches!(&report.warnings[0], ReduceWarning::Unfinished { phrase } if phrase.suggestions == vec!["perform_the_task"]));
+    }
+
+    #[test]
+    fn options_separator_joins_emitted_identifier() {
+        let parsed = parse(&lex("perform 5 task").unwrap()).unwrap();
+        let mut context = SimplePhraseContext::new();
+        context.set_separator("-");
+        context.add_phrase("perform-task").unwrap();
+
+        let (reduced, report) = reduce_phrases_with_options(&parsed, &context, &ReduceOptions::default()).unwrap();
+        assert_eq!(text(&reduced, reduced.get_node(reduced.get_root()).unwrap().get_right()), "perform-task");
+        assert_eq!(report.matches[0].phrase, "perform_task");
+
+        let options = ReduceOptions { separator: Some("::".to_string()), ..ReduceOptions::default() };
+        let (reduced, _) = reduce_phrases_with_options(&parsed, &context, &options).unwrap();
+        assert_eq!(text(&reduced, reduced.get_node(reduced.get_root()).unwrap().get_right()), "perform::task");
+    }
+
+    #[test]
+    fn alias_reduced_as_target_phrase() {
+        let parsed = parse(&lex("kick off 5 job").unwrap()).unwrap();
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("start_job", PhraseMeta::new().with_doc("Starts a job")).unwrap();
+        context.add_alias("kick_off_job", "start_job").unwrap();
+        context.add_alias("begin_job", "run_job").unwrap();
+
+        let (reduced, report) = reduce_phrases_with_options(&parsed, &context, &ReduceOptions::default()).unwrap();
+        assert_eq!(text(&reduced, reduced.get_node(reduced.get_root()).unwrap().get_right()), "start_job");
+        assert_eq!(report.matches[0].phrase, "start_job");
+
+        let parsed = parse(&lex("begin 5 job").unwrap()).unwrap();
+        let options = ReduceOptions { naming: crate::NamingPolicy::Camel, ..ReduceOptions::default() };
+        let (reduced, _) = reduce_phrases_with_options(&parsed, &context, &options).unwrap();
+        assert_eq!(text(&reduced, reduced.get_node(reduced.get_root()).unwrap().get_right()), "runJob");
     }
 
     #[test]
-    fn simple_phrase_with_two_arguments() {
-        let input = "perform 5 10 task";
+    fn camel_case_dictionary_emits_kebab_case() {
+        let parsed = parse(&lex("perform 5 the task").unwrap()).unwrap();
+        let mut context = SimplePhraseContext::new();
+        context.set_split_case(true);
+        context.add_phrase("performTheTask").unwrap();
 
-        let tokens = lex(input).unwrap();
-        let parsed = parse(&tokens).unwrap();
+        let options = ReduceOptions { separator: Some("-".to_string()), ..ReduceOptions::default() };
+        let (reduced, report) = reduce_phrases_with_options(&parsed, &context, &options).unwrap();
+
+        assert_eq!(text(&reduced, reduced.get_node(reduced.get_root()).unwrap().get_right()), "perform-the-task");
+        assert_eq!(report.matches[0].phrase, "perform_the_task");
+    }
 
+    #[test]
+    fn naming_policy_applied_before_hygiene() {
+        let parsed = parse(&lex("perform 5 task").unwrap()).unwrap();
         let mut context = SimplePhraseContext::new();
         context.add_phrase("perform_task").unwrap();
 
-        let phrased_tokens = reduce_phrases(&parsed, &context).unwrap();
+        let options = ReduceOptions { naming: crate::NamingPolicy::Camel, hygiene: Hygiene::new().with_prefix("p_"), ..ReduceOptions::default() };
+        let (reduced, report) = reduce_phrases_with_options(&parsed, &context, &options).unwrap();
 
-        let apply_token = phrased_tokens.get_node(5).unwrap();
+        assert_eq!(text(&reduced, reduced.get_node(reduced.get_root()).unwrap().get_right()), "p_performTask");
+        assert_eq!(report.matches[0].phrase, "perform_task");
+    }
 
-        assert_eq!(apply_token.get_definition(), Definition::ApplyTo);
-        assert_eq!(apply_token.get_left(), Some(3));
-        assert_eq!(apply_token.get_right(), Some(6));
-        assert_eq!(apply_token.get_parent(), None);
+    #[test]
+    fn matches_resolved_to_expressions() {
+        let parsed = parse(&lex("perform 5 task, halt now").unwrap()).unwrap();
+        let mut context = SimplePhraseContext::new();
+        context.add_phrases(["perform_task", "halt_now"]).unwrap();
+        let expressions = std::collections::HashMap::from([("perform_task".to_string(), 3)]);
 
-        let identifier_token = phrased_tokens.get_node(6).unwrap();
-        assert_eq!(identifier_token.get_definition(), Definition::Identifier);
-        assert_eq!(identifier_token.get_left(), None);
-        assert_eq!(identifier_token.get_right(), None);
-        assert_eq!(identifier_token.get_parent(), Some(5));
-        assert_eq!(identifier_token.get_lex_token().get_text(), "perform_task");
+        let (_, report) = crate::reduce_phrases_with_expressions(&parsed, &context, &ReduceOptions::default(), &expressions).unwrap();
 
-        let identifier_token = phrased_tokens.get_node(3).unwrap();
-        assert_eq!(identifier_token.get_definition(), Definition::List);
-        assert_eq!(identifier_token.get_left(), Some(2));
-        assert_eq!(identifier_token.get_right(), Some(4));
-        assert_eq!(identifier_token.get_parent(), Some(5));
+        let perform = report.matches.iter().find(|m| m.phrase == "perform_task").unwrap();
+        assert_eq!(perform.expression, Some(3));
+        assert_eq!(report.expression_indices().into_iter().collect::<Vec<(usize, usize)>>(), vec![(perform.node, 3)]);
+        assert!(matches!(&report.warnings[..], [ReduceWarning::MissingExpression { phrase, .. }] if phrase == "halt_now"));
+    }
 
-        let identifier_token = phrased_tokens.get_node(2).unwrap();
-        assert_eq!(identifier_token.get_definition(), Definition::Number);
-        assert_eq!(identifier_token.get_left(), None);
-        assert_eq!(identifier_token.get_right(), None);
-        assert_eq!(identifier_token.get_parent(), Some(3));
-        assert_eq!(identifier_token.get_lex_token().get_text(), "5");
+    #[test]
+    fn generated_tokens_follow_rules() {
+        let parsed = parse(&lex("perform 5 the task").unwrap()).unwrap();
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_the_task").unwrap();
 
-        let identifier_token = phrased_tokens.get_node(4).unwrap();
-        assert_eq!(identifier_token.get_definition(), Definition::Number);
-        assert_eq!(identifier_token.get_left(), None);
-        assert_eq!(identifier_token.get_right(), None);
-        assert_eq!(identifier_token.get_parent(), Some(3));
-        assert_eq!(identifier_token.get_lex_token().get_text(), "10");
+        let (reduced, _) = reduce_phrases_with_options(&parsed, &context, &ReduceOptions::default()).unwrap();
+        let token = reduced.get_node(reduced.get_node(reduced.get_root()).unwrap().get_right().unwrap()).unwrap().get_lex_token();
+        assert_eq!((token.get_token_type(), token.get_column()), (garnish_lang_compiler::lex::TokenType::Identifier, 14));
+
+        let tokens = crate::TokenRules::new()
+            .with_token_type(garnish_lang_compiler::lex::TokenType::Symbol)
+            .with_position(crate::TokenPosition::FirstWord);
+        let (reduced, _) = reduce_phrases_with_options(&parsed, &context, &ReduceOptions { tokens, ..ReduceOptions::default() }).unwrap();
+        let token = reduced.get_node(reduced.get_node(reduced.get_root()).unwrap().get_right().unwrap()).unwrap().get_lex_token();
+        assert_eq!((token.get_token_type(), token.get_line(), token.get_column()), (garnish_lang_compiler::lex::TokenType::Symbol, 0, 0));
     }
 
     #[test]
-    fn simple_phrase_with_three_arguments() {
-        let input = "perform 5 10 15 task";
+    fn duplicate_words_policy() {
+        let parsed = parse(&lex("stop stop").unwrap()).unwrap();
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("stop").unwrap();
+        let span = Span { start: Position { line: 0, column: 0 }, end: Position { line: 0, column: 9 } };
 
-        let tokens = lex(input).unwrap();
-        let parsed = parse(&tokens).unwrap();
+        let (reduced, report) = reduce_phrases_with_options(&parsed, &context, &ReduceOptions::default()).unwrap();
+        assert_eq!(report.matches.len(), 2);
+        assert!(report.warnings.is_empty());
+
+        let options = ReduceOptions { duplicate_words: crate::DuplicateWords::Warn, ..ReduceOptions::default() };
+        let (warned, report) = reduce_phrases_with_options(&parsed, &context, &options).unwrap();
+        assert_eq!(warned, reduced);
+        assert_eq!(report.warnings, vec![ReduceWarning::DuplicateWord { word: "stop".to_string(), span }]);
+        assert_eq!(report.warnings[0].to_string(), "word 'stop' at 1:1 is repeated");
+
+        let options = ReduceOptions { duplicate_words: crate::DuplicateWords::Deny, ..ReduceOptions::default() };
+        let error = reduce_phrases_with_options(&parsed, &context, &options).unwrap_err();
+        assert!(matches!(error, PhraseError::DuplicateWord { word, span: found } if word == "stop" && found == span));
+    }
 
+    #[test]
+    fn repeated_first_word_starts_match_at_repeat() {
+        let parsed = parse(&lex("perform perform 5 task").unwrap()).unwrap();
         let mut context = SimplePhraseContext::new();
         context.add_phrase("perform_task").unwrap();
 
-        let phrased_tokens = reduce_phrases(&parsed, &context).unwrap();
-
-        let apply_token = phrased_tokens.get_node(7).unwrap();
+        let (_, report) = reduce_phrases_with_options(&parsed, &context, &ReduceOptions::default()).unwrap();
 
-        assert_eq!(apply_token.get_definition(), Definition::ApplyTo);
-        assert_eq!(apply_token.get_left(), Some(5));
-        assert_eq!(apply_token.get_right(), Some(8));
-        assert_eq!(apply_token.get_parent(), None);
+        assert_eq!(report.matches.len(), 1);
+        assert_eq!(report.matches[0].span.start, Position { line: 0, column: 8 });
+    }
 
-        let identifier_token = phrased_tokens.get_node(8).unwrap();
-        assert_eq!(identifier_token.get_definition(), Definition::Identifier);
-        assert_eq!(identifier_token.get_left(), None);
-        assert_eq!(identifier_token.get_right(), None);
-        assert_eq!(identifier_token.get_parent(), Some(7));
-        assert_eq!(identifier_token.get_lex_token().get_text(), "perform_task");
+    #[test]
+    fn free_order_words_reduce_to_phrase() {
+        let parsed = parse(&lex("sort ascending list").unwrap()).unwrap();
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_with_meta("sort_list_ascending", PhraseMeta::new().with_free_order().with_doc("sorts")).unwrap();
 
-        let identifier_token = phrased_tokens.get_node(5).unwrap();
-        assert_eq!(identifier_token.get_definition(), Definition::List);
-        assert_eq!(identifier_token.get_left(), Some(3));
-        assert_eq!(identifier_token.get_right(), Some(6));
-        assert_eq!(identifier_token.get_parent(), Some(7));
+        let (reduced, report) = reduce_phrases_with_options(&parsed, &context, &ReduceOptions::default()).unwrap();
 
-        let identifier_token = phrased_tokens.get_node(6).unwrap();
-        assert_eq!(identifier_token.get_definition(), Definition::Number);
-        assert_eq!(identifier_token.get_left(), None);
-        assert_eq!(identifier_token.get_right(), None);
-        assert_eq!(identifier_token.get_parent(), Some(5));
-        assert_eq!(identifier_token.get_lex_token().get_text(), "15");
+        assert_eq!(report.matches[0].phrase, "sort_list_ascending");
+        assert_eq!(report.matches[0].doc(), Some("sorts"));
+        assert_eq!(text(&reduced, reduced.get_node(reduced.get_root()).unwrap().get_left()), "sort_list_ascending");
+    }
 
-        let identifier_token = phrased_tokens.get_node(3).unwrap();
-        assert_eq!(identifier_token.get_definition(), Definition::List);
-        assert_eq!(identifier_token.get_left(), Some(2));
-        assert_eq!(identifier_token.get_right(), Some(4));
-        assert_eq!(identifier_token.get_parent(), Some(5));
+    #[test]
+    fn guarded_single_words_left_as_written() {
+        let parsed = parse(&lex("stop count id").unwrap()).unwrap();
+        let mut context = SimplePhraseContext::new();
+        context.add_phrases(["stop", "count", "id"]).unwrap();
 
-        let identifier_token = phrased_tokens.get_node(2).unwrap();
-        assert_eq!(identifier_token.get_definition(), Definition::Number);
-        assert_eq!(identifier_token.get_left(), None);
-        assert_eq!(identifier_token.get_right(), None);
-        assert_eq!(identifier_token.get_parent(), Some(3));
-        assert_eq!(identifier_token.get_lex_token().get_text(), "5");
+        let guard = crate::SingleWordGuard::new().with_words(["count"]).with_min_length(3);
+        let options = ReduceOptions { single_word_guard: guard, ..ReduceOptions::default() };
+        let (_, report) = reduce_phrases_with_options(&parsed, &context, &options).unwrap();
 
-        let identifier_token = phrased_tokens.get_node(4).unwrap();
-        assert_eq!(identifier_token.get_definition(), Definition::Number);
-        assert_eq!(identifier_token.get_left(), None);
-        assert_eq!(identifier_token.get_right(), None);
-        assert_eq!(identifier_token.get_parent(), Some(3));
-        assert_eq!(identifier_token.get_lex_token().get_text(), "10");
+        assert_eq!(report.matches.iter().map(|m| m.phrase.as_str()).collect::<Vec<&str>>(), vec!["stop"]);
     }
 
     #[test]
-    fn three_word_two_arg_phrase() {
-        let input = "perform 5 special 10 task";
-
-        let tokens = lex(input).unwrap();
+    fn phrases_in_no_reduce_region_left_as_written() {
+        let tokens = lex("perform (@no_phrases halt now @phrases) task").unwrap();
         let parsed = parse(&tokens).unwrap();
-
         let mut context = SimplePhraseContext::new();
-        context.add_phrase("perform_special_task").unwrap();
+        context.add_phrases(["perform_task", "halt_now"]).unwrap();
 
-        let phrased_tokens = reduce_phrases(&parsed, &context).unwrap();
+        let options = ReduceOptions { skip_regions: crate::no_reduce_regions(&tokens), ..ReduceOptions::default() };
+        let (_, report) = reduce_phrases_with_options(&parsed, &context, &options).unwrap();
 
-        let apply_token = phrased_tokens.get_node(7).unwrap();
+        assert_eq!(report.matches.iter().map(|m| m.phrase.as_str()).collect::<Vec<&str>>(), vec!["perform_task"]);
+    }
 
-        assert_eq!(apply_token.get_definition(), Definition::ApplyTo);
-        assert_eq!(apply_token.get_left(), Some(5));
-        assert_eq!(apply_token.get_right(), Some(8));
-        assert_eq!(apply_token.get_parent(), None);
+    #[test]
+    fn rejected_match_left_as_written() {
+        let parsed = parse(&lex("perform 5 task, perform 500 task").unwrap()).unwrap();
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
 
-        let identifier_token = phrased_tokens.get_node(8).unwrap();
-        assert_eq!(identifier_token.get_definition(), Definition::Identifier);
-        assert_eq!(identifier_token.get_left(), None);
-        assert_eq!(identifier_token.get_right(), None);
-        assert_eq!(identifier_token.get_parent(), Some(7));
-        assert_eq!(identifier_token.get_lex_token().get_text(), "perform_special_task");
+        let validation = crate::Validation::Custom(std::sync::Arc::new(|matched: &crate::MatchedPhrase, result: &garnish_lang_compiler::parse::ParseResult| {
+            matched.words.len() == 2 && matched.arguments.iter().all(|argument| text(result, Some(*argument)).len() < 3)
+        }));
+        let (_, report) = reduce_phrases_with_options(&parsed, &context, &ReduceOptions { validation, ..ReduceOptions::default() }).unwrap();
 
-        let identifier_token = phrased_tokens.get_node(5).unwrap();
-        assert_eq!(identifier_token.get_definition(), Definition::List);
-        assert_eq!(identifier_token.get_left(), Some(2));
-        assert_eq!(identifier_token.get_right(), Some(6));
-        assert_eq!(identifier_token.get_parent(), Some(7));
+        assert_eq!(report.matches.len(), 1);
+        assert_eq!(report.matches[0].span.start, Position { line: 0, column: 0 });
+    }
 
-        let identifier_token = phrased_tokens.get_node(2).unwrap();
-        assert_eq!(identifier_token.get_definition(), Definition::Number);
-        assert_eq!(identifier_token.get_left(), None);
-        assert_eq!(identifier_token.get_right(), None);
-        assert_eq!(identifier_token.get_parent(), Some(5));
-        assert_eq!(identifier_token.get_lex_token().get_text(), "5");
+    #[test]
+    fn unfinished_phrase_suggests_completions() {
+        let parsed = parse(&lex("perform the 5").unwrap()).unwrap();
+        let mut context = SimplePhraseContext::new();
+        context.add_phrases(["perform_the_task", "perform_the_tasks", "stop"]).unwrap();
 
-        let identifier_token = phrased_tokens.get_node(6).unwrap();
-        assert_eq!(identifier_token.get_definition(), Definition::Number);
-        assert_eq!(identifier_token.get_left(), None);
-        assert_eq!(identifier_token.get_right(), None);
-        assert_eq!(identifier_token.get_parent(), Some(5));
-        assert_eq!(identifier_token.get_lex_token().get_text(), "10");
+        let (_, report) = reduce_phrases_with_options(&parsed, &context, &ReduceOptions { learn: true, ..ReduceOptions::default() }).unwrap();
+
+        assert_eq!(report.unfinished[0].suggestions, vec!["perform_the_task", "perform_the_tasks"]);
+        assert_eq!(report.unfinished[0].to_string(), "phrase 'perform the' at 1:1 is unfinished, did you mean 'perform the task' or 'perform the tasks'?");
     }
 
     #[test]
-    fn nested_two_word_phrase() {
-        let input = "perform super special task";
+    fn unfinished_phrase_warns_by_default() {
+        let parsed = parse(&lex("perform the 5").unwrap()).unwrap();
+        let mut context = SimplePhraseContext::new();
+        context.add_phrases(["perform_the_task", "perform_the_tasks", "stop"]).unwrap();
 
-        let tokens = lex(input).unwrap();
-        let parsed = parse(&tokens).unwrap();
+        let (_, report) = reduce_phrases_with_options(&parsed, &context, &ReduceOptions::default()).unwrap();
 
-        let mut context = SimplePhraseContext::new();
-        context.add_phrase("perform_task").unwrap();
-        context.add_phrase("super_special").unwrap();
+        assert!(report.unfinished.is_empty());
+        assert_eq!(report.warnings.len(), 1);
+        assert_eq!(report.warnings[0].to_string(), "phrase 'perform the' at 1:1 is unfinished, did you mean 'perform the task' or 'perform the tasks'?");
+    }
 
-        let phrased_tokens = reduce_phrases(&parsed, &context).unwrap();
+    #[test]
+    fn misspelled_first_word_suggests_phrases() {
+        let parsed = parse(&lex("perfrom 5 task").unwrap()).unwrap();
+        let mut context = SimplePhraseContext::new();
+        context.add_phrases(["perform_task", "stop"]).unwrap();
 
-        let apply_token = phrased_tokens.get_node(5).unwrap();
+        let (_, report) = reduce_phrases_with_options(&parsed, &context, &ReduceOptions::default()).unwrap();
+        assert!(report.warnings.is_empty());
 
-        assert_eq!(phrased_tokens.get_nodes().len(), 8);
+        let options = ReduceOptions { suggest_phrases: true, ..ReduceOptions::default() };
+        let (_, report) = reduce_phrases_with_options(&parsed, &context, &options).unwrap();
 
-        assert_eq!(phrased_tokens.get_root(), 5);
-        assert_eq!(apply_token.get_definition(), Definition::ApplyTo);
-        assert_eq!(apply_token.get_left(), Some(7));
-        assert_eq!(apply_token.get_right(), Some(6));
-        assert_eq!(apply_token.get_parent(), None);
+        assert!(report.matches.is_empty());
+        assert_eq!(report.warnings, vec![ReduceWarning::NotAPhrase {
+            word: "perfrom".to_string(),
+            span: Span { start: Position { line: 0, column: 0 }, end: Position { line: 0, column: 7 } },
+            suggestions: vec!["perform_task".to_string()],
+        }]);
+        assert_eq!(report.warnings[0].to_string(), "word 'perfrom' at 1:1 isn't a phrase word, did you mean 'perform task'?");
+    }
 
-        let identifier_token = phrased_tokens.get_node(6).unwrap();
-        assert_eq!(identifier_token.get_definition(), Definition::Identifier);
-        assert_eq!(identifier_token.get_left(), None);
-        assert_eq!(identifier_token.get_right(), None);
-        assert_eq!(identifier_token.get_parent(), Some(5));
-        assert_eq!(identifier_token.get_lex_token().get_text(), "perform_task");
+    #[test]
+    fn match_limits_exceeded() {
+        let parsed = parse(&lex("copy 1 2 3 to").unwrap()).unwrap();
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("copy_to").unwrap();
 
-        let identifier_token = phrased_tokens.get_node(7).unwrap();
-        assert_eq!(identifier_token.get_definition(), Definition::EmptyApply);
-        assert_eq!(identifier_token.get_left(), Some(4));
-        assert_eq!(identifier_token.get_right(), None);
-        assert_eq!(identifier_token.get_parent(), Some(5));
+        let options = ReduceOptions { limits: crate::MatchLimits::new().with_max_arguments(3), ..ReduceOptions::default() };
+        assert!(reduce_phrases_with_options(&parsed, &context, &options).is_ok());
 
-        let identifier_token = phrased_tokens.get_node(4).unwrap();
-        assert_eq!(identifier_token.get_definition(), Definition::Identifier);
-        assert_eq!(identifier_token.get_left(), None);
-        assert_eq!(identifier_token.get_right(), None);
-        assert_eq!(identifier_token.get_parent(), Some(7));
-        assert_eq!(identifier_token.get_lex_token().get_text(), "super_special");
+        let options = ReduceOptions { limits: crate::MatchLimits::new().with_max_arguments(2), ..ReduceOptions::default() };
+        let error = reduce_phrases_with_options(&parsed, &context, &options).unwrap_err();
+        assert!(matches!(error, PhraseError::LimitExceeded { limit: crate::MatchLimit::Arguments, max: 2, .. }));
+        assert_eq!(error.to_string(), "phrase 'copy' at 1:1 has more than 2 arguments");
     }
 
     #[test]
-    fn operator_terminates_phrase() {
-        let input = "perform + task";
-
-        let tokens = lex(input).unwrap();
-        let parsed = parse(&tokens).unwrap();
+    fn malformed_trees_are_errors() {
+        let mut cyclic = parse(&lex("perform 5 task").unwrap()).unwrap();
+        let root = cyclic.get_root();
+        let left = cyclic.get_node(root).unwrap().get_left().unwrap();
+        cyclic.get_node_mut(left).unwrap().set_left(Some(root));
 
         let mut context = SimplePhraseContext::new();
         context.add_phrase("perform_task").unwrap();
 
-        let phrased_tokens = reduce_phrases(&parsed, &context).unwrap();
+        let error = reduce_phrases(&cyclic, &context).unwrap_err();
+        assert!(matches!(error, PhraseError::CyclicTree { node, via } if node == root && via == left));
 
-        let apply_token = phrased_tokens.get_node(1).unwrap();
+        let mut dangling = parse(&lex("perform 5 task").unwrap()).unwrap();
+        let root = dangling.get_root();
+        dangling.get_node_mut(root).unwrap().set_right(Some(40));
 
-        assert_eq!(phrased_tokens.get_nodes().len(), 3);
+        let error = reduce_phrases(&dangling, &context).unwrap_err();
+        assert!(matches!(error, PhraseError::DanglingLink { node, link: 40 } if node == root));
 
-        assert_eq!(phrased_tokens.get_root(), 1);
-        assert_eq!(apply_token.get_definition(), Definition::Addition);
-        assert_eq!(apply_token.get_left(), Some(0));
-        assert_eq!(apply_token.get_right(), Some(2));
-        assert_eq!(apply_token.get_parent(), None);
+        let mut looping = parse(&lex("perform 5 task").unwrap()).unwrap();
+        let root = looping.get_root();
+        looping.get_node_mut(root).unwrap().set_parent(Some(left));
 
-        let identifier_token = phrased_tokens.get_node(0).unwrap();
-        assert_eq!(identifier_token.get_definition(), Definition::Identifier);
-        assert_eq!(identifier_token.get_left(), None);
-        assert_eq!(identifier_token.get_right(), None);
-        assert_eq!(identifier_token.get_parent(), Some(1));
-        assert_eq!(identifier_token.get_lex_token().get_text(), "perform");
+        assert!(matches!(reduce_phrases(&looping, &context).unwrap_err(), PhraseError::CyclicTree { .. }));
 
-        let identifier_token = phrased_tokens.get_node(2).unwrap();
-        assert_eq!(identifier_token.get_definition(), Definition::Identifier);
-        assert_eq!(identifier_token.get_left(), None);
-        assert_eq!(identifier_token.get_right(), None);
-        assert_eq!(identifier_token.get_parent(), Some(1));
-        assert_eq!(identifier_token.get_lex_token().get_text(), "task");
+        let mut orphaned = parse(&lex("perform 5 task").unwrap()).unwrap();
+        let root = orphaned.get_root();
+        let right = orphaned.get_node(root).unwrap().get_right().unwrap();
+        orphaned.get_node_mut(right).unwrap().set_parent(None);
+
+        let error = reduce_phrases(&orphaned, &context).unwrap_err();
+        assert!(matches!(error, PhraseError::MismatchedParent { node, linked_from, parent: None } if node == right && linked_from == root));
     }
 
     #[test]
-    fn phrase_operator_arg() {
-        let input = "perform 5 + 10 task";
+    fn overload_chosen_by_argument_count() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase_overload("draw_circle", 1, PhraseMeta::new().with_target("draw_unit_circle")).unwrap();
+        context.add_phrase_overload("draw_circle", 3, PhraseMeta::new().with_target("draw_circle_at")).unwrap();
 
-        let tokens = lex(input).unwrap();
-        let parsed = parse(&tokens).unwrap();
+        for (input, target) in [("draw 5 circle", "draw_unit_circle"), ("draw 1 2 3 circle", "draw_circle_at")] {
+            let parsed = parse(&lex(input).unwrap()).unwrap();
+            let (reduced, report) = reduce_phrases_with_options(&parsed, &context, &ReduceOptions::default()).unwrap();
 
-        let mut context = SimplePhraseContext::new();
-        context.add_phrase("perform_task").unwrap();
+            assert_eq!(report.matches[0].meta.as_ref().and_then(|meta| meta.target.as_deref()), Some(target));
+            assert!(reduced.get_nodes().iter().any(|node| node.get_lex_token().get_text() == target));
+        }
 
-        let phrased_tokens = reduce_phrases(&parsed, &context).unwrap();
+        // 2 arguments could be a partial application of either
+        context.add_phrase_overload("draw_circle", 4, PhraseMeta::new().with_target("draw_circle_in")).unwrap();
+        let parsed = parse(&lex("draw 1 2 circle").unwrap()).unwrap();
+        let error = reduce_phrases_with_options(&parsed, &context, &ReduceOptions::default()).unwrap_err();
 
-        let apply_token = phrased_tokens.get_node(5).unwrap();
+        assert!(matches!(error, PhraseError::AmbiguousOverload { found: 2, ref arities, .. } if arities == &vec![3, 4]));
+        assert_eq!(context.phrase_overloads("draw_circle"), vec![1, 3, 4]);
+    }
 
-        assert_eq!(apply_token.get_definition(), Definition::ApplyTo);
-        assert_eq!(apply_token.get_left(), Some(3));
-        assert_eq!(apply_token.get_right(), Some(6));
-        assert_eq!(apply_token.get_parent(), None);
+    #[test]
+    fn reducing_again_changes_nothing() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrases(["perform_task", "halt", "stop_now", "copy_to"]).unwrap();
 
-        let identifier_token = phrased_tokens.get_node(6).unwrap();
-        assert_eq!(identifier_token.get_definition(), Definition::Identifier);
-        assert_eq!(identifier_token.get_left(), None);
-        assert_eq!(identifier_token.get_right(), None);
-        assert_eq!(identifier_token.get_parent(), Some(5));
-        assert_eq!(identifier_token.get_lex_token().get_text(), "perform_task");
+        let mut overlapping = SimplePhraseContext::new();
+        overlapping.set_overlap_policy(crate::context::OverlapPolicy::AllowCompletePrefix);
+        overlapping.add_phrases(["perform", "perform_task", "perform_task_quickly", "stop", "halt", "stop_now", "copy_to"]).unwrap();
 
-        let identifier_token = phrased_tokens.get_node(3).unwrap();
-        assert_eq!(identifier_token.get_definition(), Definition::Addition);
-        assert_eq!(identifier_token.get_left(), Some(2));
-        assert_eq!(identifier_token.get_right(), Some(4));
-        assert_eq!(identifier_token.get_parent(), Some(5));
-        assert_eq!(identifier_token.get_lex_token().get_text(), "+");
+        let options = [
+            ReduceOptions::default(),
+            ReduceOptions { split_identifiers: true, ..ReduceOptions::default() },
+            ReduceOptions { preserve_words: true, ..ReduceOptions::default() },
+            ReduceOptions { named_arguments: true, ..ReduceOptions::default() },
+            ReduceOptions { annotate: true, ..ReduceOptions::default() },
+        ];
+        let inputs = [
+            "perform 5 task",
+            "value perform_task",
+            "stop now",
+            "halt",
+            "5 halt",
+            "copy 1 2 to, perform 5 task",
+            "x = (perform 5 task) 7",
+            "perform task, stop",
+            "perform task quickly 3",
+            "load file 3 then perform task then stop",
+            "perform task stop now",
+        ];
 
-        let identifier_token = phrased_tokens.get_node(2).unwrap();
-        assert_eq!(identifier_token.get_definition(), Definition::Number);
-        assert_eq!(identifier_token.get_left(), None);
-        assert_eq!(identifier_token.get_right(), None);
-        assert_eq!(identifier_token.get_parent(), Some(3));
-        assert_eq!(identifier_token.get_lex_token().get_text(), "5");
+        for context in [&context, &overlapping] {
+            for options in options.iter() {
+                for input in inputs {
+                    let parsed = parse(&lex(input).unwrap()).unwrap();
+                    let (once, _) = reduce_phrases_with_options(&parsed, context, options).unwrap();
+                    let (twice, report) = reduce_phrases_with_options(&once, context, options).unwrap();
 
-        let identifier_token = phrased_tokens.get_node(4).unwrap();
-        assert_eq!(identifier_token.get_definition(), Definition::Number);
-        assert_eq!(identifier_token.get_left(), None);
-        assert_eq!(identifier_token.get_right(), None);
-        assert_eq!(identifier_token.get_parent(), Some(3));
-        assert_eq!(identifier_token.get_lex_token().get_text(), "10");
+                    assert!(crate::ParseResultExt::check_structure(&once).is_ok(), "{:?} with {:?}", input, options);
+                    assert_eq!(twice, once, "{:?} with {:?}", input, options);
+                    assert!(report.matches.is_empty(), "{:?} with {:?}", input, options);
+                }
+            }
+        }
     }
 }
@@ -0,0 +1,70 @@
+use std::fmt::{Display, Formatter};
+
+// bounds on the work a single match can take, for reducing untrusted input against large dictionaries
+// exceeding any of them fails the reduction with PhraseError::LimitExceeded
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct MatchLimits {
+    pub max_words: Option<usize>,
+    pub max_arguments: Option<usize>,
+    // phrases in progress at once, each argument starting a phrase adds one
+    pub max_depth: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum MatchLimit {
+    Words,
+    Arguments,
+    Depth,
+}
+
+impl Display for MatchLimit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MatchLimit::Words => write!(f, "words"),
+            MatchLimit::Arguments => write!(f, "arguments"),
+            MatchLimit::Depth => write!(f, "phrases in progress"),
+        }
+    }
+}
+
+impl MatchLimits {
+    pub fn new() -> Self {
+        MatchLimits::default()
+    }
+
+    pub fn with_max_words(mut self, max: usize) -> Self {
+        self.max_words = Some(max);
+        self
+    }
+
+    pub fn with_max_arguments(mut self, max: usize) -> Self {
+        self.max_arguments = Some(max);
+        self
+    }
+
+    pub fn with_max_depth(mut self, max: usize) -> Self {
+        self.max_depth = Some(max);
+        self
+    }
+
+    // first limit the counts of a match in progress are over, with its maximum
+    pub(crate) fn exceeded(&self, words: usize, arguments: usize, depth: usize) -> Option<(MatchLimit, usize)> {
+        [(MatchLimit::Words, self.max_words, words), (MatchLimit::Arguments, self.max_arguments, arguments), (MatchLimit::Depth, self.max_depth, depth)]
+            .into_iter()
+            .find_map(|(limit, max, found)| max.filter(|max| found > *max).map(|max| (limit, max)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::limits::{MatchLimit, MatchLimits};
+
+    #[test]
+    fn first_exceeded_limit() {
+        let limits = MatchLimits::new().with_max_words(3).with_max_arguments(2);
+
+        assert_eq!(limits.exceeded(3, 2, 10), None);
+        assert_eq!(limits.exceeded(3, 3, 10), Some((MatchLimit::Arguments, 2)));
+        assert_eq!(limits.exceeded(4, 3, 10), Some((MatchLimit::Words, 3)));
+    }
+}
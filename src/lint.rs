@@ -0,0 +1,178 @@
+use std::fmt::{Display, Formatter};
+
+use crate::context::SimplePhraseContext;
+use crate::distance::edit_distance;
+
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum LintSeverity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum LintFinding {
+    // prefix registered with no complete phrase to finish it
+    DeadPrefix { prefix: String },
+    // phrase can't be started right after `after` inside `by`
+    // because its first word continues `by` instead
+    Shadowed { phrase: String, by: String, after: String },
+    // phrases within configured edit distance of each other
+    Similar { first: String, second: String, distance: usize },
+}
+
+impl LintFinding {
+    pub fn severity(&self) -> LintSeverity {
+        match self {
+            LintFinding::DeadPrefix { .. } => LintSeverity::Error,
+            LintFinding::Shadowed { .. } => LintSeverity::Warning,
+            LintFinding::Similar { .. } => LintSeverity::Warning,
+        }
+    }
+}
+
+impl Display for LintFinding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LintFinding::DeadPrefix { prefix } => write!(f, "prefix '{}' is not completed by any phrase", prefix),
+            LintFinding::Shadowed { phrase, by, after } => write!(
+                f,
+                "phrase '{}' can't start after '{}' because it continues phrase '{}'",
+                phrase, after, by
+            ),
+            LintFinding::Similar { first, second, distance } => write!(
+                f,
+                "phrases '{}' and '{}' are {} edit(s) apart",
+                first, second, distance
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct LintOptions {
+    // phrases this many edits apart or closer are reported as similar, 0 disables check
+    pub similarity_distance: usize,
+}
+
+impl Default for LintOptions {
+    fn default() -> Self {
+        LintOptions { similarity_distance: 1 }
+    }
+}
+
+pub fn lint(context: &SimplePhraseContext) -> Vec<LintFinding> {
+    lint_with_options(context, &LintOptions::default())
+}
+
+pub fn lint_with_options(context: &SimplePhraseContext, options: &LintOptions) -> Vec<LintFinding> {
+    let phrases = context.complete_phrases();
+    let mut findings = vec![];
+
+    for prefix in context.incomplete_phrases() {
+        let extension = format!("{}_", prefix);
+        if !phrases.iter().any(|phrase| phrase.starts_with(&extension)) {
+            findings.push(LintFinding::DeadPrefix { prefix: prefix.to_string() });
+        }
+    }
+
+    for phrase in phrases.iter() {
+        let parts = phrase.split('_').collect::<Vec<&str>>();
+
+        for (i, part) in parts.iter().enumerate().skip(1) {
+            for other in phrases.iter() {
+                if other != phrase && other.split('_').next() == Some(*part) {
+                    findings.push(LintFinding::Shadowed {
+                        phrase: other.to_string(),
+                        by: phrase.to_string(),
+                        after: parts[..i].join("_"),
+                    });
+                }
+            }
+        }
+    }
+
+    if options.similarity_distance > 0 {
+        for (i, first) in phrases.iter().enumerate() {
+            for second in phrases.iter().skip(i + 1) {
+                let distance = edit_distance(first, second);
+                if distance <= options.similarity_distance {
+                    findings.push(LintFinding::Similar {
+                        first: first.to_string(),
+                        second: second.to_string(),
+                        distance,
+                    });
+                }
+            }
+        }
+    }
+
+    findings.sort();
+    findings.dedup();
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::SimplePhraseContext;
+    use crate::lint::{lint, lint_with_options, LintFinding, LintOptions, LintSeverity};
+
+    #[test]
+    fn clean_dictionary() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+        context.add_phrase("stop").unwrap();
+
+        assert!(lint(&context).is_empty());
+    }
+
+    #[test]
+    fn dead_prefix() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task_").unwrap();
+
+        let findings = lint(&context);
+
+        assert_eq!(findings, vec![
+            LintFinding::DeadPrefix { prefix: "perform".to_string() },
+            LintFinding::DeadPrefix { prefix: "perform_task".to_string() },
+        ]);
+        assert_eq!(findings[0].severity(), LintSeverity::Error);
+    }
+
+    #[test]
+    fn shadowed_phrase() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_special_task").unwrap();
+        context.add_phrase("special_move").unwrap();
+
+        assert_eq!(lint(&context), vec![LintFinding::Shadowed {
+            phrase: "special_move".to_string(),
+            by: "perform_special_task".to_string(),
+            after: "perform".to_string(),
+        }]);
+    }
+
+    #[test]
+    fn similar_phrases() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+        context.add_phrase("perform_tasks").unwrap();
+
+        assert_eq!(lint(&context), vec![LintFinding::Similar {
+            first: "perform_task".to_string(),
+            second: "perform_tasks".to_string(),
+            distance: 1,
+        }]);
+    }
+
+    #[test]
+    fn similarity_check_disabled() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+        context.add_phrase("perform_tasks").unwrap();
+
+        let findings = lint_with_options(&context, &LintOptions { similarity_distance: 0 });
+
+        assert!(findings.is_empty());
+    }
+}
@@ -0,0 +1,216 @@
+use std::ops::Range;
+
+use crate::context::{PhraseContext, PhraseStatus};
+
+// what PhraseMatcher found in a sequence of words, indices are positions in that sequence
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum MatchEvent {
+    Phrase {
+        phrase: String,
+        words: Vec<usize>,
+        // words between the phrase words, a nested phrase is one argument
+        arguments: Vec<Range<usize>>,
+    },
+    // word outside of any phrase
+    Word { index: usize },
+    // words that began a phrase the input ended before finishing
+    Unfinished { phrase: String, words: Vec<usize> },
+}
+
+#[derive(Debug, Clone)]
+struct Progress {
+    text: String,
+    words: Vec<usize>,
+    // argument ranges paired with whether the argument is a phrase
+    arguments: Vec<(Range<usize>, bool)>,
+    // state when the words so far were a complete phrase that a longer one starts with
+    fallback: Option<Box<Progress>>,
+}
+
+impl Progress {
+    fn start(&self) -> usize {
+        self.words.first().copied().unwrap_or_default()
+    }
+}
+
+// the reducer's matching for plain words, e.g. command line input or chat commands
+// words continue the innermost phrase in progress when they can, start a phrase when they can, otherwise are arguments
+pub struct PhraseMatcher<'a, Context: PhraseContext + ?Sized> {
+    context: &'a Context,
+}
+
+impl<'a, Context: PhraseContext + ?Sized> PhraseMatcher<'a, Context> {
+    pub fn new(context: &'a Context) -> Self {
+        PhraseMatcher { context }
+    }
+
+    // events in the order they were settled, a phrase is settled by its last word
+    pub fn match_words<'w>(&self, words: impl IntoIterator<Item = &'w str>) -> Vec<MatchEvent> {
+        let mut events = vec![];
+        let mut stack: Vec<Progress> = vec![];
+
+        for (index, word) in words.into_iter().enumerate() {
+            let extended = stack.last().map(|top| format!("{}_{}", top.text, word));
+            let extended_status = extended.as_deref().map(|text| self.context.get_phrase_status(text)).unwrap_or(PhraseStatus::NotAPhrase);
+
+            let finished = match (stack.pop(), extended) {
+                (Some(mut top), Some(text)) if extended_status != PhraseStatus::NotAPhrase => {
+                    top.text = text;
+                    top.words.push(index);
+                    self.advance(top, extended_status, &mut stack)
+                }
+                (top, _) => {
+                    stack.extend(top);
+                    match self.context.get_phrase_status(word) {
+                        PhraseStatus::NotAPhrase => {
+                            match stack.last_mut() {
+                                None => events.push(MatchEvent::Word { index }),
+                                Some(top) => top.arguments.push((index..index + 1, false)),
+                            }
+                            None
+                        }
+                        status => {
+                            let progress = Progress { text: word.to_string(), words: vec![index], arguments: vec![], fallback: None };
+                            self.advance(progress, status, &mut stack)
+                        }
+                    }
+                }
+            };
+
+            if let Some(finished) = finished {
+                self.settle(finished, &mut stack, &mut events);
+            }
+        }
+
+        while let Some(progress) = stack.pop() {
+            self.abandon(progress, &mut stack, &mut events);
+        }
+
+        events
+    }
+
+    // splits on whitespace
+    pub fn match_text(&self, text: &str) -> Vec<MatchEvent> {
+        self.match_words(text.split_whitespace())
+    }
+
+    // returns progress when it is a finished phrase, otherwise keeps it on the stack
+    fn advance(&self, mut progress: Progress, status: PhraseStatus, stack: &mut Vec<Progress>) -> Option<Progress> {
+        match status {
+            PhraseStatus::Complete => Some(progress),
+            PhraseStatus::CompleteAndExtendable => {
+                progress.fallback = Some(Box::new(Progress { fallback: None, ..progress.clone() }));
+                stack.push(progress);
+                None
+            }
+            _ => {
+                stack.push(progress);
+                None
+            }
+        }
+    }
+
+    // emits a finished phrase, it becomes an argument of the phrase it is nested in
+    fn settle(&self, progress: Progress, stack: &mut [Progress], events: &mut Vec<MatchEvent>) {
+        let start = progress.start().min(progress.arguments.first().map(|(range, _)| range.start).unwrap_or(usize::MAX));
+        let end = progress.words.last().copied().unwrap_or_default() + 1;
+        if let Some(top) = stack.last_mut() {
+            top.arguments.push((start..end, true));
+        }
+
+        events.push(MatchEvent::Phrase {
+            phrase: progress.text,
+            words: progress.words,
+            arguments: progress.arguments.into_iter().map(|(range, _)| range).collect(),
+        });
+    }
+
+    // input ended inside progress, the longest complete part of it is still a phrase
+    fn abandon(&self, progress: Progress, stack: &mut [Progress], events: &mut Vec<MatchEvent>) {
+        let (words, arguments) = match progress.fallback {
+            None => {
+                events.push(MatchEvent::Unfinished { phrase: progress.text, words: progress.words.clone() });
+                (progress.words, progress.arguments)
+            }
+            Some(fallback) => {
+                let words = progress.words[fallback.words.len()..].to_vec();
+                let arguments = progress.arguments[fallback.arguments.len()..].to_vec();
+                self.settle(*fallback, stack, events);
+                (words, arguments)
+            }
+        };
+
+        // left over words are plain words again, nested phrases were already emitted
+        let mut left = words.into_iter()
+            .chain(arguments.into_iter().filter(|(_, is_phrase)| !is_phrase).map(|(range, _)| range.start))
+            .collect::<Vec<usize>>();
+        left.sort();
+        for index in left {
+            match stack.last_mut() {
+                None => events.push(MatchEvent::Word { index }),
+                Some(top) => top.arguments.push((index..index + 1, false)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::{OverlapPolicy, SimplePhraseContext};
+    use crate::matcher::{MatchEvent, PhraseMatcher};
+
+    fn phrase(phrase: &str, words: &[usize], arguments: &[(usize, usize)]) -> MatchEvent {
+        MatchEvent::Phrase {
+            phrase: phrase.to_string(),
+            words: words.to_vec(),
+            arguments: arguments.iter().map(|(start, end)| *start..*end).collect(),
+        }
+    }
+
+    #[test]
+    fn phrase_with_arguments() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrases(["perform_task", "stop"]).unwrap();
+
+        let events = PhraseMatcher::new(&context).match_text("perform backup now task then stop");
+
+        assert_eq!(events, vec![
+            phrase("perform_task", &[0, 3], &[(1, 2), (2, 3)]),
+            MatchEvent::Word { index: 4 },
+            phrase("stop", &[5], &[]),
+        ]);
+    }
+
+    #[test]
+    fn nested_phrase_is_one_argument() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrases(["send_to", "copy_of"]).unwrap();
+
+        let events = PhraseMatcher::new(&context).match_words(["send", "copy", "report", "of", "to", "team"]);
+
+        assert_eq!(events, vec![
+            phrase("copy_of", &[1, 3], &[(2, 3)]),
+            phrase("send_to", &[0, 4], &[(1, 4)]),
+            MatchEvent::Word { index: 5 },
+        ]);
+    }
+
+    #[test]
+    fn unfinished_and_fallback() {
+        let mut context = SimplePhraseContext::new();
+        context.set_overlap_policy(OverlapPolicy::AllowCompletePrefix);
+        context.add_phrases(["perform_task", "perform_task_now", "open_door"]).unwrap();
+
+        let matcher = PhraseMatcher::new(&context);
+
+        assert_eq!(matcher.match_text("perform 5 task 10"), vec![
+            phrase("perform_task", &[0, 2], &[(1, 2)]),
+            MatchEvent::Word { index: 3 },
+        ]);
+        assert_eq!(matcher.match_text("open 5"), vec![
+            MatchEvent::Unfinished { phrase: "open".to_string(), words: vec![0] },
+            MatchEvent::Word { index: 0 },
+            MatchEvent::Word { index: 1 },
+        ]);
+    }
+}
@@ -0,0 +1,364 @@
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::fingerprint::Fingerprinter;
+
+// where a phrase was defined
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum PhraseSource {
+    File(String),
+    Library(String),
+    // label given by code that added the phrase
+    Call(String),
+}
+
+impl Display for PhraseSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PhraseSource::File(path) => write!(f, "file '{}'", path),
+            PhraseSource::Library(name) => write!(f, "library '{}'", name),
+            PhraseSource::Call(label) => write!(f, "'{}'", label),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    #[default]
+    Public,
+    // only matched when reduction opts in, for helper phrases of shared libraries
+    Internal,
+}
+
+// node a phrase with arguments is rewritten to
+// phrases without arguments are always rewritten to EmptyApply
+#[derive(Debug, Copy, Clone, Default, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApplyForm {
+    // args ~> phrase
+    #[default]
+    ApplyTo,
+    // phrase ~ args
+    Apply,
+    // args ~> phrase~~
+    EmptyApply,
+    // args.phrase, only the last segment of a namespaced target is used
+    Access,
+}
+
+// where in the tree a phrase's words are written, decided by the parent of the list holding them
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyntacticPosition {
+    // whole expression or a whole statement of a subexpression
+    ExpressionRoot,
+    // item of a comma list
+    ListElement,
+    // anywhere else, e.g. in a group or as an operand
+    Nested,
+}
+
+// keys of the pairs arguments are wrapped in when ReduceOptions::named_arguments is set
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub enum ArgumentKeys {
+    // PhraseMeta::argument_name of the argument's rewritten position
+    #[default]
+    Position,
+    // phrase word written before the argument, perform 5 task is (perform = 5) ~ perform_task
+    // later arguments after the same word are numbered, scale 5 10 by is scale = 5, scale2 = 10
+    PrecedingWord,
+}
+
+// order arguments are placed in the rewritten apply
+#[derive(Debug, Clone, Default, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArgumentOrder {
+    #[default]
+    AsWritten,
+    Reversed,
+    // position i of the rewritten list takes written argument at index permutation[i]
+    Permutation(Vec<usize>),
+}
+
+impl ArgumentOrder {
+    // None when a permutation doesn't cover the arguments exactly
+    pub fn arrange<T: Clone>(&self, arguments: &[T]) -> Option<Vec<T>> {
+        match self {
+            ArgumentOrder::AsWritten => Some(arguments.to_vec()),
+            ArgumentOrder::Reversed => Some(arguments.iter().rev().cloned().collect()),
+            ArgumentOrder::Permutation(permutation) => {
+                let mut sorted = permutation.clone();
+                sorted.sort();
+                if sorted != (0..arguments.len()).collect::<Vec<usize>>() {
+                    return None;
+                }
+
+                Some(permutation.iter().map(|index| arguments[*index].clone()).collect())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Deprecation {
+    // phrase to use instead
+    pub replacement: Option<String>,
+}
+
+// fields are added between releases, build from PhraseMeta::new() with the with_ methods
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct PhraseMeta {
+    pub doc: Option<String>,
+    pub examples: Vec<String>,
+    pub deprecated: Option<Deprecation>,
+    pub visibility: Visibility,
+    pub source: Option<PhraseSource>,
+    pub apply: ApplyForm,
+    // identifier emitted in place of the phrase text
+    // dotted paths are emitted as an access chain, tasks::perform is the same as tasks.perform
+    pub target: Option<String>,
+    // overrides ReduceOptions::argument_order
+    pub argument_order: Option<ArgumentOrder>,
+    // number of arguments the phrase expects, unchecked when None
+    pub arity: Option<usize>,
+    // most arguments the phrase takes when more than its arity are allowed, see ReduceOptions::extra_arguments
+    pub max_arity: Option<usize>,
+    // keys of the pairs arguments are wrapped in when ReduceOptions::named_arguments is set, by rewritten position
+    // positions without a name use arg1, arg2 and so on
+    pub argument_names: Vec<String>,
+    // free form labels for grouping and filtering phrases
+    pub tags: Vec<String>,
+    // positions the phrase may match in, anywhere when empty
+    pub positions: Vec<SyntacticPosition>,
+    // preference from usage data, compared by Disambiguation::Weighted, 0 when None
+    pub weight: Option<i64>,
+    // wins over overlapping phrases of lower priority, 0 when None
+    // a phrase with a priority may also be added where it overlaps another, see OverlapPolicy
+    pub priority: Option<i64>,
+    // words may be written in any order, e.g. "sort ascending list" for sort_list_ascending
+    // only takes effect when added to a SimplePhraseContext with the meta
+    pub free_order: bool,
+    // items of the list written after the final word are arguments too, e.g. "add to list 5 10"
+    // they're taken as written, a phrase among them needs to be grouped
+    pub trailing_arguments: bool,
+    // arguments written after each word when added as a template, e.g. [1, 1] for "move _ to _"
+    // unchecked when empty
+    pub slots: Vec<usize>,
+}
+
+impl PhraseMeta {
+    pub fn new() -> Self {
+        PhraseMeta::default()
+    }
+
+    // each field in a fixed order with fixed codes for enums, so the fingerprint doesn't change with the type's layout
+    // new fields go at the end, where the phrase was loaded from doesn't change how it reduces so source is left out
+    pub(crate) fn write_fingerprint(&self, fingerprinter: &mut Fingerprinter) {
+        fingerprinter.write_option(self.doc.as_deref(), Fingerprinter::write_str);
+        fingerprinter.write_list(&self.examples, |fingerprinter, example| fingerprinter.write_str(example));
+        fingerprinter.write_option(self.deprecated.as_ref(), |fingerprinter, deprecation| {
+            fingerprinter.write_option(deprecation.replacement.as_deref(), Fingerprinter::write_str)
+        });
+        fingerprinter.write_u8(match self.visibility {
+            Visibility::Public => 0,
+            Visibility::Internal => 1,
+        });
+        fingerprinter.write_u8(match self.apply {
+            ApplyForm::ApplyTo => 0,
+            ApplyForm::Apply => 1,
+            ApplyForm::EmptyApply => 2,
+            ApplyForm::Access => 3,
+        });
+        fingerprinter.write_option(self.target.as_deref(), Fingerprinter::write_str);
+        fingerprinter.write_option(self.argument_order.as_ref(), |fingerprinter, order| match order {
+            ArgumentOrder::AsWritten => fingerprinter.write_u8(0),
+            ArgumentOrder::Reversed => fingerprinter.write_u8(1),
+            ArgumentOrder::Permutation(permutation) => {
+                fingerprinter.write_u8(2);
+                fingerprinter.write_list(permutation, |fingerprinter, index| fingerprinter.write_u64(*index as u64));
+            }
+        });
+        fingerprinter.write_option(self.arity, |fingerprinter, arity| fingerprinter.write_u64(arity as u64));
+        fingerprinter.write_option(self.max_arity, |fingerprinter, arity| fingerprinter.write_u64(arity as u64));
+        fingerprinter.write_list(&self.argument_names, |fingerprinter, name| fingerprinter.write_str(name));
+        fingerprinter.write_list(&self.tags, |fingerprinter, tag| fingerprinter.write_str(tag));
+        fingerprinter.write_list(&self.positions, |fingerprinter, position| fingerprinter.write_u8(match position {
+            SyntacticPosition::ExpressionRoot => 0,
+            SyntacticPosition::ListElement => 1,
+            SyntacticPosition::Nested => 2,
+        }));
+        fingerprinter.write_option(self.weight, |fingerprinter, weight| fingerprinter.write_u64(weight as u64));
+        fingerprinter.write_option(self.priority, |fingerprinter, priority| fingerprinter.write_u64(priority as u64));
+        fingerprinter.write_u8(self.free_order as u8);
+        fingerprinter.write_u8(self.trailing_arguments as u8);
+        fingerprinter.write_list(&self.slots, |fingerprinter, count| fingerprinter.write_u64(*count as u64));
+    }
+
+    pub fn with_doc(mut self, doc: &str) -> Self {
+        self.doc = Some(doc.to_string());
+        self
+    }
+
+    pub fn with_example(mut self, example: &str) -> Self {
+        self.examples.push(example.to_string());
+        self
+    }
+
+    pub fn with_deprecation(mut self, replacement: Option<&str>) -> Self {
+        self.deprecated = Some(Deprecation { replacement: replacement.map(|r| r.to_string()) });
+        self
+    }
+
+    pub fn with_visibility(mut self, visibility: Visibility) -> Self {
+        self.visibility = visibility;
+        self
+    }
+
+    pub fn with_source(mut self, source: PhraseSource) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    pub fn with_apply(mut self, apply: ApplyForm) -> Self {
+        self.apply = apply;
+        self
+    }
+
+    pub fn with_target(mut self, target: &str) -> Self {
+        self.target = Some(target.to_string());
+        self
+    }
+
+    pub fn with_argument_order(mut self, order: ArgumentOrder) -> Self {
+        self.argument_order = Some(order);
+        self
+    }
+
+    pub fn with_arity(mut self, arity: usize) -> Self {
+        self.arity = Some(arity);
+        self
+    }
+
+    // fewer than min is under applied, more than max are extra arguments
+    pub fn with_arity_range(mut self, min: usize, max: usize) -> Self {
+        self.arity = Some(min);
+        self.max_arity = Some(max.max(min));
+        self
+    }
+
+    // most arguments the phrase takes, None when any number is accepted
+    pub fn max_arguments(&self) -> Option<usize> {
+        self.max_arity.or(self.arity)
+    }
+
+    pub fn with_argument_names(mut self, names: &[&str]) -> Self {
+        self.argument_names = names.iter().map(|name| name.to_string()).collect();
+        self
+    }
+
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+
+    pub fn with_position(mut self, position: SyntacticPosition) -> Self {
+        self.positions.push(position);
+        self
+    }
+
+    pub fn with_weight(mut self, weight: i64) -> Self {
+        self.weight = Some(weight);
+        self
+    }
+
+    pub fn with_priority(mut self, priority: i64) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    pub fn with_free_order(mut self) -> Self {
+        self.free_order = true;
+        self
+    }
+
+    pub fn with_trailing_arguments(mut self) -> Self {
+        self.trailing_arguments = true;
+        self
+    }
+
+    pub fn with_slots(mut self, slots: &[usize]) -> Self {
+        self.slots = slots.to_vec();
+        self
+    }
+
+    // phrase written with its argument slots, None when it isn't a template
+    pub fn template(&self, phrase: &str) -> Option<String> {
+        (!self.slots.is_empty()).then(|| fill_template(phrase, &self.slots))
+    }
+
+    pub fn allows_position(&self, position: SyntacticPosition) -> bool {
+        self.positions.is_empty() || self.positions.contains(&position)
+    }
+
+    // key of the argument at position of the rewritten apply
+    pub fn argument_name(&self, position: usize) -> String {
+        match self.argument_names.get(position) {
+            None => format!("arg{}", position + 1),
+            Some(name) => name.clone(),
+        }
+    }
+}
+
+// words of phrase with an _ for each argument written after them
+pub(crate) fn fill_template(phrase: &str, slots: &[usize]) -> String {
+    let mut parts = vec![];
+    for (position, word) in phrase.split('_').enumerate() {
+        parts.push(word);
+        parts.extend(std::iter::repeat_n("_", slots.get(position).copied().unwrap_or_default()));
+    }
+
+    parts.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::meta::{ArgumentOrder, PhraseMeta};
+
+    #[test]
+    fn arrange_as_written() {
+        assert_eq!(ArgumentOrder::AsWritten.arrange(&[1, 2, 3]), Some(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn arrange_reversed() {
+        assert_eq!(ArgumentOrder::Reversed.arrange(&[1, 2, 3]), Some(vec![3, 2, 1]));
+    }
+
+    #[test]
+    fn arrange_permutation() {
+        assert_eq!(ArgumentOrder::Permutation(vec![1, 2, 0]).arrange(&[1, 2, 3]), Some(vec![2, 3, 1]));
+    }
+
+    #[test]
+    fn declared_and_generated_argument_names() {
+        let meta = PhraseMeta::new().with_argument_names(&["source"]);
+
+        assert_eq!(meta.argument_name(0), "source");
+        assert_eq!(meta.argument_name(1), "arg2");
+    }
+
+    #[test]
+    fn template_from_slots() {
+        assert_eq!(PhraseMeta::new().with_slots(&[1, 0, 2]).template("move_from_to"), Some("move _ from to _ _".to_string()));
+        assert_eq!(PhraseMeta::new().template("move_to"), None);
+    }
+
+    #[test]
+    fn arrange_bad_permutation() {
+        assert_eq!(ArgumentOrder::Permutation(vec![0, 0]).arrange(&[1, 2]), None);
+        assert_eq!(ArgumentOrder::Permutation(vec![0, 1]).arrange(&[1, 2, 3]), None);
+    }
+}
@@ -0,0 +1,40 @@
+use garnish_lang_compiler::parse::ParseResult;
+use serde::{Deserialize, Serialize};
+
+use crate::span::Span;
+
+// rewrite the reducer is about to perform or just performed
+// node indices refer to the parse result passed alongside
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Mutation {
+    // phrase words and arguments rewritten into an apply
+    Phrase { phrase: String, words: Vec<usize>, arguments: Vec<usize>, span: Span },
+    // result of a chained segment fed into the next segment
+    Pipe { previous: usize, next: usize },
+}
+
+// called around every tree mutation so embedders can keep their own indices in sync
+pub trait MutationMiddleware {
+    fn before(&mut self, _mutation: &Mutation, _result: &ParseResult) {}
+
+    // node is the root of the rewritten structure
+    fn after(&mut self, _mutation: &Mutation, _node: Option<usize>, _result: &ParseResult) {}
+}
+
+impl MutationMiddleware for () {}
+
+#[cfg(test)]
+mod tests {
+    use garnish_lang_compiler::parse::ParseResult;
+
+    use crate::middleware::{Mutation, MutationMiddleware};
+
+    #[test]
+    fn unit_does_nothing() {
+        let mutation = Mutation::Pipe { previous: 0, next: 1 };
+        let result = ParseResult::new();
+
+        ().before(&mutation, &result);
+        ().after(&mutation, None, &result);
+    }
+}
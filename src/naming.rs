@@ -0,0 +1,83 @@
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+// builds the identifier for a phrase from its words
+pub trait IdentifierNamer {
+    fn name(&self, words: &[&str]) -> String;
+}
+
+impl<F: Fn(&[&str]) -> String> IdentifierNamer for F {
+    fn name(&self, words: &[&str]) -> String {
+        self(words)
+    }
+}
+
+// naming convention of identifiers emitted for phrases without a target
+// applied before hygiene, so prefixes and suffixes are kept as given
+#[derive(Clone, Default)]
+pub enum NamingPolicy {
+    // words joined by the separator, perform_task
+    #[default]
+    Snake,
+    // performTask, the separator isn't used
+    Camel,
+    // PerformTask, the separator isn't used
+    Pascal,
+    Custom(Arc<dyn IdentifierNamer + Send + Sync>),
+}
+
+impl Debug for NamingPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NamingPolicy::Snake => write!(f, "Snake"),
+            NamingPolicy::Camel => write!(f, "Camel"),
+            NamingPolicy::Pascal => write!(f, "Pascal"),
+            NamingPolicy::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
+impl NamingPolicy {
+    // identifier for the canonical, underscore joined phrase
+    pub fn name(&self, phrase: &str, separator: &str) -> String {
+        let words = phrase.split('_').filter(|word| !word.is_empty()).collect::<Vec<&str>>();
+
+        match self {
+            NamingPolicy::Snake => words.join(separator),
+            NamingPolicy::Camel => words.iter()
+                .enumerate()
+                .map(|(index, word)| match index {
+                    0 => word.to_lowercase(),
+                    _ => capitalize(word),
+                })
+                .collect(),
+            NamingPolicy::Pascal => words.iter().map(|word| capitalize(word)).collect(),
+            NamingPolicy::Custom(namer) => namer.name(&words),
+        }
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        None => String::new(),
+        Some(first) => first.to_uppercase().chain(chars.flat_map(char::to_lowercase)).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::naming::NamingPolicy;
+
+    #[test]
+    fn policies() {
+        assert_eq!(NamingPolicy::Snake.name("perform_the_task", "-"), "perform-the-task");
+        assert_eq!(NamingPolicy::Camel.name("perform_the_task", "-"), "performTheTask");
+        assert_eq!(NamingPolicy::Pascal.name("perform_the_task", "_"), "PerformTheTask");
+
+        let custom = NamingPolicy::Custom(Arc::new(|words: &[&str]| words.join("_").to_uppercase()));
+        assert_eq!(custom.name("perform_the_task", "_"), "PERFORM_THE_TASK");
+    }
+}
@@ -0,0 +1,138 @@
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+// maps a written word to the form used in phrases, e.g. a plural to its singular
+pub trait WordNormalizer {
+    fn normalize(&self, word: &str) -> String;
+}
+
+impl<F: Fn(&str) -> String> WordNormalizer for F {
+    fn normalize(&self, word: &str) -> String {
+        self(word)
+    }
+}
+
+// words as written are always tried first, the normalized form only when they don't match
+#[derive(Clone, Default)]
+pub enum Normalization {
+    #[default]
+    None,
+    // strips English plural s and es endings, see english_singular
+    EnglishPlural,
+    Custom(Arc<dyn WordNormalizer + Send + Sync>),
+}
+
+impl Debug for Normalization {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Normalization::None => write!(f, "None"),
+            Normalization::EnglishPlural => write!(f, "EnglishPlural"),
+            Normalization::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
+impl Normalization {
+    // None when the word has no other form to try
+    pub(crate) fn normalize(&self, word: &str) -> Option<String> {
+        if matches!(self, Normalization::None) {
+            return None;
+        }
+
+        // each word of a joined identifier is normalized on its own
+        if word.contains('_') {
+            let normalized = word.split('_').map(|part| self.normalize(part).unwrap_or_else(|| part.to_string())).collect::<Vec<String>>().join("_");
+            return (normalized != word).then_some(normalized);
+        }
+
+        let normalized = match self {
+            Normalization::None => return None,
+            Normalization::EnglishPlural => english_singular(word),
+            Normalization::Custom(normalizer) => normalizer.normalize(word),
+        };
+
+        (normalized != word).then_some(normalized)
+    }
+}
+
+// simple suffix stemmer, not a dictionary
+// boxes, matches, wishes, buzzes and classes lose es, other words ending in a single s lose the s
+pub fn english_singular(word: &str) -> String {
+    const ES_ENDINGS: &[&str] = &["sses", "xes", "ches", "shes", "zes"];
+
+    if ES_ENDINGS.iter().any(|ending| word.ends_with(ending)) {
+        return word[..word.len() - 2].to_string();
+    }
+
+    match word.strip_suffix('s') {
+        Some(stem) if stem.len() > 2 && !stem.ends_with('s') => stem.to_string(),
+        _ => word.to_string(),
+    }
+}
+
+// words of a camelCase or snake_case identifier, lowercased
+// a run of capitals is one word, e.g. parseHTTPRequest is parse, http, request
+pub fn identifier_words(identifier: &str) -> Vec<String> {
+    let chars = identifier.chars().collect::<Vec<char>>();
+    let mut words = vec![];
+    let mut current = String::new();
+
+    for (index, c) in chars.iter().enumerate() {
+        if *c == '_' {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+
+        let boundary = c.is_uppercase()
+            && !current.is_empty()
+            && (!chars[index - 1].is_uppercase() || chars.get(index + 1).is_some_and(|next| next.is_lowercase()));
+        if boundary {
+            words.push(std::mem::take(&mut current));
+        }
+
+        current.extend(c.to_lowercase());
+    }
+
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::normalize::{english_singular, identifier_words, Normalization};
+
+    #[test]
+    fn words_of_identifiers() {
+        assert_eq!(identifier_words("performTask"), vec!["perform", "task"]);
+        assert_eq!(identifier_words("perform_task"), vec!["perform", "task"]);
+        assert_eq!(identifier_words("PerformTask2Now"), vec!["perform", "task2", "now"]);
+        assert_eq!(identifier_words("parseHTTPRequest"), vec!["parse", "http", "request"]);
+        assert_eq!(identifier_words("_perform__task_"), vec!["perform", "task"]);
+    }
+
+    #[test]
+    fn joined_words_normalized_separately() {
+        assert_eq!(Normalization::EnglishPlural.normalize("files_tasks"), Some("file_task".to_string()));
+    }
+
+    #[test]
+    fn singular_forms() {
+        let words = ["tasks", "boxes", "matches", "wishes", "classes", "cases", "class", "is", "task"];
+
+        assert_eq!(words.map(english_singular), ["task", "box", "match", "wish", "class", "case", "class", "is", "task"]);
+    }
+
+    #[test]
+    fn unchanged_word_has_no_normalized_form() {
+        assert_eq!(Normalization::EnglishPlural.normalize("task"), None);
+        assert_eq!(Normalization::None.normalize("tasks"), None);
+        assert_eq!(Normalization::Custom(Arc::new(|word: &str| word.to_lowercase())).normalize("Task"), Some("task".to_string()));
+    }
+}
@@ -0,0 +1,93 @@
+use crate::arbitration::Disambiguation;
+use crate::context::SimplePhraseContext;
+use crate::duplicate::DuplicateWords;
+use crate::hygiene::Hygiene;
+use crate::limits::MatchLimits;
+use crate::meta::{ArgumentKeys, ArgumentOrder};
+use crate::naming::NamingPolicy;
+use crate::normalize::Normalization;
+use crate::partial::{ExtraArguments, PartialShape};
+use crate::precedence::PrecedenceTable;
+use crate::reserved::SingleWordGuard;
+use crate::span::Span;
+use crate::token::TokenRules;
+use crate::traversal::Traversal;
+use crate::validation::Validation;
+
+// fields are added between releases, start from ReduceOptions::default() and set the ones needed
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct ReduceOptions {
+    // record time spent in each reduction stage
+    pub profile: bool,
+    // match phrases marked internal
+    pub include_internal: bool,
+    // word separating phrases in a top level list whose results feed into the next phrase
+    // e.g. "then" for "load file then print result"
+    pub chain_word: Option<String>,
+    // used for phrases that don't set their own order
+    pub argument_order: ArgumentOrder,
+    // wrap each argument in a pair keyed by its name from PhraseMeta::argument_name
+    // so the target can take arguments by key instead of position
+    pub named_arguments: bool,
+    pub argument_keys: ArgumentKeys,
+    // output for phrases given fewer arguments than their arity
+    pub partial_shape: PartialShape,
+    // handling of phrases given more arguments than PhraseMeta::max_arguments
+    pub extra_arguments: ExtraArguments,
+    // leave the final word's token as written instead of replacing it with the phrase text
+    // phrases are linked to their words through PhraseMatch::words, targets aren't emitted
+    pub preserve_words: bool,
+    // add an annotation node with the phrase and its span to each apply, see phrase_annotations
+    pub annotate: bool,
+    // leave phrases with missing arguments or a bad argument order as written and keep going
+    // each is recorded in ReduceReport::errors instead of failing the reduction
+    pub collect_errors: bool,
+    // complete a phrase left unterminated at the end of input when only one phrase can finish it
+    // for editors reducing code that is still being typed
+    pub complete_unterminated: bool,
+    // finish a phrase missing only its final word at the end of input when a single word can finish it
+    // e.g. "perform 5" as perform_task, each is reported as ReduceWarning::InferredWord
+    pub infer_final_word: bool,
+    // phrases valid only for this reduction, looked up alongside the context without changing it
+    // where one is the start of the other the longer phrase is matched, meta here is used first
+    pub extra_phrases: SimplePhraseContext,
+    // active version labels, a phrase uses meta of the first one it was registered for
+    // phrases registered for none of them use their unversioned meta
+    pub versions: Vec<String>,
+    pub disambiguation: Disambiguation,
+    // checks each match before it's rewritten, rejected matches are left as written
+    pub validation: Validation,
+    // handling of a phrase word written twice in a row
+    pub duplicate_words: DuplicateWords,
+    // joins the words of identifiers emitted for phrases, e.g. "-" or "::", overrides the context's
+    // the context's separator or underscore when None
+    pub separator: Option<String>,
+    // token type and position of tokens made for phrases
+    pub tokens: TokenRules,
+    // casing of identifiers emitted for phrases without a target, e.g. camelCase to match the linked code
+    pub naming: NamingPolicy,
+    // naming of identifiers generated for phrases without a target
+    pub hygiene: Hygiene,
+    // bounds on words, arguments and nesting of a match, unbounded by default
+    pub limits: MatchLimits,
+    // phrases overlapping any of these are left as written, see no_reduce_regions
+    pub skip_regions: Vec<Span>,
+    // words never matched as single word phrases
+    pub single_word_guard: SingleWordGuard,
+    // alternate form of words tried when the written form doesn't match, e.g. singular for plural
+    pub normalization: Normalization,
+    // match a camelCase or snake_case identifier as its words, e.g. performTask as perform task
+    pub split_identifiers: bool,
+    // a word this many edits from the next word of the phrase in progress is read as that word, 0 disables
+    // each correction is reported as ReduceWarning::Corrected
+    pub typo_distance: usize,
+    // warn about a word that isn't a phrase word but is close to the start of a registered phrase
+    // each is reported as ReduceWarning::NotAPhrase, off by default as every other identifier is checked
+    pub suggest_phrases: bool,
+    // record phrases started but never completed in ReduceReport::unfinished, see LearningReport
+    pub learn: bool,
+    // operators binding looser than phrase words are regrouped around the phrase before matching
+    pub precedence: PrecedenceTable,
+    pub traversal: Traversal,
+}
@@ -0,0 +1,142 @@
+use garnish_lang_compiler::lex::{LexerToken, TokenType};
+use garnish_lang_compiler::parse::{Definition, ParseNode, ParseResult, SecondaryDefinition};
+
+use crate::error::PhraseError;
+use crate::tree::{add_joining_node, replace_child};
+
+// what to emit for a phrase used with fewer arguments than its arity
+#[derive(Debug, Copy, Clone, Default, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum PartialShape {
+    #[default]
+    Error,
+    // { args $ ~> phrase }, missing arguments come from the expression's input
+    Expression,
+    // phrase ~ args, partial apply operator of newer compilers
+    // PhraseError::UnsupportedPartialApply without the compiler-0-0-24 feature
+    PartialApply,
+}
+
+// what to do with a phrase used with more arguments than it takes, see PhraseMeta::max_arguments
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum ExtraArguments {
+    // matched with every argument
+    Allow,
+    // matched like Allow, each is reported as ReduceWarning::ExtraArguments
+    // default so an over applied phrase isn't quietly given a list argument
+    #[default]
+    Warn,
+    // PhraseError::ExtraArguments, recorded in ReduceReport::errors when collecting errors
+    Deny,
+}
+
+// rewrites the apply made for an under applied phrase, returns the new root of the phrase
+pub(crate) fn shape_partial(
+    result: &mut ParseResult,
+    apply_index: usize,
+    phrase_index: usize,
+    has_arguments: bool,
+    shape: PartialShape,
+) -> Result<usize, PhraseError> {
+    match shape {
+        PartialShape::Error => Ok(apply_index),
+        PartialShape::Expression => shape_expression(result, apply_index, phrase_index, has_arguments),
+        #[cfg(feature = "compiler-0-0-24")]
+        PartialShape::PartialApply => shape_partial_apply(result, apply_index, phrase_index, has_arguments),
+        #[cfg(not(feature = "compiler-0-0-24"))]
+        PartialShape::PartialApply => Err(PhraseError::UnsupportedPartialApply),
+    }
+}
+
+fn shape_expression(
+    result: &mut ParseResult,
+    apply_index: usize,
+    phrase_index: usize,
+    has_arguments: bool,
+) -> Result<usize, PhraseError> {
+    let token = result.get_node(apply_index).ok_or(PhraseError::MissingNode(apply_index))?.get_lex_token();
+    let make_token = |text: &str, token_type: TokenType| {
+        LexerToken::new(text.to_string(), token_type, token.get_line(), token.get_column())
+    };
+
+    let value = result.get_nodes().len();
+    result.add_node(ParseNode::new(
+        Definition::Value,
+        SecondaryDefinition::Value,
+        Some(apply_index),
+        None,
+        None,
+        make_token("$", TokenType::Value),
+    ));
+
+    match has_arguments {
+        true => {
+            // input fills the remaining arguments after those given
+            let arguments = result.get_node(apply_index)
+                .and_then(|node| node.get_left())
+                .ok_or(PhraseError::MissingLeft(apply_index))?;
+            let list = add_joining_node(result, Definition::List, arguments, value, " ", TokenType::Whitespace, &token);
+
+            result.get_node_mut(list).ok_or(PhraseError::MissingNode(list))?.set_parent(Some(apply_index));
+            result.get_node_mut(apply_index).ok_or(PhraseError::MissingNode(apply_index))?.set_left(Some(list));
+        }
+        false => {
+            // empty apply becomes $ ~> phrase
+            let node = result.get_node_mut(apply_index).ok_or(PhraseError::MissingNode(apply_index))?;
+            node.set_definition(Definition::ApplyTo);
+            node.set_secondary_definition(SecondaryDefinition::BinaryLeftToRight);
+            node.set_left(Some(value));
+            node.set_right(Some(phrase_index));
+        }
+    }
+
+    let parent = result.get_node(apply_index).ok_or(PhraseError::MissingNode(apply_index))?.get_parent();
+    let expression = result.get_nodes().len();
+    result.add_node(ParseNode::new(
+        Definition::NestedExpression,
+        SecondaryDefinition::StartGrouping,
+        parent,
+        None,
+        Some(apply_index),
+        make_token("{", TokenType::StartExpression),
+    ));
+
+    result.get_node_mut(apply_index).ok_or(PhraseError::MissingNode(apply_index))?.set_parent(Some(expression));
+    replace_child(result, parent, apply_index, expression);
+
+    Ok(expression)
+}
+
+#[cfg(feature = "compiler-0-0-24")]
+fn shape_partial_apply(
+    result: &mut ParseResult,
+    apply_index: usize,
+    phrase_index: usize,
+    has_arguments: bool,
+) -> Result<usize, PhraseError> {
+    match has_arguments {
+        true => {
+            let node = result.get_node_mut(apply_index).ok_or(PhraseError::MissingNode(apply_index))?;
+            let arguments = node.get_left();
+            node.set_definition(Definition::PartialApply);
+            node.set_left(Some(phrase_index));
+            node.set_right(arguments);
+        }
+        false => {
+            // nothing to apply yet, phrase itself takes the apply's place
+            let apply = result.get_node(apply_index).ok_or(PhraseError::MissingNode(apply_index))?;
+            let parent = apply.get_parent();
+            let mut phrase = result.get_node(phrase_index).ok_or(PhraseError::MissingNode(phrase_index))?.clone();
+            phrase.set_parent(parent);
+
+            for child in [phrase.get_left(), phrase.get_right()].into_iter().flatten() {
+                if let Some(child) = result.get_node_mut(child) {
+                    child.set_parent(Some(apply_index));
+                }
+            }
+
+            result.replace_node(apply_index, phrase);
+        }
+    }
+
+    Ok(apply_index)
+}
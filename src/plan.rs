@@ -0,0 +1,211 @@
+use std::collections::BTreeSet;
+
+use garnish_lang_compiler::parse::{Definition, ParseResult};
+use serde::{Deserialize, Serialize};
+
+use crate::compat::node_text;
+use crate::context::{OverlapPolicy, PhraseContext, SimplePhraseContext};
+use crate::dictionary::DictionaryEntry;
+use crate::error::PhraseError;
+use crate::fingerprint::Fingerprinter;
+use crate::options::ReduceOptions;
+use crate::span::Span;
+
+// one phrase match a plan would rewrite, found by its phrase and span so it survives serializing
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PlannedRewrite {
+    pub phrase: String,
+    pub span: Span,
+}
+
+// rewrites found by planning a parse result, applied later to the same parse result
+// carries the meta each phrase had when planned so applying doesn't need the context
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RewritePlan {
+    // fingerprint of the planned parse result, applying to another one fails
+    pub source: u64,
+    pub rewrites: Vec<PlannedRewrite>,
+    // one entry per planned phrase, sorted by phrase
+    pub phrases: Vec<DictionaryEntry>,
+}
+
+impl RewritePlan {
+    // keeps rewrites f returns true for, e.g. to apply only part of a plan
+    pub fn retain(&mut self, f: impl FnMut(&PlannedRewrite) -> bool) {
+        self.rewrites.retain(f);
+        let phrases = self.rewrites.iter().map(|rewrite| rewrite.phrase.as_str()).collect::<BTreeSet<&str>>();
+        self.phrases.retain(|entry| phrases.contains(entry.phrase.as_str()));
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn from_json(input: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(input)
+    }
+
+    pub(crate) fn allows(&self, phrase: &str, span: Span) -> bool {
+        self.rewrites.iter().any(|rewrite| rewrite.phrase == phrase && rewrite.span == span)
+    }
+}
+
+pub fn plan<Context: PhraseContext + ?Sized>(parse_result: &ParseResult, context: &Context) -> Result<RewritePlan, PhraseError> {
+    plan_with_options(parse_result, context, &ReduceOptions::default())
+}
+
+pub fn plan_with_options<Context: PhraseContext + ?Sized>(
+    parse_result: &ParseResult,
+    context: &Context,
+    options: &ReduceOptions,
+) -> Result<RewritePlan, PhraseError> {
+    let (_, report) = crate::reduce_phrases_with_options(parse_result, context, options)?;
+
+    let mut phrases: Vec<DictionaryEntry> = vec![];
+    for m in &report.matches {
+        if !phrases.iter().any(|entry| entry.phrase == m.phrase) {
+            phrases.push(DictionaryEntry::with_meta(&m.phrase, m.meta.clone().unwrap_or_default()));
+        }
+    }
+    phrases.sort_by(|first, second| first.phrase.cmp(&second.phrase));
+
+    Ok(RewritePlan {
+        source: fingerprint(parse_result),
+        rewrites: report.matches.iter().map(|m| PlannedRewrite { phrase: m.phrase.clone(), span: m.span }).collect(),
+        phrases,
+    })
+}
+
+pub fn apply(plan: &RewritePlan, parse_result: &ParseResult) -> Result<ParseResult, PhraseError> {
+    apply_with_options(plan, parse_result, &ReduceOptions::default())
+}
+
+// options should be the ones planned with, phrases and their meta come from the plan instead
+// only planned rewrites are made, other matches are left as written
+pub fn apply_with_options(plan: &RewritePlan, parse_result: &ParseResult, options: &ReduceOptions) -> Result<ParseResult, PhraseError> {
+    let found = fingerprint(parse_result);
+    if found != plan.source {
+        return Err(PhraseError::PlanMismatch { planned: plan.source, found });
+    }
+
+    let mut context = SimplePhraseContext::new();
+    context.set_overlap_policy(OverlapPolicy::AllowCompletePrefix);
+    for entry in &plan.phrases {
+        context.try_add_phrase_with_meta(&entry.phrase, entry.meta())?;
+    }
+
+    // meta was resolved when planning
+    let options = ReduceOptions {
+        include_internal: true,
+        extra_phrases: SimplePhraseContext::new(),
+        versions: vec![],
+        ..options.clone()
+    };
+
+    crate::reduce_planned(parse_result, &context, &options, plan).map(|(result, _)| result)
+}
+
+// fixed code for each definition the matcher reads or writes, so the fingerprint doesn't depend on the compiler's Debug output
+// the rest share a code and are told apart by their written token, e.g. + from -
+fn definition_code(definition: Definition) -> u8 {
+    match definition {
+        Definition::Identifier => 1,
+        Definition::Property => 2,
+        Definition::Number => 3,
+        Definition::Value => 4,
+        Definition::List => 5,
+        Definition::CommaList => 6,
+        Definition::Apply => 7,
+        Definition::ApplyTo => 8,
+        Definition::EmptyApply => 9,
+        Definition::Access => 10,
+        Definition::Pair => 11,
+        Definition::Group => 12,
+        Definition::NestedExpression => 13,
+        Definition::Subexpression => 14,
+        Definition::Drop => 15,
+        _ => 0,
+    }
+}
+
+// stable over the tree's structure and tokens
+fn fingerprint(parse_result: &ParseResult) -> u64 {
+    let mut fingerprinter = Fingerprinter::new();
+    fingerprinter.write_str(&parse_result.get_root().to_string());
+
+    for node in parse_result.get_nodes() {
+        let token = node.get_lex_token();
+        let links = [node.get_parent(), node.get_left(), node.get_right()].map(|link| link.map(|index| index.to_string()).unwrap_or_default());
+
+        fingerprinter.write_u8(definition_code(node.get_definition()));
+        fingerprinter.write_str(&node_text(node));
+        fingerprinter.write_str(&format!("{}:{}", token.get_line(), token.get_column()));
+        for link in links {
+            fingerprinter.write_str(&link);
+        }
+    }
+
+    fingerprinter.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use garnish_lang_compiler::lex::lex;
+    use garnish_lang_compiler::parse::parse;
+
+    use crate::compat::node_text;
+    use crate::context::SimplePhraseContext;
+    use crate::error::PhraseError;
+    use crate::plan::{apply, plan, RewritePlan};
+    use crate::reduce_phrases;
+
+    fn context() -> SimplePhraseContext {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrases(["perform_task", "stop"]).unwrap();
+        context
+    }
+
+    #[test]
+    fn applied_plan_same_as_reducing() {
+        let parsed = parse(&lex("perform 5 task").unwrap()).unwrap();
+
+        let plan = plan(&parsed, &context()).unwrap();
+
+        assert_eq!(plan.rewrites.len(), 1);
+        assert_eq!(apply(&plan, &parsed).unwrap(), reduce_phrases(&parsed, &context()).unwrap());
+    }
+
+    #[test]
+    fn filtered_plan_applied() {
+        let parsed = parse(&lex("perform (halt now) task").unwrap()).unwrap();
+        let mut context = context();
+        context.add_phrase("halt_now").unwrap();
+
+        let mut plan = plan(&parsed, &context).unwrap();
+        plan.retain(|rewrite| rewrite.phrase == "halt_now");
+
+        let applied = apply(&plan, &parsed).unwrap();
+        let words = applied.get_nodes().iter().map(node_text).collect::<Vec<String>>();
+
+        assert_eq!(plan.phrases.len(), 1);
+        assert!(words.contains(&"halt_now".to_string()));
+        assert!(!words.contains(&"perform_task".to_string()));
+    }
+
+    #[test]
+    fn plan_round_trips_through_json() {
+        let parsed = parse(&lex("perform 5 task").unwrap()).unwrap();
+        let plan = plan(&parsed, &context()).unwrap();
+
+        assert_eq!(RewritePlan::from_json(&plan.to_json().unwrap()).unwrap(), plan);
+    }
+
+    #[test]
+    fn plan_for_other_source_rejected() {
+        let parsed = parse(&lex("perform 5 task").unwrap()).unwrap();
+        let other = parse(&lex("perform 6 task").unwrap()).unwrap();
+        let plan = plan(&parsed, &context()).unwrap();
+
+        assert!(matches!(apply(&plan, &other), Err(PhraseError::PlanMismatch { .. })));
+    }
+}
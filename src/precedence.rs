@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+
+use garnish_lang_compiler::lex::TokenType;
+use garnish_lang_compiler::parse::{Definition, ParseResult, SecondaryDefinition};
+
+use crate::compat::node_text;
+use crate::tree::{add_joining_node, replace_child};
+
+// levels of binary operators relative to phrase words
+// operators below the phrase level bind looser than phrase words, so "perform 2 task * 3" is (perform 2 task) * 3
+// and "perform 2 * 3 task" captures just 2, the product applying to the phrase's result
+// operators without a level bind tighter, as the parser groups them, and are captured whole
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct PrecedenceTable {
+    pub phrase_level: u32,
+    pub levels: HashMap<Definition, u32>,
+}
+
+impl PrecedenceTable {
+    pub fn new(phrase_level: u32) -> Self {
+        PrecedenceTable { phrase_level, levels: HashMap::new() }
+    }
+
+    pub fn with_level(mut self, operator: Definition, level: u32) -> Self {
+        self.levels.insert(operator, level);
+        self
+    }
+
+    pub fn level(&self, operator: Definition) -> Option<u32> {
+        self.levels.get(&operator).copied()
+    }
+
+    pub fn binds_looser(&self, operator: Definition) -> bool {
+        self.level(operator).is_some_and(|level| level < self.phrase_level)
+    }
+}
+
+// lifts operators binding looser than phrase words out of whitespace lists containing a phrase word
+// None when nothing was lifted
+pub(crate) fn regroup(parse_result: &ParseResult, table: &PrecedenceTable, is_phrase_word: impl Fn(&str) -> bool) -> Option<ParseResult> {
+    if !table.levels.values().any(|level| *level < table.phrase_level) {
+        return None;
+    }
+
+    let mut result = parse_result.clone();
+    let mut changed = false;
+
+    // each lift moves an operator above a list, bounded by the node count in case of a malformed tree
+    for _ in 0..parse_result.get_nodes().len() {
+        match find_lift(&result, table, &is_phrase_word) {
+            None => break,
+            Some((list, items, position)) => {
+                lift(&mut result, list, &items, position)?;
+                changed = true;
+            }
+        }
+    }
+
+    changed.then_some(result)
+}
+
+// first list with a phrase word and a looser operator item, its items and the operator's position
+fn find_lift(result: &ParseResult, table: &PrecedenceTable, is_phrase_word: &impl Fn(&str) -> bool) -> Option<(usize, Vec<usize>, usize)> {
+    let mut stack = vec![result.get_root()];
+
+    while let Some(index) = stack.pop() {
+        let node = result.get_node(index)?;
+        stack.extend(node.get_right());
+        stack.extend(node.get_left());
+
+        let is_list_top = node.get_definition() == Definition::List
+            && !node.get_parent().and_then(|parent| result.get_node(parent))
+                .is_some_and(|parent| parent.get_definition() == Definition::List && parent.get_left() == Some(index));
+        if !is_list_top {
+            continue;
+        }
+
+        let items = list_items(result, index)?;
+        let position = match items.iter().position(|item| is_looser_operator(result, table, *item)) {
+            None => continue,
+            Some(position) => position,
+        };
+
+        // operand that joins the list, the left one unless the operator is the first item
+        let operator = result.get_node(items[position])?;
+        let joining = match position {
+            0 => operator.get_right()?,
+            _ => operator.get_left()?,
+        };
+
+        let has_phrase_word = items.iter()
+            .filter(|item| **item != items[position])
+            .chain(std::iter::once(&joining))
+            .filter_map(|item| result.get_node(*item))
+            .any(|node| node.get_definition() == Definition::Identifier && is_phrase_word(&node_text(node)));
+
+        if has_phrase_word {
+            return Some((index, items, position));
+        }
+    }
+
+    None
+}
+
+fn is_looser_operator(result: &ParseResult, table: &PrecedenceTable, index: usize) -> bool {
+    result.get_node(index).is_some_and(|node| {
+        matches!(node.get_secondary_definition(), SecondaryDefinition::BinaryLeftToRight | SecondaryDefinition::BinaryRightToLeft)
+            && node.get_left().is_some()
+            && node.get_right().is_some()
+            && table.binds_looser(node.get_definition())
+    })
+}
+
+// items of the left leaning list at index, in source order
+fn list_items(result: &ParseResult, index: usize) -> Option<Vec<usize>> {
+    let mut current = index;
+    let mut items = vec![];
+
+    loop {
+        let node = result.get_node(current)?;
+        if node.get_definition() != Definition::List {
+            items.push(current);
+            break;
+        }
+
+        items.push(node.get_right()?);
+        current = node.get_left()?;
+    }
+
+    items.reverse();
+    Some(items)
+}
+
+// rebuilds the list without the operator at position, then puts the operator above it
+// the operand next to the other items joins the list, the operator keeps the other one
+fn lift(result: &mut ParseResult, list: usize, items: &[usize], position: usize) -> Option<()> {
+    let operator = items[position];
+    let parent = result.get_node(list)?.get_parent();
+    let operator_node = result.get_node(operator)?;
+    let (left, right) = (operator_node.get_left()?, operator_node.get_right()?);
+
+    let mut new_items = items.to_vec();
+    new_items[position] = match position {
+        0 => right,
+        _ => left,
+    };
+
+    let mut root = new_items[0];
+    for item in new_items.iter().skip(1) {
+        let token = result.get_node(*item)?.get_lex_token();
+        root = add_joining_node(result, Definition::List, root, *item, " ", TokenType::Whitespace, &token);
+    }
+
+    result.get_node_mut(root)?.set_parent(Some(operator));
+    let operator_node = result.get_node_mut(operator)?;
+    operator_node.set_parent(parent);
+    match position {
+        0 => operator_node.set_right(Some(root)),
+        _ => operator_node.set_left(Some(root)),
+    }
+    replace_child(result, parent, list, operator);
+
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use garnish_lang_compiler::parse::Definition;
+
+    use crate::precedence::PrecedenceTable;
+
+    #[test]
+    fn operators_below_phrase_level_bind_looser() {
+        let table = PrecedenceTable::new(5)
+            .with_level(Definition::Addition, 2)
+            .with_level(Definition::MultiplicationSign, 8);
+
+        assert!(table.binds_looser(Definition::Addition));
+        assert!(!table.binds_looser(Definition::MultiplicationSign));
+        assert!(!table.binds_looser(Definition::Subtraction));
+    }
+}
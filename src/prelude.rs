@@ -0,0 +1,42 @@
+// curated items most callers need: reducing with a context and options and reading the report
+// removing or changing any of them requires a breaking version bump
+// options, meta, errors, warnings and the report are non exhaustive so adding to them isn't breaking
+// everything else is exported from the crate root and may be reorganized between releases
+
+#[cfg(feature = "full")]
+pub use crate::compiler::{lex, parse, ParseResult};
+pub use crate::context::{ContextError, PhraseContext, PhraseStatus, SimpleContextCodes, SimplePhraseContext};
+pub use crate::dictionary::{Dictionary, DictionaryEntry, DictionaryError};
+pub use crate::error::PhraseError;
+pub use crate::ext::ParseResultExt;
+pub use crate::meta::{ApplyForm, ArgumentOrder, PhraseMeta, Visibility};
+pub use crate::options::ReduceOptions;
+pub use crate::profile::ReduceProfile;
+pub use crate::project::PhraseProject;
+pub use crate::report::{PhraseMatch, ReduceError, ReduceReport, ReduceWarning};
+pub use crate::span::{Position, Span};
+#[cfg(feature = "watch")]
+pub use crate::watch::{DictionaryWatcher, SwappableContext};
+pub use crate::{reduce_phrases, reduce_phrases_at, reduce_phrases_at_with_options, reduce_phrases_with_options};
+
+#[cfg(test)]
+mod tests {
+    use garnish_lang_compiler::lex::lex;
+    use garnish_lang_compiler::parse::parse;
+
+    use crate::prelude::*;
+
+    #[test]
+    fn reduce_with_prelude_only() {
+        let tokens = lex("perform 5 task").unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+
+        let (reduced, report): (_, ReduceReport) = reduce_phrases_with_options(&parsed, &context, &ReduceOptions::default()).unwrap();
+
+        assert_eq!(reduced, reduce_phrases(&parsed, &context).unwrap());
+        assert_eq!(report.profile, None);
+    }
+}
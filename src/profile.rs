@@ -0,0 +1,101 @@
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct ReduceProfile {
+    pub traversal: Duration,
+    pub context_lookup: Duration,
+    pub mutation: Duration,
+    pub context_lookups: usize,
+    pub mutations: usize,
+}
+
+impl ReduceProfile {
+    pub fn total(&self) -> Duration {
+        self.traversal + self.context_lookup + self.mutation
+    }
+
+    pub(crate) fn add(&mut self, other: &ReduceProfile) {
+        self.traversal += other.traversal;
+        self.context_lookup += other.context_lookup;
+        self.mutation += other.mutation;
+        self.context_lookups += other.context_lookups;
+        self.mutations += other.mutations;
+    }
+}
+
+// collects timings only when enabled so default reductions
+// don't pay for calls to Instant::now
+pub(crate) struct Profiler {
+    profile: Option<ReduceProfile>,
+}
+
+impl Profiler {
+    pub fn new(enabled: bool) -> Self {
+        Profiler { profile: if enabled { Some(ReduceProfile::default()) } else { None } }
+    }
+
+    pub fn traversal<T>(&mut self, f: impl FnOnce() -> T) -> T {
+        self.measure(f, |profile, elapsed| {
+            profile.traversal += elapsed;
+        })
+    }
+
+    pub fn lookup<T>(&mut self, f: impl FnOnce() -> T) -> T {
+        self.measure(f, |profile, elapsed| {
+            profile.context_lookup += elapsed;
+            profile.context_lookups += 1;
+        })
+    }
+
+    pub fn mutation<T>(&mut self, f: impl FnOnce() -> T) -> T {
+        self.measure(f, |profile, elapsed| {
+            profile.mutation += elapsed;
+            profile.mutations += 1;
+        })
+    }
+
+    pub fn finish(self) -> Option<ReduceProfile> {
+        self.profile
+    }
+
+    fn measure<T>(&mut self, f: impl FnOnce() -> T, record: impl FnOnce(&mut ReduceProfile, Duration)) -> T {
+        match self.profile.as_mut() {
+            None => f(),
+            Some(profile) => {
+                let start = Instant::now();
+                let value = f();
+                record(profile, start.elapsed());
+                value
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::profile::Profiler;
+
+    #[test]
+    fn disabled_records_nothing() {
+        let mut profiler = Profiler::new(false);
+        let value = profiler.lookup(|| 10);
+
+        assert_eq!(value, 10);
+        assert_eq!(profiler.finish(), None);
+    }
+
+    #[test]
+    fn enabled_counts_lookups_and_mutations() {
+        let mut profiler = Profiler::new(true);
+        profiler.lookup(|| ());
+        profiler.lookup(|| ());
+        profiler.mutation(|| ());
+        profiler.traversal(|| ());
+
+        let profile = profiler.finish().unwrap();
+
+        assert_eq!(profile.context_lookups, 2);
+        assert_eq!(profile.mutations, 1);
+        assert_eq!(profile.total(), profile.traversal + profile.context_lookup + profile.mutation);
+    }
+}
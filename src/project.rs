@@ -0,0 +1,312 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+use garnish_lang_compiler::lex::lex;
+use garnish_lang_compiler::parse::{parse, ParseResult};
+
+use crate::cache::CachingPhraseContext;
+use crate::context::PhraseContext;
+use crate::corpus::CorpusStats;
+use crate::error::PhraseError;
+use crate::options::ReduceOptions;
+use crate::reduce_phrases_with_options;
+use crate::report::{PhraseMatch, ReduceError, ReduceReport, ReduceWarning};
+
+// why a source has no reduced result
+#[derive(Debug)]
+pub enum SourceError {
+    Lex(String),
+    Parse(String),
+    Reduce(PhraseError),
+}
+
+impl Display for SourceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SourceError::Lex(message) => write!(f, "could not lex: {}", message),
+            SourceError::Parse(message) => write!(f, "could not parse: {}", message),
+            SourceError::Reduce(error) => write!(f, "could not reduce: {}", error),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ProjectDiagnostic<'a> {
+    Source { source: &'a str, error: &'a SourceError },
+    Warning { source: &'a str, warning: &'a ReduceWarning },
+    Error { source: &'a str, error: &'a ReduceError },
+}
+
+impl Display for ProjectDiagnostic<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProjectDiagnostic::Source { source, error } => write!(f, "{}: {}", source, error),
+            ProjectDiagnostic::Warning { source, warning } => write!(f, "{}: warning: {}", source, warning),
+            ProjectDiagnostic::Error { source, error } => write!(f, "{}: error: {}", source, error),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Source {
+    text: String,
+    parsed: Option<ParseResult>,
+    outcome: Result<(ParseResult, ReduceReport), SourceError>,
+}
+
+// named sources reduced against one cached context with the same options
+// for language servers and build tools that keep many files reduced at once
+#[derive(Debug)]
+pub struct PhraseProject<Context: PhraseContext> {
+    context: CachingPhraseContext<Context>,
+    options: ReduceOptions,
+    sources: BTreeMap<String, Source>,
+}
+
+impl<Context: PhraseContext> PhraseProject<Context> {
+    pub fn new(context: Context, options: ReduceOptions) -> Self {
+        PhraseProject { context: CachingPhraseContext::new(context), options, sources: BTreeMap::new() }
+    }
+
+    pub fn context(&self) -> &CachingPhraseContext<Context> {
+        &self.context
+    }
+
+    pub fn options(&self) -> &ReduceOptions {
+        &self.options
+    }
+
+    // replaces the context and reduces every source again
+    pub fn set_context(&mut self, context: Context) {
+        self.context = CachingPhraseContext::new(context);
+        self.reduce_all();
+    }
+
+    pub fn set_options(&mut self, options: ReduceOptions) {
+        self.options = options;
+        self.reduce_all();
+    }
+
+    // reduces every source again if the context changed since it was last checked, returns whether it did
+    // changed is decided by the context's fingerprint, which covers meta and aliases as well as phrases
+    pub fn refresh(&mut self) -> bool {
+        let changed = self.context.validate();
+        if changed {
+            self.reduce_all();
+        }
+
+        changed
+    }
+
+    // adds or replaces source, reducing it immediately
+    pub fn set_source(&mut self, name: impl Into<String>, text: impl Into<String>) -> Result<&ReduceReport, &SourceError> {
+        let name = name.into();
+        let text = text.into();
+        let source = self.reduce_source(text);
+        self.sources.insert(name.clone(), source);
+
+        self.sources[&name].outcome.as_ref().map(|(_, report)| report)
+    }
+
+    pub fn remove_source(&mut self, name: &str) -> bool {
+        self.sources.remove(name).is_some()
+    }
+
+    // sorted
+    pub fn source_names(&self) -> impl Iterator<Item = &str> {
+        self.sources.keys().map(String::as_str)
+    }
+
+    pub fn text(&self, name: &str) -> Option<&str> {
+        self.sources.get(name).map(|source| source.text.as_str())
+    }
+
+    pub fn reduced(&self, name: &str) -> Option<&ParseResult> {
+        self.sources.get(name).and_then(|source| source.outcome.as_ref().ok()).map(|(reduced, _)| reduced)
+    }
+
+    pub fn report(&self, name: &str) -> Option<&ReduceReport> {
+        self.sources.get(name).and_then(|source| source.outcome.as_ref().ok()).map(|(_, report)| report)
+    }
+
+    pub fn error(&self, name: &str) -> Option<&SourceError> {
+        self.sources.get(name).and_then(|source| source.outcome.as_ref().err())
+    }
+
+    // every match of phrase paired with its source, sorted by source then position
+    pub fn usages(&self, phrase: &str) -> Vec<(&str, &PhraseMatch)> {
+        self.reports()
+            .flat_map(|(name, report)| report.matches.iter().filter(|m| m.phrase == phrase).map(move |m| (name, m)))
+            .collect()
+    }
+
+    // sources matching each phrase, sorted by phrase then source
+    pub fn usage_index(&self) -> BTreeMap<&str, Vec<&str>> {
+        let mut index: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (name, report) in self.reports() {
+            for m in &report.matches {
+                let sources = index.entry(m.phrase.as_str()).or_default();
+                if sources.last() != Some(&name) {
+                    sources.push(name);
+                }
+            }
+        }
+
+        index
+    }
+
+    // failed sources, warnings and collected errors of every source, sorted by source
+    pub fn diagnostics(&self) -> Vec<ProjectDiagnostic<'_>> {
+        let mut diagnostics = vec![];
+        for (name, source) in &self.sources {
+            match &source.outcome {
+                Err(error) => diagnostics.push(ProjectDiagnostic::Source { source: name, error }),
+                Ok((_, report)) => {
+                    diagnostics.extend(report.warnings.iter().map(|warning| ProjectDiagnostic::Warning { source: name, warning }));
+                    diagnostics.extend(report.errors.iter().map(|error| ProjectDiagnostic::Error { source: name, error }));
+                }
+            }
+        }
+
+        diagnostics
+    }
+
+    pub fn stats(&self) -> CorpusStats {
+        let mut stats = CorpusStats::new();
+        for (name, source) in &self.sources {
+            if let (Some(parsed), Ok((_, report))) = (&source.parsed, &source.outcome) {
+                stats.add(name.as_str(), parsed, report);
+            }
+        }
+
+        stats
+    }
+
+    fn reports(&self) -> impl Iterator<Item = (&str, &ReduceReport)> {
+        self.sources.iter().filter_map(|(name, source)| source.outcome.as_ref().ok().map(|(_, report)| (name.as_str(), report)))
+    }
+
+    fn reduce_all(&mut self) {
+        let sources = std::mem::take(&mut self.sources);
+        self.sources = sources.into_iter().map(|(name, source)| (name, self.reduce_source(source.text))).collect();
+    }
+
+    fn reduce_source(&self, text: String) -> Source {
+        let parsed = lex(&text)
+            .map_err(|error| SourceError::Lex(error.get_message().clone()))
+            .and_then(|tokens| parse(&tokens).map_err(|error| SourceError::Parse(error.get_message().clone())));
+
+        match parsed {
+            Err(error) => Source { text, parsed: None, outcome: Err(error) },
+            Ok(parsed) => {
+                let outcome = reduce_phrases_with_options(&parsed, &self.context, &self.options).map_err(SourceError::Reduce);
+                Source { text, parsed: Some(parsed), outcome }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::SimplePhraseContext;
+    use crate::meta::PhraseMeta;
+    use crate::options::ReduceOptions;
+    use crate::project::{PhraseProject, SourceError};
+
+    fn project() -> PhraseProject<SimplePhraseContext> {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrases(["perform_task", "stop"]).unwrap();
+        context.add_phrase_with_meta("halt_now", PhraseMeta::new().with_deprecation(None)).unwrap();
+
+        PhraseProject::new(context, ReduceOptions::default())
+    }
+
+    #[test]
+    fn sources_reduced_when_set() {
+        let mut project = project();
+
+        let report = project.set_source("a.garnish", "perform 5 task").unwrap();
+        assert_eq!(report.matches[0].phrase, "perform_task");
+
+        project.set_source("b.garnish", "stop").unwrap();
+        project.set_source("a.garnish", "value").unwrap();
+
+        assert_eq!(project.source_names().collect::<Vec<&str>>(), vec!["a.garnish", "b.garnish"]);
+        assert!(project.report("a.garnish").unwrap().matches.is_empty());
+        assert_eq!(project.text("a.garnish"), Some("value"));
+    }
+
+    #[test]
+    fn usage_index_across_sources() {
+        let mut project = project();
+        project.set_source("b.garnish", "perform 10 task").unwrap();
+        project.set_source("a.garnish", "perform 5 task").unwrap();
+        project.set_source("c.garnish", "stop").unwrap();
+
+        let index = project.usage_index();
+
+        assert_eq!(index.get("perform_task"), Some(&vec!["a.garnish", "b.garnish"]));
+        assert_eq!(index.get("stop"), Some(&vec!["c.garnish"]));
+        assert_eq!(project.usages("perform_task").len(), 2);
+        assert_eq!(project.stats().phrase_counts().get("perform_task"), Some(&2));
+    }
+
+    #[test]
+    fn diagnostics_of_all_sources() {
+        let mut project = project();
+        project.set_source("a.garnish", "halt now").unwrap();
+        assert!(matches!(project.set_source("b.garnish", "5 +"), Err(SourceError::Parse(_))));
+
+        let diagnostics = project.diagnostics().iter().map(|diagnostic| diagnostic.to_string()).collect::<Vec<String>>();
+
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics[0].starts_with("a.garnish: warning: phrase 'halt_now'"));
+        assert!(diagnostics[1].starts_with("b.garnish: could not parse"));
+    }
+
+    #[test]
+    fn new_context_reduces_again() {
+        let mut project = project();
+        project.set_source("a.garnish", "load file").unwrap();
+        assert!(project.report("a.garnish").unwrap().matches.is_empty());
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("load_file").unwrap();
+        project.set_context(context);
+
+        assert_eq!(project.report("a.garnish").unwrap().matches[0].phrase, "load_file");
+    }
+
+    #[cfg(feature = "watch")]
+    #[test]
+    fn refresh_after_meta_or_alias_swap() {
+        use crate::watch::SwappableContext;
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrases(["start_job", "stop_job"]).unwrap();
+        let swappable = SwappableContext::new(context.clone());
+        let mut project = PhraseProject::new(swappable.clone(), ReduceOptions::default());
+        project.set_source("a.garnish", "start job").unwrap();
+        assert!(!project.refresh());
+
+        let mut targeted = context.clone();
+        targeted.add_phrase_with_meta("start_job", PhraseMeta::new().with_target("begin")).unwrap();
+        swappable.swap(targeted);
+        let before = project.reduced("a.garnish").unwrap().clone();
+        assert!(project.refresh());
+        assert_ne!(project.reduced("a.garnish").unwrap(), &before);
+
+        project.set_source("b.garnish", "kick off job").unwrap();
+        let mut aliased = context.clone();
+        aliased.add_alias("kick_off_job", "start_job").unwrap();
+        swappable.swap(aliased);
+        assert!(project.refresh());
+        assert_eq!(project.report("b.garnish").unwrap().matches[0].phrase, "start_job");
+
+        let mut realiased = context;
+        realiased.add_alias("kick_off_job", "stop_job").unwrap();
+        swappable.swap(realiased);
+        assert!(project.refresh());
+        assert_eq!(project.report("b.garnish").unwrap().matches[0].phrase, "stop_job");
+    }
+}
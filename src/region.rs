@@ -0,0 +1,55 @@
+use garnish_lang_compiler::lex::{LexerToken, TokenType};
+
+use crate::span::{Position, Span};
+
+// annotations marking source phrases aren't reduced in, garnish's parser drops annotations
+// so they only need to be found in the tokens, e.g.
+// @no_phrases
+// perform 5 task
+// @phrases
+pub const REGION_START: &str = "@no_phrases";
+pub const REGION_END: &str = "@phrases";
+
+// regions between start and end markers, for ReduceOptions::skip_regions
+// a region without an end marker runs to the end of input, end markers outside a region are ignored
+pub fn no_reduce_regions(tokens: &[LexerToken]) -> Vec<Span> {
+    let mut regions = vec![];
+    let mut start = None;
+
+    for token in tokens.iter().filter(|token| token.get_token_type() == TokenType::Annotation) {
+        let position = Position { line: token.get_line(), column: token.get_column() };
+        match (token.get_text().as_str(), start) {
+            (REGION_START, None) => start = Some(position),
+            (REGION_END, Some(region_start)) => {
+                regions.push(Span { start: region_start, end: position });
+                start = None;
+            }
+            _ => (),
+        }
+    }
+
+    if let (Some(start), Some(last)) = (start, tokens.last()) {
+        let end = Position { line: last.get_line(), column: last.get_column() + last.get_text().chars().count() };
+        regions.push(Span { start, end });
+    }
+
+    regions
+}
+
+#[cfg(test)]
+mod tests {
+    use garnish_lang_compiler::lex::lex;
+
+    use crate::region::no_reduce_regions;
+    use crate::span::{Position, Span};
+
+    #[test]
+    fn regions_between_markers() {
+        let tokens = lex("@no_phrases perform 5 task @phrases halt now @no_phrases stop").unwrap();
+
+        assert_eq!(no_reduce_regions(&tokens), vec![
+            Span { start: Position { line: 0, column: 0 }, end: Position { line: 0, column: 27 } },
+            Span { start: Position { line: 0, column: 45 }, end: Position { line: 0, column: 61 } },
+        ]);
+    }
+}
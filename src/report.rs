@@ -0,0 +1,395 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+use crate::context::SimplePhraseContext;
+use crate::meta::{PhraseMeta, PhraseSource};
+use crate::profile::ReduceProfile;
+use crate::span::Span;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PhraseMatch {
+    // joined text of the phrase that was matched
+    pub phrase: String,
+    // index of the node that replaced the phrase in the reduced result
+    pub node: usize,
+    // first word through last word in the original source
+    pub span: Span,
+    pub meta: Option<PhraseMeta>,
+    // word nodes of the phrase in source order, indices valid in the reduced result
+    pub words: Vec<usize>,
+    // phrase was unterminated at the end of input and completed, its last word node was synthesized
+    pub recovered: bool,
+    // precompiled expression the phrase dispatches to, only set by reduce_phrases_with_expressions
+    pub expression: Option<usize>,
+}
+
+impl PhraseMatch {
+    // same match with node indices moved by offset
+    pub(crate) fn offset(self, offset: usize) -> Self {
+        self.map_nodes(|node| node + offset)
+    }
+
+    // same match with each node index replaced by where it was placed
+    pub(crate) fn map_nodes(self, place: impl Fn(usize) -> usize) -> Self {
+        PhraseMatch {
+            node: place(self.node),
+            words: self.words.iter().map(|word| place(*word)).collect(),
+            ..self
+        }
+    }
+
+    pub fn doc(&self) -> Option<&str> {
+        self.meta.as_ref().and_then(|meta| meta.doc.as_deref())
+    }
+
+    pub fn source(&self) -> Option<&PhraseSource> {
+        self.meta.as_ref().and_then(|meta| meta.source.as_ref())
+    }
+
+    // first word through last word as a byte range of the source that was reduced
+    pub fn byte_range(&self, source: &str) -> Option<Range<usize>> {
+        self.span.byte_range(source)
+    }
+
+    pub fn examples(&self) -> &[String] {
+        match &self.meta {
+            None => &[],
+            Some(meta) => &meta.examples,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ReduceWarning {
+    Deprecated {
+        phrase: String,
+        replacement: Option<String>,
+        span: Span,
+        source: Option<PhraseSource>,
+    },
+    // phrases competing for the same words had the same weight, the structural preference was used
+    TiedWeights {
+        phrases: Vec<String>,
+        span: Span,
+    },
+    // misspelled word read as the phrase word it was closest to
+    Corrected {
+        written: String,
+        corrected: String,
+        span: Span,
+    },
+    // identifier generated for the phrase is already used by the program
+    NameCollision {
+        phrase: String,
+        name: String,
+        span: Span,
+    },
+    // phrase was missing its final word at the end of input, the only word that could finish it was assumed
+    InferredWord {
+        phrase: String,
+        word: String,
+        span: Span,
+    },
+    // phrase word written twice in a row, see DuplicateWords
+    DuplicateWord {
+        word: String,
+        span: Span,
+    },
+    // expression table given to reduce_phrases_with_expressions has no index for the phrase
+    MissingExpression {
+        phrase: String,
+        span: Span,
+    },
+    // phrase was given more arguments than it takes, see ExtraArguments
+    ExtraArguments {
+        phrase: String,
+        expected: usize,
+        found: usize,
+        span: Span,
+    },
+    // phrase still in progress at the end of input, the same phrase is in ReduceReport::unfinished when learning
+    Unfinished {
+        phrase: UnfinishedPhrase,
+    },
+    // word is no phrase's word but close to the first word of registered phrases, e.g. a misspelling
+    NotAPhrase {
+        word: String,
+        span: Span,
+        suggestions: Vec<String>,
+    },
+}
+
+impl Display for ReduceWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReduceWarning::Deprecated { phrase, replacement, span, source } => {
+                write!(f, "phrase '{}' at {}:{} is deprecated", phrase, span.start.line + 1, span.start.column + 1)?;
+                if let Some(source) = source {
+                    write!(f, " by {}", source)?;
+                }
+                match replacement {
+                    None => Ok(()),
+                    Some(replacement) => write!(f, ", use '{}' instead", replacement),
+                }
+            }
+            ReduceWarning::TiedWeights { phrases, span } => write!(
+                f,
+                "phrases {} at {}:{} have the same weight",
+                phrases.iter().map(|phrase| format!("'{}'", phrase)).collect::<Vec<String>>().join(", "),
+                span.start.line + 1,
+                span.start.column + 1
+            ),
+            ReduceWarning::Corrected { written, corrected, span } => write!(
+                f,
+                "word '{}' at {}:{} was read as '{}'",
+                written,
+                span.start.line + 1,
+                span.start.column + 1,
+                corrected
+            ),
+            ReduceWarning::NameCollision { phrase, name, span } => write!(
+                f,
+                "phrase '{}' at {}:{} generates '{}' which is already used",
+                phrase,
+                span.start.line + 1,
+                span.start.column + 1,
+                name
+            ),
+            ReduceWarning::InferredWord { phrase, word, span } => write!(
+                f,
+                "phrase '{}' at {}:{} is missing its final word, '{}' was assumed",
+                phrase,
+                span.start.line + 1,
+                span.start.column + 1,
+                word
+            ),
+            ReduceWarning::DuplicateWord { word, span } => write!(
+                f,
+                "word '{}' at {}:{} is repeated",
+                word,
+                span.start.line + 1,
+                span.start.column + 1
+            ),
+            ReduceWarning::MissingExpression { phrase, span } => write!(
+                f,
+                "phrase '{}' at {}:{} has no expression to dispatch to",
+                phrase,
+                span.start.line + 1,
+                span.start.column + 1
+            ),
+            ReduceWarning::ExtraArguments { phrase, expected, found, span } => write!(
+                f,
+                "phrase '{}' at {}:{} takes at most {} argument(s) but was given {}",
+                phrase,
+                span.start.line + 1,
+                span.start.column + 1,
+                expected,
+                found
+            ),
+            ReduceWarning::Unfinished { phrase } => phrase.fmt(f),
+            ReduceWarning::NotAPhrase { word, span, suggestions } => {
+                write!(f, "word '{}' at {}:{} isn't a phrase word", word, span.start.line + 1, span.start.column + 1)?;
+                write_suggestions(f, suggestions)
+            }
+        }
+    }
+}
+
+// problem reduction continued past in collect errors mode, the phrase was left as written
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ReduceError {
+    MissingArguments { phrase: String, expected: usize, found: usize, span: Span },
+    ExtraArguments { phrase: String, expected: usize, found: usize, span: Span },
+    ArgumentOrder { phrase: String, arguments: usize, span: Span },
+    TemplateMismatch { phrase: String, template: String, found: String, span: Span },
+}
+
+impl ReduceError {
+    pub fn span(&self) -> Span {
+        match self {
+            ReduceError::MissingArguments { span, .. }
+            | ReduceError::ExtraArguments { span, .. }
+            | ReduceError::ArgumentOrder { span, .. }
+            | ReduceError::TemplateMismatch { span, .. } => *span,
+        }
+    }
+}
+
+impl Display for ReduceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let span = self.span();
+        match self {
+            ReduceError::MissingArguments { phrase, expected, found, .. } => write!(
+                f,
+                "phrase '{}' at {}:{} expects {} argument(s) but was given {}",
+                phrase, span.start.line + 1, span.start.column + 1, expected, found
+            ),
+            ReduceError::ExtraArguments { phrase, expected, found, .. } => write!(
+                f,
+                "phrase '{}' at {}:{} takes at most {} argument(s) but was given {}",
+                phrase, span.start.line + 1, span.start.column + 1, expected, found
+            ),
+            ReduceError::ArgumentOrder { phrase, arguments, .. } => write!(
+                f,
+                "argument order for phrase '{}' at {}:{} doesn't fit its {} argument(s)",
+                phrase, span.start.line + 1, span.start.column + 1, arguments
+            ),
+            ReduceError::TemplateMismatch { phrase, template, found, .. } => write!(
+                f,
+                "phrase '{}' at {}:{} is written '{}' but was given arguments as '{}'",
+                phrase, span.start.line + 1, span.start.column + 1, template, found
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct ReduceReport {
+    pub profile: Option<ReduceProfile>,
+    pub matches: Vec<PhraseMatch>,
+    pub warnings: Vec<ReduceWarning>,
+    // only filled when ReduceOptions::collect_errors is set
+    pub errors: Vec<ReduceError>,
+    // only filled when ReduceOptions::learn is set
+    pub unfinished: Vec<UnfinishedPhrase>,
+}
+
+// words that began a phrase but were never completed, candidates for a missing dictionary entry
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct UnfinishedPhrase {
+    // written words joined like a phrase, arguments between them left out
+    pub words: String,
+    pub span: Span,
+    // registered phrases the words could have been meant as, closest first
+    pub suggestions: Vec<String>,
+}
+
+impl Display for UnfinishedPhrase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "phrase '{}' at {}:{} is unfinished", self.words.replace('_', " "), self.span.start.line + 1, self.span.start.column + 1)?;
+        write_suggestions(f, &self.suggestions)
+    }
+}
+
+fn write_suggestions(f: &mut Formatter<'_>, suggestions: &[String]) -> std::fmt::Result {
+    match suggestions {
+        [] => Ok(()),
+        suggestions => write!(
+            f,
+            ", did you mean {}?",
+            suggestions.iter().map(|phrase| format!("'{}'", phrase.replace('_', " "))).collect::<Vec<String>>().join(" or ")
+        ),
+    }
+}
+
+impl ReduceReport {
+    // match the given word node belongs to
+    pub fn linked_phrase(&self, word: usize) -> Option<&PhraseMatch> {
+        self.matches.iter().find(|m| m.words.contains(&word))
+    }
+
+    // times each phrase was matched, sorted by phrase
+    pub fn phrase_counts(&self) -> BTreeMap<&str, usize> {
+        let mut counts = BTreeMap::new();
+        for m in &self.matches {
+            *counts.entry(m.phrase.as_str()).or_insert(0) += 1;
+        }
+
+        counts
+    }
+
+    // apply nodes produced for each phrase, keyed by canonical phrase, nodes in match order
+    // for wiring phrases to their implementations without scanning the reduced tree
+    pub fn nodes_by_phrase(&self) -> BTreeMap<&str, Vec<usize>> {
+        let mut nodes = BTreeMap::new();
+        for m in &self.matches {
+            nodes.entry(m.phrase.as_str()).or_insert_with(Vec::new).push(m.node);
+        }
+
+        nodes
+    }
+
+    // word nodes folded into phrases across all matches
+    pub fn nodes_rewritten(&self) -> usize {
+        self.matches.iter().map(|m| m.words.len()).sum()
+    }
+
+    // expression index of each resolved apply node, keyed by node
+    pub fn expression_indices(&self) -> BTreeMap<usize, usize> {
+        self.matches.iter().filter_map(|m| Some((m.node, m.expression?))).collect()
+    }
+
+    // byte range of each match in source, in match order
+    pub fn byte_ranges(&self, source: &str) -> Vec<Option<Range<usize>>> {
+        self.matches.iter().map(|m| m.byte_range(source)).collect()
+    }
+
+    // registered phrases with no match in this report, sorted
+    pub fn unused_phrases<'a>(&self, context: &'a SimplePhraseContext) -> Vec<&'a str> {
+        let counts = self.phrase_counts();
+        context.phrases().filter(|phrase| !counts.contains_key(phrase)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use garnish_lang_compiler::lex::lex;
+    use garnish_lang_compiler::parse::{parse, Definition};
+
+    use crate::context::SimplePhraseContext;
+    use crate::{reduce_phrases_with_options, ReduceOptions};
+
+    #[test]
+    fn counts_and_coverage() {
+        let parsed = parse(&lex("perform 5 task, perform 10 task").unwrap()).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+        context.add_phrase("halt").unwrap();
+
+        let (_, report) = reduce_phrases_with_options(&parsed, &context, &ReduceOptions::default()).unwrap();
+
+        assert_eq!(report.phrase_counts().into_iter().collect::<Vec<(&str, usize)>>(), vec![("perform_task", 2)]);
+        assert_eq!(report.nodes_rewritten(), 4);
+        assert_eq!(report.unused_phrases(&context), vec!["halt"]);
+    }
+
+    #[test]
+    fn match_byte_ranges() {
+        let source = "perform \"é\" task, perform 10 task";
+        let parsed = parse(&lex(source).unwrap()).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+
+        let (_, report) = reduce_phrases_with_options(&parsed, &context, &ReduceOptions::default()).unwrap();
+        let mut ranges = report.byte_ranges(source).into_iter().map(Option::unwrap).collect::<Vec<_>>();
+        ranges.sort_by_key(|range| range.start);
+
+        assert_eq!(ranges.iter().map(|range| &source[range.clone()]).collect::<Vec<&str>>(), vec!["perform \"é\" task", "perform 10 task"]);
+    }
+
+    #[test]
+    fn apply_nodes_by_phrase() {
+        let parsed = parse(&lex("halt now, perform 5 task, perform 10 task").unwrap()).unwrap();
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrases(["perform_task", "halt_now"]).unwrap();
+
+        let (reduced, report) = reduce_phrases_with_options(&parsed, &context, &ReduceOptions::default()).unwrap();
+        let nodes = report.nodes_by_phrase();
+
+        assert_eq!(nodes.keys().copied().collect::<Vec<&str>>(), vec!["halt_now", "perform_task"]);
+        assert_eq!(nodes["perform_task"].len(), 2);
+        for node in nodes["perform_task"].iter().chain(&nodes["halt_now"]) {
+            assert!(matches!(
+                reduced.get_node(*node).unwrap().get_definition(),
+                Definition::ApplyTo | Definition::EmptyApply
+            ));
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum ReservedKind {
+    // names of garnish literals
+    Keyword,
+    // spoken names of garnish operators
+    OperatorWord,
+    // functions commonly provided by garnish runtimes
+    Builtin,
+}
+
+impl Display for ReservedKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReservedKind::Keyword => write!(f, "keyword"),
+            ReservedKind::OperatorWord => write!(f, "operator word"),
+            ReservedKind::Builtin => write!(f, "builtin"),
+        }
+    }
+}
+
+// what happens when a single word phrase matches a reserved word
+#[derive(Debug, Copy, Clone, Default, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum CollisionSeverity {
+    Allow,
+    #[default]
+    Warn,
+    Deny,
+}
+
+// identifiers a reduction never captures as single word phrases, even when the context has them
+// e.g. variable names a shared base dictionary would otherwise hijack
+// multi word phrases containing these words still match
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct SingleWordGuard {
+    pub words: BTreeSet<String>,
+    // shorter words are left as written, 0 allows any length
+    pub min_length: usize,
+}
+
+impl SingleWordGuard {
+    pub fn new() -> Self {
+        SingleWordGuard::default()
+    }
+
+    pub fn with_words<T: Into<String>>(mut self, words: impl IntoIterator<Item = T>) -> Self {
+        self.words.extend(words.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn with_min_length(mut self, min_length: usize) -> Self {
+        self.min_length = min_length;
+        self
+    }
+
+    pub fn blocks(&self, word: &str) -> bool {
+        self.words.contains(word) || word.chars().count() < self.min_length
+    }
+}
+
+const KEYWORDS: &[&str] = &["true", "false", "unit", "value"];
+
+const OPERATOR_WORDS: &[&str] = &[
+    "and", "or", "xor", "not", "tis", "apply", "reapply", "typeof", "typecast", "concat", "range",
+];
+
+const BUILTINS: &[&str] = &["print", "println", "debug", "length", "len", "type", "to_string"];
+
+pub(crate) fn reserved_kind(word: &str) -> Option<ReservedKind> {
+    if KEYWORDS.contains(&word) {
+        Some(ReservedKind::Keyword)
+    } else if OPERATOR_WORDS.contains(&word) {
+        Some(ReservedKind::OperatorWord)
+    } else if BUILTINS.contains(&word) {
+        Some(ReservedKind::Builtin)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::reserved::{reserved_kind, ReservedKind, SingleWordGuard};
+
+    #[test]
+    fn kinds() {
+        assert_eq!(reserved_kind("true"), Some(ReservedKind::Keyword));
+        assert_eq!(reserved_kind("xor"), Some(ReservedKind::OperatorWord));
+        assert_eq!(reserved_kind("print"), Some(ReservedKind::Builtin));
+        assert_eq!(reserved_kind("perform"), None);
+    }
+
+    #[test]
+    fn guard_blocks_listed_and_short_words() {
+        let guard = SingleWordGuard::new().with_words(["count"]).with_min_length(3);
+
+        assert!(guard.blocks("count"));
+        assert!(guard.blocks("id"));
+        assert!(!guard.blocks("stop"));
+    }
+}
@@ -0,0 +1,98 @@
+use std::ops::Range;
+
+use garnish_lang_compiler::parse::ParseNode;
+use serde::{Deserialize, Serialize};
+
+use crate::compat::node_text;
+
+#[derive(Debug, Copy, Clone, Default, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+// zero based, end is exclusive
+#[derive(Debug, Copy, Clone, Default, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Position {
+    // byte offset into the source the position was lexed from, columns count chars
+    // None when the source has no such position
+    pub fn byte_offset(&self, source: &str) -> Option<usize> {
+        let mut line_start = 0;
+        for _ in 0..self.line {
+            line_start += source[line_start..].find('\n')? + 1;
+        }
+
+        let line = &source[line_start..];
+        let line = &line[..line.find('\n').unwrap_or(line.len())];
+        match line.char_indices().nth(self.column) {
+            Some((offset, _)) => Some(line_start + offset),
+            None if line.chars().count() == self.column => Some(line_start + line.len()),
+            None => None,
+        }
+    }
+}
+
+impl Span {
+    // byte range of the span in the source it was lexed from, for editors working in byte offsets
+    pub fn byte_range(&self, source: &str) -> Option<Range<usize>> {
+        Some(self.start.byte_offset(source)?..self.end.byte_offset(source)?)
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        self.start <= position && position < self.end
+    }
+
+    pub fn of_node(node: &ParseNode) -> Self {
+        Span::between(node, node)
+    }
+
+    pub fn between(first: &ParseNode, last: &ParseNode) -> Self {
+        let first_token = first.get_lex_token();
+        let last_token = last.get_lex_token();
+
+        Span {
+            start: Position { line: first_token.get_line(), column: first_token.get_column() },
+            end: Position {
+                line: last_token.get_line(),
+                column: last_token.get_column() + node_text(last).chars().count(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use garnish_lang_compiler::lex::lex;
+    use garnish_lang_compiler::parse::parse;
+
+    use crate::span::{Position, Span};
+
+    #[test]
+    fn span_between_words() {
+        let tokens = lex("perform 5\n  task").unwrap();
+        let parsed = parse(&tokens).unwrap();
+
+        let span = Span::between(parsed.get_node(0).unwrap(), parsed.get_node(4).unwrap());
+
+        assert_eq!(span, Span {
+            start: Position { line: 0, column: 0 },
+            end: Position { line: 1, column: 6 },
+        });
+    }
+
+    #[test]
+    fn byte_range_counts_multibyte_chars() {
+        let source = "é = 1\nperform 5 tâsk";
+        let span = Span { start: Position { line: 1, column: 0 }, end: Position { line: 1, column: 14 } };
+
+        assert_eq!(span.byte_range(source), Some(7..22));
+        assert_eq!(&source[7..22], "perform 5 tâsk");
+        assert_eq!(Position { line: 2, column: 0 }.byte_offset(source), None);
+        assert_eq!(Position { line: 0, column: 6 }.byte_offset(source), None);
+    }
+}
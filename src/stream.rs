@@ -0,0 +1,192 @@
+use garnish_lang_compiler::lex::{lex, TokenType};
+use garnish_lang_compiler::parse::{parse, ParseResult};
+
+use crate::context::PhraseContext;
+use crate::error::PhraseError;
+use crate::options::ReduceOptions;
+use crate::reduce_phrases_with_options;
+use crate::report::PhraseMatch;
+use crate::span::{Position, Span};
+
+// words written after a phrase that could still be extended before its match is treated as final
+pub const DEFAULT_LOOKAHEAD: usize = 3;
+
+// reduces input that grows a piece at a time, like an expression being typed into a repl
+// each push reduces everything written so far and reports the matches more input can no longer change
+pub struct StreamingReducer<'a, Context: PhraseContext + ?Sized> {
+    context: &'a Context,
+    options: ReduceOptions,
+    lookahead: usize,
+    input: String,
+    // matches already reported as final
+    finalized: Vec<Span>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StreamUpdate {
+    // matches that became final with this push, node indices are for reduced
+    pub finalized: Vec<PhraseMatch>,
+    // matches more words could still turn into a longer phrase
+    pub tentative: Vec<PhraseMatch>,
+    // start of the input that may still be reduced differently, None when all of it is settled
+    pub pending_from: Option<Position>,
+    // everything written so far reduced, None while the input doesn't parse
+    pub reduced: Option<ParseResult>,
+}
+
+impl<'a, Context: PhraseContext + ?Sized> StreamingReducer<'a, Context> {
+    pub fn new(context: &'a Context) -> Self {
+        StreamingReducer {
+            context,
+            options: ReduceOptions::default(),
+            lookahead: DEFAULT_LOOKAHEAD,
+            input: String::new(),
+            finalized: vec![],
+        }
+    }
+
+    pub fn with_options(mut self, options: ReduceOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    // a phrase that is also the start of a longer one stays tentative until this many words follow it
+    pub fn with_lookahead(mut self, words: usize) -> Self {
+        self.lookahead = words;
+        self
+    }
+
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    // appends text to the input and reduces all of it
+    pub fn push(&mut self, text: &str) -> Result<StreamUpdate, PhraseError> {
+        self.input.push_str(text);
+        self.update(false)
+    }
+
+    // input is complete, every match is final
+    pub fn finish(&mut self) -> Result<StreamUpdate, PhraseError> {
+        self.update(true)
+    }
+
+    // starts over for the next expression
+    pub fn clear(&mut self) {
+        self.input.clear();
+        self.finalized.clear();
+    }
+
+    fn update(&mut self, settle: bool) -> Result<StreamUpdate, PhraseError> {
+        // incomplete input often doesn't lex or parse yet, everything after the settled matches is pending
+        let parsed = lex(&self.input).ok().and_then(|tokens| Some((parse(&tokens).ok()?, tokens)));
+        let (parsed, tokens) = match parsed {
+            None => {
+                let pending_from = self.finalized.iter().map(|span| span.end).max().unwrap_or_default();
+                return Ok(StreamUpdate { pending_from: Some(pending_from), ..StreamUpdate::default() });
+            }
+            Some(parsed) => parsed,
+        };
+
+        let options = ReduceOptions { learn: true, ..self.options.clone() };
+        let (reduced, report) = reduce_phrases_with_options(&parsed, self.context, &options)?;
+
+        let words = tokens.iter()
+            .filter(|token| token.get_token_type() != TokenType::Whitespace)
+            .map(|token| Position { line: token.get_line(), column: token.get_column() })
+            .collect::<Vec<Position>>();
+
+        let mut matches = report.matches;
+        matches.sort_by_key(|m| m.span);
+
+        // an unfinished phrase or a phrase that may still grow holds back everything after it
+        let mut pending_from = report.unfinished.iter().map(|unfinished| unfinished.span.start).min();
+        if !settle {
+            for m in matches.iter() {
+                let following = words.iter().filter(|position| **position >= m.span.end).count();
+                if self.is_extendable(&m.phrase) && following < self.lookahead {
+                    pending_from = Some(pending_from.map_or(m.span.start, |pending| pending.min(m.span.start)));
+                }
+            }
+        }
+
+        let mut update = StreamUpdate { pending_from, ..StreamUpdate::default() };
+        for m in matches {
+            match pending_from {
+                Some(pending) if m.span.end > pending => update.tentative.push(m),
+                _ if self.finalized.contains(&m.span) => (),
+                _ => {
+                    self.finalized.push(m.span);
+                    update.finalized.push(m);
+                }
+            }
+        }
+        update.reduced = Some(reduced);
+
+        Ok(update)
+    }
+
+    fn is_extendable(&self, phrase: &str) -> bool {
+        self.context.get_phrase_status(phrase).merge(self.options.extra_phrases.get_phrase_status(phrase)).is_extendable()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::{OverlapPolicy, SimplePhraseContext};
+    use crate::span::Position;
+    use crate::stream::StreamingReducer;
+
+    #[test]
+    fn matches_final_once_unambiguous() {
+        let mut context = SimplePhraseContext::new();
+        context.set_overlap_policy(OverlapPolicy::AllowCompletePrefix);
+        context.add_phrases(["perform_task", "perform_task_now"]).unwrap();
+
+        let mut stream = StreamingReducer::new(&context).with_lookahead(1);
+
+        let update = stream.push("perform 5").unwrap();
+        assert!(update.finalized.is_empty());
+        assert_eq!(update.pending_from, Some(Position { line: 0, column: 0 }));
+
+        let update = stream.push(" task").unwrap();
+        assert!(update.finalized.is_empty());
+        assert_eq!(update.tentative[0].phrase, "perform_task");
+
+        let update = stream.push(" 10").unwrap();
+        assert_eq!(update.finalized.iter().map(|m| m.phrase.as_str()).collect::<Vec<&str>>(), vec!["perform_task"]);
+        assert_eq!(update.pending_from, None);
+
+        // already reported
+        assert!(stream.push(" 20").unwrap().finalized.is_empty());
+    }
+
+    #[test]
+    fn finish_settles_remaining() {
+        let mut context = SimplePhraseContext::new();
+        context.set_overlap_policy(OverlapPolicy::AllowCompletePrefix);
+        context.add_phrases(["perform_task", "perform_task_now"]).unwrap();
+
+        let mut stream = StreamingReducer::new(&context);
+        assert!(stream.push("perform 5 task").unwrap().finalized.is_empty());
+
+        let update = stream.finish().unwrap();
+        assert_eq!(update.finalized.len(), 1);
+        assert!(update.tentative.is_empty());
+
+        stream.clear();
+        assert_eq!(stream.input(), "");
+    }
+
+    #[test]
+    fn unparsed_input_is_pending() {
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+
+        let mut stream = StreamingReducer::new(&context);
+        let update = stream.push("value = (perform 5").unwrap();
+
+        assert!(update.reduced.is_none());
+        assert_eq!(update.pending_from, Some(Position::default()));
+    }
+}
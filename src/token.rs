@@ -0,0 +1,63 @@
+use garnish_lang_compiler::lex::{LexerToken, TokenType};
+use garnish_lang_compiler::parse::ParseResult;
+
+use crate::compat::node_text;
+
+// word of the phrase whose line and column tokens made for the phrase take
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum TokenPosition {
+    FirstWord,
+    // the word whose node becomes the phrase identifier
+    #[default]
+    LastWord,
+}
+
+// fields of tokens made for a phrase, its identifier and any apply, empty apply or access nodes added for it
+// token_type replaces only identifier tokens, the period of a dotted target stays a period
+// arguments, argument names and list nodes reused as applies keep their own tokens
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct TokenRules {
+    pub token_type: TokenType,
+    pub position: TokenPosition,
+}
+
+impl Default for TokenRules {
+    fn default() -> Self {
+        TokenRules { token_type: TokenType::Identifier, position: TokenPosition::LastWord }
+    }
+}
+
+impl TokenRules {
+    pub fn new() -> Self {
+        TokenRules::default()
+    }
+
+    pub fn with_token_type(mut self, token_type: TokenType) -> Self {
+        self.token_type = token_type;
+        self
+    }
+
+    pub fn with_position(mut self, position: TokenPosition) -> Self {
+        self.position = position;
+        self
+    }
+
+    // rewrites the tokens of nodes, first and last are the phrase's words as written
+    pub(crate) fn place(&self, result: &mut ParseResult, nodes: impl IntoIterator<Item = usize>, first: &LexerToken, last: &LexerToken) {
+        let position = match self.position {
+            TokenPosition::FirstWord => first,
+            TokenPosition::LastWord => last,
+        };
+
+        for index in nodes {
+            if let Some(node) = result.get_node_mut(index) {
+                let token_type = match node.get_lex_token().get_token_type() {
+                    TokenType::Identifier => self.token_type,
+                    token_type => token_type,
+                };
+                let token = LexerToken::new(node_text(node), token_type, position.get_line(), position.get_column());
+                node.set_lex_token(token);
+            }
+        }
+    }
+}
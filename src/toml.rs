@@ -0,0 +1,288 @@
+// minimal toml reading for dictionaries
+// key/value pairs at the top and in arrays of tables like [[phrases]]
+// values are strings, integers, booleans and arrays of them, arrays may span lines
+// other tables, dotted keys, floats and dates aren't supported
+
+use serde_json::{Map, Value};
+
+// document as the json value serde would have made from the same data
+// errors carry the 1 based line they were found on
+pub(crate) fn parse(input: &str) -> Result<Value, (usize, String)> {
+    let mut root = Map::new();
+    let mut table: Option<String> = None;
+    let mut lines = input.lines().enumerate().map(|(index, line)| (index + 1, line));
+
+    while let Some((line, text)) = lines.next() {
+        let mut text = strip_comment(text).trim().to_string();
+        if text.is_empty() {
+            continue;
+        }
+
+        if let Some(name) = text.strip_prefix("[[").and_then(|rest| rest.strip_suffix("]]")) {
+            let name = name.trim();
+            check_key(name).map_err(|message| (line, message))?;
+            match root.entry(name.to_string()).or_insert_with(|| Value::Array(vec![])) {
+                Value::Array(tables) => tables.push(Value::Object(Map::new())),
+                _ => return Err((line, format!("'{}' is already a value, not an array of tables", name))),
+            }
+            table = Some(name.to_string());
+            continue;
+        }
+        if text.starts_with('[') {
+            return Err((line, "only arrays of tables like [[phrases]] are supported".to_string()));
+        }
+
+        let (key, _) = text.split_once('=').ok_or((line, "expected key = value".to_string()))?;
+        let key = key.trim().to_string();
+        check_key(&key).map_err(|message| (line, message))?;
+
+        // arrays continue on following lines until their brackets close
+        while !is_closed(&text) {
+            match lines.next() {
+                None => return Err((line, format!("array for '{}' is never closed", key))),
+                Some((_, next)) => {
+                    text.push(' ');
+                    text.push_str(strip_comment(next).trim());
+                }
+            }
+        }
+
+        let value = text.split_once('=').map(|(_, value)| value.trim()).unwrap_or_default();
+        let value = Values::new(value).parse_all().map_err(|message| (line, format!("value of '{}' {}", key, message)))?;
+
+        let target = match &table {
+            None => &mut root,
+            Some(name) => match root.get_mut(name).and_then(Value::as_array_mut).and_then(|tables| tables.last_mut()) {
+                Some(Value::Object(target)) => target,
+                _ => return Err((line, format!("'{}' is not an array of tables", name))),
+            },
+        };
+        if target.insert(key.clone(), value).is_some() {
+            return Err((line, format!("'{}' is defined twice", key)));
+        }
+    }
+
+    Ok(Value::Object(root))
+}
+
+fn check_key(key: &str) -> Result<(), String> {
+    match !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        true => Ok(()),
+        false => Err(format!("'{}' is not a supported key", key)),
+    }
+}
+
+// line up to a # outside of any string
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+
+    for (index, c) in line.char_indices() {
+        match (quote, c) {
+            (Some('"'), '\\') if !escaped => {
+                escaped = true;
+                continue;
+            }
+            (Some(open), c) if c == open && !escaped => quote = None,
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '#') => return &line[..index],
+            _ => (),
+        }
+        escaped = false;
+    }
+
+    line
+}
+
+// whether every bracket opened outside of strings is closed
+fn is_closed(text: &str) -> bool {
+    let mut depth = 0i32;
+    let mut quote = None;
+    let mut escaped = false;
+
+    for c in text.chars() {
+        match (quote, c) {
+            (Some('"'), '\\') if !escaped => {
+                escaped = true;
+                continue;
+            }
+            (Some(open), c) if c == open && !escaped => quote = None,
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '[') => depth += 1,
+            (None, ']') => depth -= 1,
+            _ => (),
+        }
+        escaped = false;
+    }
+
+    depth <= 0
+}
+
+struct Values {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Values {
+    fn new(text: &str) -> Self {
+        Values { chars: text.chars().collect(), position: 0 }
+    }
+
+    fn parse_all(&mut self) -> Result<Value, String> {
+        let value = self.value()?;
+        self.skip_whitespace();
+        match self.position == self.chars.len() {
+            true => Ok(value),
+            false => Err(format!("has unexpected '{}' after it", self.rest())),
+        }
+    }
+
+    fn rest(&self) -> String {
+        self.chars[self.position..].iter().collect()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.position += 1;
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            None => Err("is missing".to_string()),
+            Some('"') => self.basic_string().map(Value::String),
+            Some('\'') => self.literal_string().map(Value::String),
+            Some('[') => self.array(),
+            Some(_) => self.scalar(),
+        }
+    }
+
+    fn basic_string(&mut self) -> Result<String, String> {
+        self.position += 1;
+        let mut text = String::new();
+
+        loop {
+            let c = self.peek().ok_or("has an unterminated string")?;
+            self.position += 1;
+            match c {
+                '"' => return Ok(text),
+                '\\' => {
+                    let escaped = self.peek().ok_or("has an unterminated string")?;
+                    self.position += 1;
+                    match escaped {
+                        '"' => text.push('"'),
+                        '\\' => text.push('\\'),
+                        'n' => text.push('\n'),
+                        't' => text.push('\t'),
+                        'r' => text.push('\r'),
+                        other => return Err(format!("has unsupported escape '\\{}'", other)),
+                    }
+                }
+                c => text.push(c),
+            }
+        }
+    }
+
+    fn literal_string(&mut self) -> Result<String, String> {
+        self.position += 1;
+        let start = self.position;
+        while self.peek().is_some_and(|c| c != '\'') {
+            self.position += 1;
+        }
+
+        match self.peek() {
+            None => Err("has an unterminated string".to_string()),
+            Some(_) => {
+                self.position += 1;
+                Ok(self.chars[start..self.position - 1].iter().collect())
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        self.position += 1;
+        let mut values = vec![];
+
+        loop {
+            self.skip_whitespace();
+            if self.peek() == Some(']') {
+                self.position += 1;
+                return Ok(Value::Array(values));
+            }
+
+            values.push(self.value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.position += 1,
+                Some(']') => (),
+                _ => return Err("has an array missing a comma or closing bracket".to_string()),
+            }
+        }
+    }
+
+    fn scalar(&mut self) -> Result<Value, String> {
+        let start = self.position;
+        while self.peek().is_some_and(|c| !c.is_whitespace() && c != ',' && c != ']') {
+            self.position += 1;
+        }
+        let text = self.chars[start..self.position].iter().collect::<String>();
+
+        match text.as_str() {
+            "true" => Ok(Value::Bool(true)),
+            "false" => Ok(Value::Bool(false)),
+            number => number.replace('_', "").parse::<i64>()
+                .map(Value::from)
+                .map_err(|_| format!("'{}' is not a string, integer, boolean or array", number)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::toml::parse;
+
+    #[test]
+    fn arrays_of_tables() {
+        let input = r#"
+version = 1 # current
+includes = ['base.toml']
+
+[[phrases]]
+phrase = "perform_task"
+doc = "Runs a \"task\""
+arity = 2
+tags = [
+    "jobs", # first
+    "core",
+]
+
+[[phrases]]
+phrase = 'halt'
+deprecated = true
+"#;
+
+        assert_eq!(parse(input).unwrap(), json!({
+            "version": 1,
+            "includes": ["base.toml"],
+            "phrases": [
+                { "phrase": "perform_task", "doc": "Runs a \"task\"", "arity": 2, "tags": ["jobs", "core"] },
+                { "phrase": "halt", "deprecated": true },
+            ],
+        }));
+    }
+
+    #[test]
+    fn errors_have_lines() {
+        assert_eq!(parse("version = 1\n[table]").unwrap_err().0, 2);
+        assert_eq!(parse("[[phrases]]\nphrase = \"a\"\nphrase = \"b\"").unwrap_err(), (3, "'phrase' is defined twice".to_string()));
+        assert_eq!(parse("\n\nweight = 1.5").unwrap_err(), (3, "value of 'weight' '1.5' is not a string, integer, boolean or array".to_string()));
+        assert_eq!(parse("tags = [\"a\"").unwrap_err().0, 1);
+    }
+}
@@ -0,0 +1,106 @@
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+use garnish_lang_compiler::parse::{Definition, ParseResult};
+
+use crate::error::PhraseError;
+
+// node the matcher checks paired with whether it is on the left side of its parent
+pub type TraversalItem = (Option<usize>, bool);
+
+pub trait TraversalStrategy {
+    fn items(&self, parse_result: &ParseResult) -> Result<Vec<TraversalItem>, PhraseError>;
+}
+
+// order list items are fed to the matcher
+#[derive(Clone, Default)]
+pub enum Traversal {
+    // children of lists before their parents, left before right
+    #[default]
+    ParentStack,
+    // source order, every node whose parent is a list
+    InOrder,
+    Custom(Arc<dyn TraversalStrategy + Send + Sync>),
+}
+
+impl Debug for Traversal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Traversal::ParentStack => write!(f, "ParentStack"),
+            Traversal::InOrder => write!(f, "InOrder"),
+            Traversal::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
+impl Traversal {
+    pub(crate) fn items(&self, parse_result: &ParseResult) -> Result<Vec<TraversalItem>, PhraseError> {
+        match self {
+            Traversal::ParentStack => crate::collect_phrase_items(parse_result),
+            Traversal::InOrder => in_order_items(parse_result),
+            Traversal::Custom(strategy) => strategy.items(parse_result),
+        }
+    }
+}
+
+fn in_order_items(parse_result: &ParseResult) -> Result<Vec<TraversalItem>, PhraseError> {
+    let root = parse_result.get_root();
+
+    if parse_result.get_nodes().len() == 1 {
+        return Ok(vec![(Some(root), false)]);
+    }
+
+    let mut items = vec![];
+    // node and whether its left side has been walked already
+    let mut stack = vec![(root, false)];
+
+    while let Some((index, left_walked)) = stack.pop() {
+        let node = parse_result.get_node(index).ok_or(PhraseError::MissingNode(index))?;
+
+        if !left_walked {
+            stack.push((index, true));
+            if let Some(left) = node.get_left() {
+                stack.push((left, false));
+            }
+            continue;
+        }
+
+        if let Some(parent) = node.get_parent() {
+            let parent_node = parse_result.get_node(parent).ok_or(PhraseError::MissingNode(parent))?;
+            if parent_node.get_definition() == Definition::List {
+                items.push((Some(index), parent_node.get_left() == Some(index)));
+            }
+        }
+
+        if let Some(right) = node.get_right() {
+            stack.push((right, false));
+        }
+    }
+
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use garnish_lang_compiler::lex::lex;
+    use garnish_lang_compiler::parse::parse;
+
+    use crate::traversal::Traversal;
+
+    #[test]
+    fn in_order_is_source_order() {
+        let parsed = parse(&lex("perform 5 task").unwrap()).unwrap();
+
+        let items = Traversal::InOrder.items(&parsed).unwrap();
+
+        assert_eq!(items, vec![(Some(0), true), (Some(1), true), (Some(2), false), (Some(4), false)]);
+    }
+
+    #[test]
+    fn single_node() {
+        let parsed = parse(&lex("perform").unwrap()).unwrap();
+
+        assert_eq!(Traversal::InOrder.items(&parsed).unwrap(), vec![(Some(0), false)]);
+        assert_eq!(Traversal::ParentStack.items(&parsed).unwrap(), vec![(Some(0), false)]);
+    }
+}
@@ -0,0 +1,299 @@
+// helpers for building and splicing parse trees
+
+use garnish_lang_compiler::lex::{LexerToken, TokenType};
+use garnish_lang_compiler::parse::{Definition, ParseNode, ParseResult, SecondaryDefinition};
+
+use crate::error::PhraseError;
+use crate::meta::SyntacticPosition;
+
+// checks links can be followed without looping or leaving the node list
+// and that every child reached from the root has the node it was reached from as its parent
+// children are walked from the root, parents are climbed from every node reached
+pub(crate) fn check_structure(result: &ParseResult) -> Result<(), PhraseError> {
+    let count = result.get_nodes().len();
+    if count == 0 {
+        return Ok(());
+    }
+
+    let root = result.get_root();
+    if root >= count {
+        return Err(PhraseError::MissingNode(root));
+    }
+
+    let mut reached = vec![false; count];
+    let mut stack = vec![(root, None)];
+    while let Some((index, via)) = stack.pop() {
+        if reached[index] {
+            return Err(PhraseError::CyclicTree { node: index, via: via.unwrap_or(index) });
+        }
+        reached[index] = true;
+
+        let node = result.get_node(index).ok_or(PhraseError::MissingNode(index))?;
+        for link in [node.get_parent(), node.get_left(), node.get_right()].into_iter().flatten() {
+            if link >= count {
+                return Err(PhraseError::DanglingLink { node: index, link });
+            }
+        }
+        stack.extend([node.get_right(), node.get_left()].into_iter().flatten().map(|child| (child, Some(index))));
+    }
+
+    // 0 not climbed yet, 1 on the current climb, 2 known to end at a node without a parent
+    let mut state = vec![0u8; count];
+    for start in (0..count).filter(|index| reached[*index]) {
+        let mut path = vec![];
+        let mut current = Some(start);
+
+        while let Some(index) = current {
+            match state[index] {
+                2 => break,
+                1 => return Err(PhraseError::CyclicTree { node: index, via: path.last().copied().unwrap_or(index) }),
+                _ => (),
+            }
+            state[index] = 1;
+            path.push(index);
+
+            current = result.get_node(index).ok_or(PhraseError::MissingNode(index))?.get_parent();
+            if let Some(parent) = current.filter(|parent| *parent >= count) {
+                return Err(PhraseError::DanglingLink { node: index, link: parent });
+            }
+        }
+
+        for index in path {
+            state[index] = 2;
+        }
+    }
+
+    // links are known to stay in the list and not loop by now
+    for index in (0..count).filter(|index| reached[*index]) {
+        let node = result.get_node(index).ok_or(PhraseError::MissingNode(index))?;
+        for child in [node.get_left(), node.get_right()].into_iter().flatten() {
+            let parent = result.get_node(child).ok_or(PhraseError::MissingNode(child))?.get_parent();
+            if parent != Some(index) {
+                return Err(PhraseError::MismatchedParent { node: child, linked_from: index, parent });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// copies node and its descendants, returns the copied node's index which has no parent
+pub(crate) fn copy_subtree(source: &ParseResult, index: usize, target: &mut ParseResult) -> Option<usize> {
+    let node = source.get_node(index)?;
+    let new_index = target.get_nodes().len();
+    target.add_node(ParseNode::new(
+        node.get_definition(),
+        node.get_secondary_definition(),
+        None,
+        None,
+        None,
+        node.get_lex_token(),
+    ));
+
+    let left = match node.get_left() {
+        None => None,
+        Some(left) => Some(copy_subtree(source, left, target)?),
+    };
+    let right = match node.get_right() {
+        None => None,
+        Some(right) => Some(copy_subtree(source, right, target)?),
+    };
+
+    for child in [left, right].into_iter().flatten() {
+        target.get_node_mut(child)?.set_parent(Some(new_index));
+    }
+
+    let copied = target.get_node_mut(new_index)?;
+    copied.set_left(left);
+    copied.set_right(right);
+
+    Some(new_index)
+}
+
+// position of the list node_index is an item of, found by climbing out of whitespace lists
+pub(crate) fn syntactic_position(result: &ParseResult, node_index: usize) -> SyntacticPosition {
+    let mut current = node_index;
+    loop {
+        let parent = match result.get_node(current).and_then(|node| node.get_parent()) {
+            None => return SyntacticPosition::ExpressionRoot,
+            Some(parent) => parent,
+        };
+
+        match result.get_node(parent).map(|node| node.get_definition()) {
+            Some(Definition::List) => current = parent,
+            Some(Definition::CommaList) => return SyntacticPosition::ListElement,
+            Some(Definition::Subexpression) | Some(Definition::ExpressionTerminator) | Some(Definition::ExpressionSeparator) => {
+                return SyntacticPosition::ExpressionRoot
+            }
+            _ => return SyntacticPosition::Nested,
+        }
+    }
+}
+
+// appends a binary node over left and right, returns its index
+pub(crate) fn add_joining_node(
+    result: &mut ParseResult,
+    definition: Definition,
+    left: usize,
+    right: usize,
+    text: &str,
+    token_type: TokenType,
+    position: &LexerToken,
+) -> usize {
+    let index = result.get_nodes().len();
+    result.add_node(ParseNode::new(
+        definition,
+        SecondaryDefinition::BinaryLeftToRight,
+        None,
+        Some(left),
+        Some(right),
+        LexerToken::new(text.to_string(), token_type, position.get_line(), position.get_column()),
+    ));
+
+    for child in [left, right] {
+        if let Some(node) = result.get_node_mut(child) {
+            node.set_parent(Some(index));
+        }
+    }
+
+    index
+}
+
+// appends all nodes of other to result, returns the offset they were placed at
+pub(crate) fn append_tree(result: &mut ParseResult, other: &ParseResult) -> usize {
+    let offset = result.get_nodes().len();
+    let shift = |index: Option<usize>| index.map(|index| index + offset);
+
+    for node in other.get_nodes() {
+        result.add_node(ParseNode::new(
+            node.get_definition(),
+            node.get_secondary_definition(),
+            shift(node.get_parent()),
+            shift(node.get_left()),
+            shift(node.get_right()),
+            node.get_lex_token(),
+        ));
+    }
+
+    offset
+}
+
+// node and its descendants, sorted
+pub(crate) fn subtree_nodes(result: &ParseResult, node: usize) -> Vec<usize> {
+    let mut nodes = vec![];
+    let mut stack = vec![node];
+    while let Some(index) = stack.pop() {
+        if let Some(current) = result.get_node(index) {
+            nodes.push(index);
+            stack.extend([current.get_left(), current.get_right()].into_iter().flatten());
+        }
+    }
+
+    nodes.sort();
+    nodes
+}
+
+// writes the nodes of other over the nodes at slots in order, appending those past the last slot
+// slots left over are cleared of their links, returns where each node of other was placed
+pub(crate) fn place_tree(result: &mut ParseResult, other: &ParseResult, slots: &[usize]) -> Vec<usize> {
+    let end = result.get_nodes().len();
+    let places = (0..other.get_nodes().len())
+        .map(|index| slots.get(index).copied().unwrap_or(end + index - slots.len()))
+        .collect::<Vec<usize>>();
+    let place = |index: Option<usize>| index.map(|index| places[index]);
+
+    for (index, node) in other.get_nodes().iter().enumerate() {
+        let placed = ParseNode::new(
+            node.get_definition(),
+            node.get_secondary_definition(),
+            place(node.get_parent()),
+            place(node.get_left()),
+            place(node.get_right()),
+            node.get_lex_token(),
+        );
+        match result.get_node_mut(places[index]) {
+            Some(slot) => *slot = placed,
+            None => result.add_node(placed),
+        }
+    }
+
+    for slot in slots.iter().skip(other.get_nodes().len()) {
+        if let Some(node) = result.get_node_mut(*slot) {
+            node.set_parent(None);
+            node.set_left(None);
+            node.set_right(None);
+        }
+    }
+
+    places
+}
+
+// lists of the whitespace list holding node from the innermost up, with the list's items in order
+// node's parent must be a list, the lists are followed up while each is the left side of the next
+pub(crate) fn list_spine(result: &ParseResult, node: usize) -> Result<(Vec<usize>, Vec<usize>), PhraseError> {
+    let is_list = |index: Option<usize>| index.and_then(|index| result.get_node(index)).is_some_and(|node| node.get_definition() == Definition::List);
+
+    let mut top = match result.get_node(node).ok_or(PhraseError::MissingNode(node))?.get_parent() {
+        parent if is_list(parent) => parent.unwrap_or_default(),
+        _ => return Ok((vec![], vec![node])),
+    };
+    while let Some(parent) = result.get_node(top).and_then(|list| list.get_parent()).filter(|parent| is_list(Some(*parent))) {
+        match result.get_node(parent).and_then(|list| list.get_left()) == Some(top) {
+            true => top = parent,
+            false => break,
+        }
+    }
+
+    let mut spine = vec![];
+    let mut current = top;
+    while is_list(Some(current)) {
+        spine.push(current);
+        current = result.get_node(current).and_then(|list| list.get_left()).ok_or(PhraseError::MissingLeft(current))?;
+    }
+    spine.reverse();
+
+    let mut items = vec![current];
+    for list in spine.iter() {
+        items.extend(result.get_node(*list).and_then(|list| list.get_right()));
+    }
+
+    Ok((spine, items))
+}
+
+// places items in the lists of a spine from list_spine in the given order, there must be one more item than lists
+pub(crate) fn relink_list(result: &mut ParseResult, spine: &[usize], items: &[usize]) -> Result<(), PhraseError> {
+    for (position, list) in spine.iter().enumerate() {
+        let left = match position {
+            0 => items[0],
+            _ => spine[position - 1],
+        };
+        let right = items[position + 1];
+
+        let node = result.get_node_mut(*list).ok_or(PhraseError::MissingNode(*list))?;
+        node.set_left(Some(left));
+        node.set_right(Some(right));
+        for child in [left, right] {
+            result.get_node_mut(child).ok_or(PhraseError::MissingNode(child))?.set_parent(Some(*list));
+        }
+    }
+
+    Ok(())
+}
+
+// points parent, or the root, at new where it pointed at old
+pub(crate) fn replace_child(result: &mut ParseResult, parent: Option<usize>, old: usize, new: usize) {
+    match parent.and_then(|parent| result.get_node_mut(parent)) {
+        None => (),
+        Some(parent) => {
+            if parent.get_left() == Some(old) {
+                parent.set_left(Some(new));
+            } else if parent.get_right() == Some(old) {
+                parent.set_right(Some(new));
+            }
+        }
+    }
+
+    if result.get_root() == old {
+        result.set_root(new);
+    }
+}
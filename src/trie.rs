@@ -0,0 +1,260 @@
+use std::collections::BTreeMap;
+
+use crate::context::{normalize_phrase, write_meta, ContextError, ContextErrors, OverlapPolicy, PhraseContext, PhraseStatus, SimpleContextCodes, SimplePhraseContext};
+use crate::fingerprint::Fingerprinter;
+
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+struct TrieNode {
+    children: BTreeMap<String, TrieNode>,
+    complete: bool,
+}
+
+impl TrieNode {
+    fn status(&self) -> PhraseStatus {
+        match (self.complete, self.children.is_empty()) {
+            (true, true) => PhraseStatus::Complete,
+            (true, false) => PhraseStatus::CompleteAndExtendable,
+            (false, _) => PhraseStatus::Incomplete,
+        }
+    }
+
+    fn collect(&self, path: &mut Vec<String>, phrases: &mut Vec<String>) {
+        if self.complete {
+            phrases.push(path.join("_"));
+        }
+
+        for (word, child) in self.children.iter() {
+            path.push(word.clone());
+            child.collect(path, phrases);
+            path.pop();
+        }
+    }
+}
+
+// stores each word once per position, status lookups walk the words of a phrase without building joined prefixes
+// same rules for adding phrases as SimplePhraseContext, without meta
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct TriePhraseContext {
+    root: TrieNode,
+    overlap_policy: OverlapPolicy,
+    count: usize,
+}
+
+impl TriePhraseContext {
+    pub fn new() -> Self {
+        TriePhraseContext::default()
+    }
+
+    pub fn set_overlap_policy(&mut self, policy: OverlapPolicy) {
+        self.overlap_policy = policy;
+    }
+
+    pub fn add_phrase(&mut self, phrase: &str) -> Result<(), SimpleContextCodes> {
+        self.try_add_phrase(phrase).map_err(|error| error.code)
+    }
+
+    // as add_phrase, with the phrase that conflicted on failure
+    pub fn try_add_phrase(&mut self, phrase: &str) -> Result<(), ContextError> {
+        let words = phrase.split('_').filter(|word| !word.is_empty()).collect::<Vec<&str>>();
+        // trailing underscore only registers the words as the start of a phrase
+        let complete = !phrase.ends_with('_') && !words.is_empty();
+        let conflict = |conflict: String, code: SimpleContextCodes| ContextError {
+            phrase: phrase.to_string(),
+            conflict,
+            conflict_source: None,
+            code,
+        };
+
+        // check before changing anything so a failed add leaves no prefixes behind
+        let mut node = &self.root;
+        for (position, word) in words.iter().enumerate() {
+            match node.children.get(*word) {
+                None => break,
+                Some(child) => node = child,
+            }
+
+            let is_last = position + 1 == words.len();
+            if self.overlap_policy == OverlapPolicy::AllowCompletePrefix {
+                continue;
+            }
+            if node.complete && !is_last {
+                return Err(conflict(words[..=position].join("_"), SimpleContextCodes::CompleteVersionExists));
+            }
+            if is_last && complete && !node.complete && !node.children.is_empty() {
+                let mut longer = vec![];
+                node.collect(&mut words.iter().map(|word| word.to_string()).collect(), &mut longer);
+                return Err(conflict(longer.into_iter().next().unwrap_or_default(), SimpleContextCodes::IncompleteVersionExists));
+            }
+        }
+
+        let mut node = &mut self.root;
+        for word in words {
+            node = node.children.entry(word.to_string()).or_default();
+        }
+        if complete && !node.complete {
+            node.complete = true;
+            self.count += 1;
+        }
+
+        Ok(())
+    }
+
+    // adds every phrase, collecting failures instead of stopping at the first
+    pub fn add_phrases<S: AsRef<str>>(&mut self, phrases: impl IntoIterator<Item = S>) -> Result<(), ContextErrors> {
+        let errors = phrases.into_iter()
+            .filter_map(|phrase| self.try_add_phrase(phrase.as_ref()).err())
+            .collect::<Vec<ContextError>>();
+
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(ContextErrors(errors)),
+        }
+    }
+
+    pub fn contains_phrase(&self, phrase: &str) -> bool {
+        self.get_phrase_status(phrase).is_complete()
+    }
+
+    // complete phrases, prefixes aren't counted
+    pub fn phrase_count(&self) -> usize {
+        self.count
+    }
+
+    // complete phrases in sorted order
+    pub fn phrases(&self) -> Vec<String> {
+        let mut phrases = vec![];
+        self.root.collect(&mut vec![], &mut phrases);
+        phrases.sort();
+        phrases
+    }
+
+    fn find(&self, phrase: &str) -> Option<&TrieNode> {
+        let mut node = &self.root;
+        let mut words = phrase.split('_').filter(|word| !word.is_empty()).peekable();
+        words.peek()?;
+
+        for word in words {
+            node = node.children.get(word)?;
+        }
+
+        Some(node)
+    }
+}
+
+impl From<&SimplePhraseContext> for TriePhraseContext {
+    fn from(context: &SimplePhraseContext) -> Self {
+        let mut trie = TriePhraseContext::new();
+        trie.set_overlap_policy(OverlapPolicy::AllowCompletePrefix);
+
+        for phrase in context.phrases() {
+            // complete prefixes are allowed, nothing else can fail
+            let _ = trie.add_phrase(phrase);
+        }
+
+        trie
+    }
+}
+
+impl PhraseContext for TriePhraseContext {
+    fn get_phrase_status(&self, s: &str) -> PhraseStatus {
+        self.find(s).map(TrieNode::status).unwrap_or(PhraseStatus::NotAPhrase)
+    }
+
+    fn completions(&self, prefix: &str) -> Vec<String> {
+        let node = match self.find(prefix) {
+            None => return vec![],
+            Some(node) => node,
+        };
+
+        let mut path = vec![normalize_phrase(prefix)];
+        let mut phrases = vec![];
+        for (word, child) in node.children.iter() {
+            path.push(word.clone());
+            child.collect(&mut path, &mut phrases);
+            path.pop();
+        }
+        phrases.sort();
+        phrases
+    }
+
+    // sorted, as phrases
+    fn iter_phrases(&self) -> Box<dyn Iterator<Item = String> + '_> {
+        Box::new(self.phrases().into_iter())
+    }
+
+    // same value as a SimplePhraseContext with the same phrases and no meta or aliases
+    fn fingerprint(&self) -> Option<u64> {
+        let mut fingerprinter = Fingerprinter::new();
+        for phrase in self.phrases() {
+            fingerprinter.write_str(&phrase);
+            write_meta(&mut fingerprinter, None);
+        }
+
+        // no join separator or case splitting
+        fingerprinter.write_str("");
+        fingerprinter.write_u8(0);
+        Some(fingerprinter.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use garnish_lang_compiler::lex::lex;
+    use garnish_lang_compiler::parse::parse;
+
+    use crate::context::{OverlapPolicy, PhraseContext, PhraseStatus, SimpleContextCodes, SimplePhraseContext};
+    use crate::trie::TriePhraseContext;
+
+    #[test]
+    fn status_of_words() {
+        let mut context = TriePhraseContext::new();
+        context.add_phrases(["perform_the_task", "halt"]).unwrap();
+
+        assert_eq!(context.get_phrase_status("perform"), PhraseStatus::Incomplete);
+        assert_eq!(context.get_phrase_status("perform_the"), PhraseStatus::Incomplete);
+        assert_eq!(context.get_phrase_status("perform_the_task"), PhraseStatus::Complete);
+        assert_eq!(context.get_phrase_status("halt"), PhraseStatus::Complete);
+        assert_eq!(context.get_phrase_status("task"), PhraseStatus::NotAPhrase);
+        assert_eq!(context.get_phrase_status(""), PhraseStatus::NotAPhrase);
+        assert_eq!(context.phrase_count(), 2);
+    }
+
+    #[test]
+    fn conflicts_like_simple_context() {
+        let mut context = TriePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+
+        assert_eq!(context.add_phrase("perform"), Err(SimpleContextCodes::IncompleteVersionExists));
+        assert_eq!(context.add_phrase("perform_task_now"), Err(SimpleContextCodes::CompleteVersionExists));
+        assert_eq!(context.get_phrase_status("perform_task"), PhraseStatus::Complete);
+
+        context.set_overlap_policy(OverlapPolicy::AllowCompletePrefix);
+        context.add_phrase("perform").unwrap();
+        assert_eq!(context.get_phrase_status("perform"), PhraseStatus::CompleteAndExtendable);
+    }
+
+    #[test]
+    fn matches_simple_context() {
+        let mut simple = SimplePhraseContext::new();
+        simple.add_phrases(["perform_task", "perform_the_task", "halt"]).unwrap();
+        let trie = TriePhraseContext::from(&simple);
+
+        for phrase in ["perform", "perform_the", "perform_task", "halt", "stop"] {
+            assert_eq!(trie.get_phrase_status(phrase), simple.get_phrase_status(phrase));
+        }
+        assert_eq!(trie.completions("perform"), simple.completions("perform"));
+        assert_eq!(PhraseContext::fingerprint(&trie), PhraseContext::fingerprint(&simple));
+        assert_eq!(trie.phrases(), vec!["halt", "perform_task", "perform_the_task"]);
+    }
+
+    #[test]
+    fn reduce_with_trie() {
+        let parsed = parse(&lex("perform 5 task").unwrap()).unwrap();
+        let mut simple = SimplePhraseContext::new();
+        simple.add_phrase("perform_task").unwrap();
+        let mut trie = TriePhraseContext::new();
+        trie.add_phrase("perform_task").unwrap();
+
+        assert_eq!(crate::reduce_phrases(&parsed, &trie).unwrap(), crate::reduce_phrases(&parsed, &simple).unwrap());
+    }
+}
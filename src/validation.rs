@@ -0,0 +1,56 @@
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+use garnish_lang_compiler::parse::ParseResult;
+
+use crate::span::Span;
+
+// phrase whose words have all matched, before anything is rewritten
+// node indices refer to the parse result given alongside
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MatchedPhrase {
+    pub phrase: String,
+    // in source order
+    pub words: Vec<usize>,
+    // in source order, before ArgumentOrder is applied
+    pub arguments: Vec<usize>,
+    pub span: Span,
+}
+
+// semantic check gating a rewrite, e.g. that a literal argument is in range
+pub trait MatchValidator {
+    // false leaves the phrase as written like one matched where its meta doesn't allow
+    fn accept(&self, matched: &MatchedPhrase, result: &ParseResult) -> bool;
+}
+
+impl<F: Fn(&MatchedPhrase, &ParseResult) -> bool> MatchValidator for F {
+    fn accept(&self, matched: &MatchedPhrase, result: &ParseResult) -> bool {
+        self(matched, result)
+    }
+}
+
+#[derive(Clone, Default)]
+pub enum Validation {
+    // every match is rewritten
+    #[default]
+    None,
+    Custom(Arc<dyn MatchValidator + Send + Sync>),
+}
+
+impl Debug for Validation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Validation::None => write!(f, "None"),
+            Validation::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
+impl Validation {
+    pub(crate) fn accepts(&self, matched: &MatchedPhrase, result: &ParseResult) -> bool {
+        match self {
+            Validation::None => true,
+            Validation::Custom(validator) => validator.accept(matched, result),
+        }
+    }
+}
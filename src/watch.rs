@@ -0,0 +1,194 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+
+use crate::context::{PhraseContext, PhraseStatus, SimplePhraseContext};
+use crate::dictionary::{Dictionary, DictionaryError};
+use crate::meta::PhraseMeta;
+
+// handle to a context that can be replaced while other threads use it
+// clones share the same context
+// a reduction can see a swap part way through, reduce against load() for a fixed snapshot
+#[derive(Debug, Clone, Default)]
+pub struct SwappableContext {
+    current: Arc<RwLock<Arc<SimplePhraseContext>>>,
+}
+
+impl SwappableContext {
+    pub fn new(context: SimplePhraseContext) -> Self {
+        SwappableContext { current: Arc::new(RwLock::new(Arc::new(context))) }
+    }
+
+    pub fn load(&self) -> Arc<SimplePhraseContext> {
+        // a panicking writer can't leave a half built context, the swap is a single assignment
+        self.current.read().unwrap_or_else(|error| error.into_inner()).clone()
+    }
+
+    // returns the replaced context
+    pub fn swap(&self, context: SimplePhraseContext) -> Arc<SimplePhraseContext> {
+        let mut current = self.current.write().unwrap_or_else(|error| error.into_inner());
+        std::mem::replace(&mut *current, Arc::new(context))
+    }
+}
+
+impl PhraseContext for SwappableContext {
+    fn get_phrase_status(&self, s: &str) -> PhraseStatus {
+        self.load().get_phrase_status(s)
+    }
+
+    fn get_phrase_meta(&self, s: &str) -> Option<PhraseMeta> {
+        self.load().get_phrase_meta(s)
+    }
+
+    fn get_versioned_meta(&self, s: &str, version: &str) -> Option<PhraseMeta> {
+        self.load().get_versioned_meta(s, version)
+    }
+
+    fn get_overloads(&self, s: &str) -> Vec<PhraseMeta> {
+        self.load().get_overloads(s)
+    }
+
+    fn completions(&self, prefix: &str) -> Vec<String> {
+        self.load().completions(prefix)
+    }
+
+    fn fingerprint(&self) -> Option<u64> {
+        Some(SimplePhraseContext::fingerprint(&self.load()))
+    }
+
+    fn separator(&self) -> Option<String> {
+        self.load().separator()
+    }
+
+    fn free_order_phrase(&self, s: &str) -> Option<String> {
+        self.load().free_order_phrase(s)
+    }
+
+    fn alias_target(&self, s: &str) -> Option<String> {
+        self.load().alias_target(s)
+    }
+
+    // phrases of the context loaded when called, a later swap doesn't change them
+    fn iter_phrases(&self) -> Box<dyn Iterator<Item = String> + '_> {
+        Box::new(self.load().iter_phrases().collect::<Vec<String>>().into_iter())
+    }
+}
+
+// polls a json dictionary file and swaps a rebuilt context in when it changes
+// a file that fails to load leaves the previous context in place
+// only the watched file is polled, edits to files it includes are picked up with its next change
+// stops when dropped
+#[derive(Debug)]
+pub struct DictionaryWatcher {
+    stop: Arc<AtomicBool>,
+    last_error: Arc<Mutex<Option<String>>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl DictionaryWatcher {
+    // loads the file into context once before watching, failing if that load does
+    pub fn watch(path: impl Into<PathBuf>, context: SwappableContext, interval: Duration) -> Result<Self, DictionaryError> {
+        let path = path.into();
+        let mut version = file_version(&path);
+        context.swap(Dictionary::load_file(&path)?);
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let last_error = Arc::new(Mutex::new(None));
+
+        let thread = {
+            let stop = stop.clone();
+            let last_error = last_error.clone();
+            thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    thread::sleep(interval);
+
+                    let current = file_version(&path);
+                    if current == version {
+                        continue;
+                    }
+                    version = current;
+
+                    let result = Dictionary::load_file(&path).map(|loaded| {
+                        context.swap(loaded);
+                    });
+                    *last_error.lock().unwrap_or_else(|error| error.into_inner()) = result.err().map(|error| error.to_string());
+                }
+            })
+        };
+
+        Ok(DictionaryWatcher { stop, last_error, thread: Some(thread) })
+    }
+
+    // message of the most recent failed reload, cleared by a successful one
+    pub fn last_error(&self) -> Option<String> {
+        self.last_error.lock().unwrap_or_else(|error| error.into_inner()).clone()
+    }
+}
+
+impl Drop for DictionaryWatcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn file_version(path: &PathBuf) -> Option<(SystemTime, u64)> {
+    fs::metadata(path).ok().and_then(|metadata| Some((metadata.modified().ok()?, metadata.len())))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use crate::context::{PhraseContext, PhraseStatus, SimplePhraseContext};
+    use crate::watch::{DictionaryWatcher, SwappableContext};
+
+    #[test]
+    fn swap_replaces_context() {
+        let handle = SwappableContext::new(SimplePhraseContext::new());
+
+        let mut context = SimplePhraseContext::new();
+        context.add_phrase("perform_task").unwrap();
+        handle.clone().swap(context);
+
+        assert_eq!(handle.get_phrase_status("perform_task"), PhraseStatus::Complete);
+    }
+
+    #[test]
+    fn reloads_changed_file() {
+        let path = std::env::temp_dir().join(format!("garnish_phrases_watch_{}.json", std::process::id()));
+        fs::write(&path, r#"{ "version": 1, "phrases": [{ "phrase": "perform_task" }] }"#).unwrap();
+
+        let handle = SwappableContext::default();
+        let watcher = DictionaryWatcher::watch(&path, handle.clone(), Duration::from_millis(10)).unwrap();
+        assert_eq!(handle.get_phrase_status("perform_task"), PhraseStatus::Complete);
+
+        fs::write(&path, r#"{ "version": 1, "phrases": [{ "phrase": "double_the_value" }] }"#).unwrap();
+
+        let start = Instant::now();
+        while handle.get_phrase_status("perform_task") != PhraseStatus::NotAPhrase && start.elapsed() < Duration::from_secs(5) {
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        assert_eq!(handle.get_phrase_status("double_the_value"), PhraseStatus::Complete);
+        assert_eq!(handle.get_phrase_status("perform_task"), PhraseStatus::NotAPhrase);
+        assert_eq!(watcher.last_error(), None);
+
+        drop(watcher);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn initial_load_error() {
+        let path = std::env::temp_dir().join("garnish_phrases_watch_missing.json");
+
+        assert!(DictionaryWatcher::watch(&path, SwappableContext::default(), Duration::from_millis(10)).is_err());
+    }
+}